@@ -0,0 +1,435 @@
+use crate::error::{ClientError, RequestError, RequestErrorDetails};
+use crate::extensions::reqwest::to_header_map;
+use crate::transport::{send_within, HttpMethod, HttpRequest, HttpResponse, HttpTransport};
+use crate::Headers;
+use algonaut_model::algod::v2::NodeStatus;
+use async_trait::async_trait;
+use futures::future::join_all;
+use reqwest::Url;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Rounds a node can be behind the most advanced node without being considered lagging.
+const MAX_LAG_ROUNDS: u64 = 2;
+
+/// Maximum time to wait for the health and status of a node, before considering it unhealthy.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Sends the requests to the preferred healthy node, failing over to the next one after a
+/// connection error, a timeout or a 5xx response.
+///
+/// Requests which aren't idempotent (e.g. broadcasting a transaction) fail over only after a
+/// connection error: after a timeout or a 5xx response the node may have handled them.
+///
+/// The requests are built for the url of the first endpoint and sent to the url of the node.
+#[derive(Debug)]
+pub(crate) struct FailoverTransport {
+    endpoints: Vec<Endpoint>,
+    transport: Arc<dyn HttpTransport>,
+    /// Endpoint indices, from the preferred one: healthy, then lagging, then unhealthy.
+    order: Mutex<Vec<usize>>,
+    /// Whether the order has to be determined again before the next request.
+    stale: AtomicBool,
+    /// Timeout of a request to a node, if any.
+    timeout: Mutex<Option<Duration>>,
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    url: String,
+    headers: Vec<(String, String)>,
+}
+
+/// The state of a node, from the best one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum NodeState {
+    Healthy,
+    Lagging,
+    Unhealthy,
+}
+
+impl FailoverTransport {
+    /// Returns the transport and the url the requests have to be built with.
+    pub(crate) fn new(
+        endpoints: Vec<(&str, Headers)>,
+        transport: Arc<dyn HttpTransport>,
+    ) -> Result<(FailoverTransport, String), ClientError> {
+        let endpoints = endpoints
+            .into_iter()
+            .map(|(url, headers)| {
+                let headers = to_header_map(headers)?
+                    .iter()
+                    .filter_map(|(name, value)| {
+                        Some((name.to_string(), value.to_str().ok()?.to_owned()))
+                    })
+                    .collect();
+                Ok(Endpoint {
+                    url: Url::parse(url)?.as_ref().into(),
+                    headers,
+                })
+            })
+            .collect::<Result<Vec<_>, ClientError>>()?;
+        let base_url = endpoints
+            .first()
+            .ok_or_else(|| ClientError::BadUrl("No endpoints".to_owned()))?
+            .url
+            .clone();
+        let order = (0..endpoints.len()).collect();
+        Ok((
+            FailoverTransport {
+                endpoints,
+                transport,
+                order: Mutex::new(order),
+                stale: AtomicBool::new(true),
+                timeout: Mutex::new(None),
+            },
+            base_url,
+        ))
+    }
+
+    /// Sets the timeout of a request to a node, after which the next node is tried.
+    pub(crate) fn set_timeout(&self, timeout: Option<Duration>) {
+        *self.timeout.lock().unwrap() = timeout;
+    }
+
+    /// Orders the nodes by their health and last round, keeping the order of the endpoints
+    /// for nodes in the same state.
+    pub(crate) async fn check_endpoints(&self) {
+        let statuses = join_all((0..self.endpoints.len()).map(|i| self.node_status(i))).await;
+        let last_round = statuses.iter().flatten().map(|s| s.last_round).max();
+        let state = |status: &Option<NodeStatus>| match (status, last_round) {
+            (Some(status), Some(last_round)) if status.last_round + MAX_LAG_ROUNDS < last_round => {
+                NodeState::Lagging
+            }
+            (Some(_), _) => NodeState::Healthy,
+            (None, _) => NodeState::Unhealthy,
+        };
+        let mut order: Vec<usize> = (0..self.endpoints.len()).collect();
+        order.sort_by_key(|i| state(&statuses[*i]));
+        *self.order.lock().unwrap() = order;
+        self.stale.store(false, Ordering::SeqCst);
+    }
+
+    /// The status of the node, if it's healthy and not catching up.
+    async fn node_status(&self, index: usize) -> Option<NodeStatus> {
+        let timeout = match *self.timeout.lock().unwrap() {
+            Some(timeout) => timeout.min(PROBE_TIMEOUT),
+            None => PROBE_TIMEOUT,
+        };
+        let health = self
+            .send_to(index, self.request("health"), Some(timeout))
+            .await
+            .ok()?;
+        if health.status != 200 {
+            return None;
+        }
+        let status = self
+            .send_to(index, self.request("v2/status"), Some(timeout))
+            .await
+            .ok()?;
+        let status: NodeStatus = match status.status {
+            200 => serde_json::from_slice(&status.body).ok()?,
+            _ => return None,
+        };
+        let catching_up = status.catchup_time > 0
+            || status.catchpoint.iter().any(|c| !c.is_empty())
+            || status.stopped_at_unsupported_round;
+        if catching_up {
+            None
+        } else {
+            Some(status)
+        }
+    }
+
+    fn request(&self, path: &str) -> HttpRequest {
+        HttpRequest {
+            method: HttpMethod::Get,
+            url: format!("{}{}", self.endpoints[0].url, path),
+            headers: vec![],
+            body: vec![],
+        }
+    }
+
+    /// Sends the request, built for the url of the first endpoint, to the node.
+    async fn send_to(
+        &self,
+        index: usize,
+        mut request: HttpRequest,
+        timeout: Option<Duration>,
+    ) -> Result<HttpResponse, ClientError> {
+        let endpoint = &self.endpoints[index];
+        if let Some(path) = request.url.strip_prefix(&self.endpoints[0].url) {
+            request.url = format!("{}{}", endpoint.url, path);
+        }
+        for (name, value) in &endpoint.headers {
+            request
+                .headers
+                .retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+            request.headers.push((name.clone(), value.clone()));
+        }
+        send_within(self.transport.as_ref(), request, timeout).await
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl HttpTransport for FailoverTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, ClientError> {
+        if self.stale.load(Ordering::SeqCst) {
+            self.check_endpoints().await;
+        }
+        let order = self.order.lock().unwrap().clone();
+        let timeout = *self.timeout.lock().unwrap();
+        let idempotent = request.method == HttpMethod::Get;
+        let mut result = None;
+        for index in order {
+            let response = self.send_to(index, request.clone(), timeout).await;
+            if !is_failure(&response) {
+                return response;
+            }
+            // a node failed: the order is checked again before the next request
+            self.stale.store(true, Ordering::SeqCst);
+            if !idempotent && !is_connection_error(&response) {
+                return response;
+            }
+            result = Some(response);
+        }
+        result.unwrap_or_else(|| Err(ClientError::BadUrl("No endpoints".to_owned())))
+    }
+}
+
+/// Whether the node couldn't handle the request, so that another node may.
+fn is_failure(response: &Result<HttpResponse, ClientError>) -> bool {
+    match response {
+        Ok(response) => response.status >= 500,
+        Err(ClientError::Request(RequestError { details, .. })) => matches!(
+            details,
+            RequestErrorDetails::Connection { .. } | RequestErrorDetails::Timeout
+        ),
+        Err(_) => false,
+    }
+}
+
+/// Whether the request couldn't be sent, so that the node didn't handle it.
+fn is_connection_error(response: &Result<HttpResponse, ClientError>) -> bool {
+    matches!(
+        response,
+        Err(ClientError::Request(RequestError {
+            details: RequestErrorDetails::Connection { .. },
+            ..
+        }))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algod::v2::Client;
+    use crate::retry::RetryPolicy;
+    use futures::future;
+
+    #[derive(Debug, Clone)]
+    struct Node {
+        healthy: bool,
+        last_round: u64,
+        /// Response status of the requests other than the checks.
+        status: u16,
+        /// Whether the node never answers the checks.
+        checks_hang: bool,
+        /// Whether the node never answers the requests other than the checks.
+        requests_hang: bool,
+    }
+
+    /// Simulates the nodes, by host, recording the requests (other than the checks).
+    #[derive(Debug)]
+    struct NodesTransport {
+        nodes: Vec<(&'static str, Node)>,
+        requests: Mutex<Vec<HttpRequest>>,
+    }
+
+    #[async_trait]
+    impl HttpTransport for NodesTransport {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse, ClientError> {
+            let url = Url::parse(&request.url).unwrap();
+            let host = url.host_str().unwrap();
+            let node = match self.nodes.iter().find(|(h, _)| *h == host) {
+                Some((_, node)) => node,
+                None => {
+                    return Err(ClientError::Request(RequestError::new(
+                        Some(request.url.clone()),
+                        RequestErrorDetails::Connection {
+                            description: "unreachable".to_owned(),
+                        },
+                    )))
+                }
+            };
+            let check = matches!(url.path(), "/health" | "/v2/status");
+            if (check && node.checks_hang) || (!check && node.requests_hang) {
+                future::pending::<()>().await;
+            }
+            let (status, body) = match url.path() {
+                "/health" if node.healthy => (200, vec![]),
+                "/health" => (503, vec![]),
+                "/v2/status" => (200, status_json(node.last_round)),
+                "/v2/transactions" => {
+                    self.requests.lock().unwrap().push(request);
+                    let body = serde_json::json!({ "txId": host }).to_string();
+                    (node.status, body.into_bytes())
+                }
+                _ => {
+                    self.requests.lock().unwrap().push(request);
+                    (node.status, host.as_bytes().to_vec())
+                }
+            };
+            Ok(HttpResponse {
+                status,
+                headers: vec![],
+                body,
+            })
+        }
+    }
+
+    fn status_json(last_round: u64) -> Vec<u8> {
+        serde_json::json!({
+            "catchup-time": 0,
+            "last-round": last_round,
+            "last-version": "v1",
+            "next-version": "v1",
+            "next-version-round": last_round + 1,
+            "next-version-supported": true,
+            "stopped-at-unsupported-round": false,
+            "time-since-last-round": 0,
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    fn node(healthy: bool, last_round: u64, status: u16) -> Node {
+        Node {
+            healthy,
+            last_round,
+            status,
+            checks_hang: false,
+            requests_hang: false,
+        }
+    }
+
+    fn failover_client(nodes: Vec<(&'static str, Node)>) -> (Client, Arc<NodesTransport>) {
+        let transport = Arc::new(NodesTransport {
+            nodes,
+            requests: Mutex::new(vec![]),
+        });
+        let client = Client::with_endpoints(
+            vec![
+                ("http://a", vec![("X-Algo-API-Token", "a")]),
+                ("http://b", vec![("X-Algo-API-Token", "b")]),
+                ("http://c/", vec![("X-Algo-API-Token", "c")]),
+            ],
+            transport.clone(),
+        )
+        .unwrap();
+        (client, transport)
+    }
+
+    #[tokio::test]
+    async fn test_prefer_healthy_synced_node() {
+        let (client, transport) = failover_client(vec![
+            ("a", node(true, 10, 200)),
+            ("b", node(false, 20, 200)),
+            ("c", node(true, 20, 200)),
+        ]);
+
+        assert_eq!("c", client.metrics().await.unwrap());
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!("http://c/metrics", requests[0].url);
+        assert_eq!(
+            vec![("x-algo-api-token".to_owned(), "c".to_owned())],
+            requests[0].headers
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failover() {
+        let (client, _) =
+            failover_client(vec![("a", node(true, 20, 500)), ("c", node(true, 19, 200))]);
+        assert_eq!("c", client.metrics().await.unwrap());
+
+        let (client, _) = failover_client(vec![("a", node(true, 20, 500))]);
+        assert!(client.metrics().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_timeouts() {
+        let timeout = RetryPolicy::none().timeout(Duration::from_millis(50));
+
+        // the node doesn't answer the checks: it's tried last
+        let (client, transport) = failover_client(vec![
+            (
+                "a",
+                Node {
+                    checks_hang: true,
+                    ..node(true, 20, 200)
+                },
+            ),
+            ("b", node(true, 20, 200)),
+        ]);
+        let client = client.with_retry_policy(timeout.clone());
+        assert_eq!("b", client.metrics().await.unwrap());
+        assert_eq!(1, transport.requests.lock().unwrap().len());
+
+        // the node doesn't answer the request: the next one is tried
+        let (client, _) = failover_client(vec![
+            (
+                "a",
+                Node {
+                    requests_hang: true,
+                    ..node(true, 20, 200)
+                },
+            ),
+            ("b", node(true, 20, 200)),
+        ]);
+        let client = client.with_retry_policy(timeout);
+        assert_eq!("b", client.metrics().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_no_failover_of_broadcast() {
+        // the node may have handled the transaction
+        let (client, transport) =
+            failover_client(vec![("a", node(true, 20, 500)), ("b", node(true, 20, 200))]);
+        assert!(client.broadcast_raw_transaction(&[1, 2]).await.is_err());
+        assert_eq!(
+            vec!["http://a/v2/transactions"],
+            urls(&transport.requests.lock().unwrap())
+        );
+
+        // the node didn't receive the transaction
+        let (client, transport) = failover_client(vec![("b", node(true, 20, 200))]);
+        assert_eq!(
+            "b",
+            client
+                .broadcast_raw_transaction(&[1, 2])
+                .await
+                .unwrap()
+                .tx_id
+        );
+        assert_eq!(
+            vec!["http://b/v2/transactions"],
+            urls(&transport.requests.lock().unwrap())
+        );
+    }
+
+    fn urls(requests: &[HttpRequest]) -> Vec<&str> {
+        requests.iter().map(|r| r.url.as_str()).collect()
+    }
+
+    #[test]
+    fn test_no_endpoints() {
+        assert!(Client::with_endpoints(
+            vec![],
+            Arc::new(crate::transport::ReqwestTransport::new())
+        )
+        .is_err());
+    }
+}
@@ -1,32 +1,132 @@
-use crate::error::ClientError;
-use crate::extensions::reqwest::{to_header_map, ResponseExt};
+use crate::error::{ClientError, RequestError, RequestErrorDetails};
+use crate::extensions::reqwest::to_header_map;
+use crate::retry::RetryPolicy;
+use crate::transport::{HttpClient, HttpTransport, ReqwestTransport};
 use crate::Headers;
 use algonaut_core::{Address, Round};
+use algonaut_crypto::merkle::HashType;
+use algonaut_model::algod::v2::raw::AccountData;
 use algonaut_model::algod::v2::{
-    Account, ApiCompiledTeal, Application, Asset, Block, Catchup, DryrunRequest, DryrunResponse,
-    GenesisBlock, KeyRegistration, NodeStatus, PendingTransaction, PendingTransactions, Supply,
-    TransactionParams, TransactionResponse, Version,
+    Account, ApiCompiledTeal, Application, Asset, BoxValue, Boxes, Catchup, DryrunRequest,
+    DryrunResponse, GenesisBlock, KeyRegistration, NodeStatus, Supply, TransactionParams,
+    TransactionProof, TransactionResponse, Version,
 };
+use data_encoding::BASE64;
+use failover::FailoverTransport;
 use reqwest::header::HeaderMap;
 use reqwest::Url;
+use std::sync::Arc;
+
+mod failover;
+mod rejection;
+pub use rejection::TransactionRejection;
+
+/// The encoding of the responses, for the endpoints supporting both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseFormat {
+    Json,
+    /// Byte exact and faster to decode.
+    MessagePack,
+}
+
+impl Default for ResponseFormat {
+    fn default() -> Self {
+        ResponseFormat::Json
+    }
+}
+
+impl ResponseFormat {
+    fn query(self) -> [(&'static str, &'static str); 1] {
+        match self {
+            ResponseFormat::Json => [("format", "json")],
+            ResponseFormat::MessagePack => [("format", "msgpack")],
+        }
+    }
+}
 
 #[derive(Debug)]
 /// Client for interacting with the Algorand protocol daemon
 pub struct Client {
     url: String,
     headers: HeaderMap,
-    http_client: reqwest::Client,
+    http_client: HttpClient,
+    /// Set if the client has several endpoints.
+    failover: Option<Arc<FailoverTransport>>,
+    format: ResponseFormat,
 }
 
 impl Client {
     pub fn new(url: &str, headers: Headers) -> Result<Client, ClientError> {
+        Self::with_transport(url, headers, Arc::new(ReqwestTransport::new()))
+    }
+
+    /// Sends the requests with a custom transport.
+    pub fn with_transport(
+        url: &str,
+        headers: Headers,
+        transport: Arc<dyn HttpTransport>,
+    ) -> Result<Client, ClientError> {
         Ok(Client {
             url: Url::parse(url)?.as_ref().into(),
             headers: to_header_map(headers)?,
-            http_client: reqwest::Client::new(),
+            http_client: HttpClient::new(transport),
+            failover: None,
+            format: ResponseFormat::default(),
+        })
+    }
+
+    /// Sends the requests to the preferred healthy node of the endpoints (url and headers),
+    /// failing over to the next one after a connection error, a timeout or a 5xx response.
+    pub fn with_endpoints(
+        endpoints: Vec<(&str, Headers)>,
+        transport: Arc<dyn HttpTransport>,
+    ) -> Result<Client, ClientError> {
+        let (failover, url) = FailoverTransport::new(endpoints, transport)?;
+        let failover = Arc::new(failover);
+        Ok(Client {
+            url,
+            headers: HeaderMap::new(),
+            http_client: HttpClient::new(failover.clone()),
+            failover: Some(failover),
+            format: ResponseFormat::default(),
         })
     }
 
+    /// Requests the account and application information in the format: json by default.
+    ///
+    /// The pending transactions and the blocks are always requested as msgpack.
+    pub fn with_format(mut self, format: ResponseFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Orders the endpoints by the health, catchup state and last round of their nodes.
+    ///
+    /// Done before the first request and after a node fails: call it periodically to detect
+    /// nodes falling behind. Does nothing if the client has a single endpoint.
+    pub async fn check_endpoints(&self) {
+        if let Some(failover) = &self.failover {
+            failover.check_endpoints().await;
+        }
+    }
+
+    /// Retries the failed requests as allowed by the policy: by default they aren't retried.
+    ///
+    /// With several endpoints, the timeout applies to the request to each node.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        match &self.failover {
+            Some(failover) => {
+                failover.set_timeout(retry_policy.timeout);
+                self.http_client.set_retry_policy(RetryPolicy {
+                    timeout: None,
+                    ..retry_policy
+                });
+            }
+            None => self.http_client.set_retry_policy(retry_policy),
+        }
+        self
+    }
+
     pub async fn genesis(&self) -> Result<GenesisBlock, ClientError> {
         let response = self
             .http_client
@@ -34,10 +134,8 @@ impl Client {
             .headers(self.headers.clone())
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -49,8 +147,7 @@ impl Client {
             .headers(self.headers.clone())
             .send()
             .await?
-            .http_error_for_status()
-            .await?;
+            .http_error_for_status()?;
 
         Ok(())
     }
@@ -62,10 +159,8 @@ impl Client {
             .headers(self.headers.clone())
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .text()
-            .await?;
+            .http_error_for_status()?
+            .text();
 
         Ok(response)
     }
@@ -75,21 +170,29 @@ impl Client {
             .http_client
             .get(&format!("{}v2/accounts/{}", self.url, address))
             .headers(self.headers.clone())
+            .query(&self.format.query())
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
-
-        Ok(response)
+            .http_error_for_status()?;
+
+        if response.is_msgpack() {
+            // The node encodes the account record, with other keys than the json model.
+            let account: AccountData = response.msgpack()?;
+            let address = address.parse::<Address>().map_err(|e| {
+                RequestError::new(None, RequestErrorDetails::Client { description: e })
+            })?;
+            Ok(account.into_account(address))
+        } else {
+            response.json()
+        }
     }
 
+    /// Returns the msgpack encoded pending transactions of the address.
     pub async fn pending_transactions_for(
         &self,
         address: &str,
         max: u64,
-    ) -> Result<PendingTransactions, ClientError> {
+    ) -> Result<Vec<u8>, ClientError> {
         let response = self
             .http_client
             .get(&format!(
@@ -97,13 +200,11 @@ impl Client {
                 self.url, address,
             ))
             .headers(self.headers.clone())
-            .query(&[("max", max.to_string())])
+            .query(&[("max", max.to_string()), ("format", "msgpack".to_owned())])
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .bytes();
         Ok(response)
     }
 
@@ -112,12 +213,42 @@ impl Client {
             .http_client
             .get(&format!("{}v2/applications/{}", self.url, id))
             .headers(self.headers.clone())
+            .query(&self.format.query())
             .send()
             .await?
-            .http_error_for_status()
+            .http_error_for_status()?
+            .decode()?;
+
+        Ok(response)
+    }
+
+    pub async fn application_boxes(&self, id: u64, max: Option<u64>) -> Result<Boxes, ClientError> {
+        let mut request = self
+            .http_client
+            .get(&format!("{}v2/applications/{}/boxes", self.url, id))
+            .headers(self.headers.clone());
+        if let Some(max) = max {
+            request = request.query(&[("max", max.to_string())]);
+        }
+        let response = request.send().await?.http_error_for_status()?.json()?;
+
+        Ok(response)
+    }
+
+    pub async fn application_box_by_name(
+        &self,
+        id: u64,
+        name: &[u8],
+    ) -> Result<BoxValue, ClientError> {
+        let response = self
+            .http_client
+            .get(&format!("{}v2/applications/{}/box", self.url, id))
+            .headers(self.headers.clone())
+            .query(&[("name", format!("b64:{}", BASE64.encode(name)))])
+            .send()
             .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -129,25 +260,45 @@ impl Client {
             .headers(self.headers.clone())
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
 
-    pub async fn block(&self, round: Round) -> Result<Block, ClientError> {
+    /// Returns the msgpack encoded block.
+    pub async fn block(&self, round: Round) -> Result<Vec<u8>, ClientError> {
         let response = self
             .http_client
             .get(&format!("{}v2/blocks/{}", self.url, round))
             .headers(self.headers.clone())
+            .query(&[("format", "msgpack")])
             .send()
             .await?
-            .http_error_for_status()
+            .http_error_for_status()?
+            .bytes();
+
+        Ok(response)
+    }
+
+    pub async fn transaction_proof(
+        &self,
+        round: Round,
+        txid: &str,
+        hash_type: HashType,
+    ) -> Result<TransactionProof, ClientError> {
+        let response = self
+            .http_client
+            .get(&format!(
+                "{}v2/blocks/{}/transactions/{}/proof",
+                self.url, round, txid
+            ))
+            .headers(self.headers.clone())
+            .query(&[("hashtype", hash_type)])
+            .send()
             .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -159,10 +310,8 @@ impl Client {
             .headers(self.headers.clone())
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -174,10 +323,8 @@ impl Client {
             .headers(self.headers.clone())
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -189,10 +336,8 @@ impl Client {
             .headers(self.headers.clone())
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -212,10 +357,8 @@ impl Client {
             .query(&params)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -227,10 +370,8 @@ impl Client {
             .query(&[("timeout", timeout.to_string())])
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(())
     }
@@ -242,10 +383,8 @@ impl Client {
             .headers(self.headers.clone())
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -260,10 +399,8 @@ impl Client {
             .headers(self.headers.clone())
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -277,10 +414,8 @@ impl Client {
             .body(teal)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -294,14 +429,17 @@ impl Client {
             .json(req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
 
+    /// Broadcasts a raw transaction.
+    ///
+    /// If a retry (after a connection error) finds the transaction already in the ledger, the
+    /// first attempt was broadcast: returns its id too. Without retry, the node's rejection is
+    /// returned as error.
     pub async fn broadcast_raw_transaction(
         &self,
         rawtxn: &[u8],
@@ -313,12 +451,32 @@ impl Client {
             .header("Content-Type", "application/x-binary")
             .body(rawtxn.to_vec())
             .send()
-            .await?
-            .http_error_for_status()
-            .await?
-            .json()
             .await?;
 
+        let retried = response.retried();
+        match response.http_error_for_status() {
+            Ok(response) => response.json(),
+            Err(e) => match already_in_ledger(&e) {
+                Some(tx_id) if retried => Ok(TransactionResponse { tx_id }),
+                _ => Err(e.into()),
+            },
+        }
+    }
+
+    /// Returns the msgpack encoded simulation result.
+    pub async fn simulate(&self, msgpack_request: &[u8]) -> Result<Vec<u8>, ClientError> {
+        let response = self
+            .http_client
+            .post(&format!("{}v2/transactions/simulate", self.url))
+            .headers(self.headers.clone())
+            .header("Content-Type", "application/msgpack")
+            .query(&[("format", "msgpack")])
+            .body(msgpack_request.to_vec())
+            .send()
+            .await?
+            .http_error_for_status()?
+            .bytes();
+
         Ok(response)
     }
 
@@ -329,44 +487,38 @@ impl Client {
             .headers(self.headers.clone())
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
 
-    pub async fn pending_transactions(&self, max: u64) -> Result<PendingTransactions, ClientError> {
+    /// Returns the msgpack encoded pending transactions.
+    pub async fn pending_transactions(&self, max: u64) -> Result<Vec<u8>, ClientError> {
         let response = self
             .http_client
             .get(&format!("{}v2/transactions/pending", self.url))
             .headers(self.headers.clone())
-            .query(&[("max", max.to_string())])
+            .query(&[("max", max.to_string()), ("format", "msgpack".to_owned())])
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .bytes();
 
         Ok(response)
     }
 
-    pub async fn pending_transaction_with_id(
-        &self,
-        txid: &str,
-    ) -> Result<PendingTransaction, ClientError> {
+    /// Returns the msgpack encoded pending transaction.
+    pub async fn pending_transaction_with_id(&self, txid: &str) -> Result<Vec<u8>, ClientError> {
         let response = self
             .http_client
             .get(&format!("{}v2/transactions/pending/{}", self.url, txid))
             .headers(self.headers.clone())
+            .query(&[("format", "msgpack")])
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .bytes();
 
         Ok(response)
     }
@@ -378,11 +530,23 @@ impl Client {
             .headers(self.headers.clone())
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
 }
+
+/// The id of the transaction, if the error is about the node already having it.
+fn already_in_ledger(error: &RequestError) -> Option<String> {
+    match &error.details {
+        RequestErrorDetails::Http {
+            status: 400,
+            message,
+        } => match TransactionRejection::parse(message)? {
+            TransactionRejection::AlreadyInLedger { txid } => Some(txid),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,238 @@
+use algonaut_core::{MicroAlgos, Round};
+
+/// Why the node rejected a transaction, parsed from the error message of the node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionRejection {
+    /// The sender tried to spend more than its balance.
+    Overspend {
+        account: String,
+        balance: Option<MicroAlgos>,
+        amount: Option<MicroAlgos>,
+    },
+    /// The balance of the account would be below its minimum balance.
+    BelowMinBalance {
+        account: String,
+        balance: MicroAlgos,
+        min_balance: MicroAlgos,
+    },
+    /// The last valid round of the transaction has passed.
+    Dead {
+        round: Round,
+        first_valid: Round,
+        last_valid: Round,
+    },
+    /// The first valid round of the transaction hasn't come yet.
+    NotYetValid {
+        round: Round,
+        first_valid: Round,
+        last_valid: Round,
+    },
+    /// The transaction was already committed (or is pending).
+    AlreadyInLedger { txid: String },
+    /// The fee is below the minimum fee (or the fee per byte) required by the network.
+    FeeTooSmall {
+        fee: MicroAlgos,
+        min_fee: MicroAlgos,
+    },
+    /// The approval program or the logic signature failed (or rejected the transaction).
+    LogicEval {
+        /// The program counter at which the evaluation failed, if known.
+        pc: Option<u64>,
+        message: String,
+    },
+    /// The transaction group is incomplete or inconsistent.
+    GroupInvalid { message: String },
+}
+
+impl TransactionRejection {
+    /// Parses the error message returned by the node when broadcasting a transaction.
+    ///
+    /// Returns `None` if the message doesn't match any known rejection.
+    pub fn parse(message: &str) -> Option<TransactionRejection> {
+        if let Some(txid) = word_after(message, "transaction already in ledger: ") {
+            return Some(TransactionRejection::AlreadyInLedger { txid });
+        }
+        if let Some(i) = message.find("logic eval error: ") {
+            let details = &message[i + "logic eval error: ".len()..];
+            return Some(TransactionRejection::LogicEval {
+                pc: number_after(details, "pc="),
+                message: details
+                    .split(". Details:")
+                    .next()
+                    .unwrap_or(details)
+                    .to_owned(),
+            });
+        }
+        if message.contains("rejected by logic") {
+            return Some(TransactionRejection::LogicEval {
+                pc: number_after(message, "pc="),
+                message: "rejected by logic".to_owned(),
+            });
+        }
+        if let Some(round) = number_after(message, "txn dead: round ") {
+            let first_valid = number_after(message, " outside of ")?;
+            let last_valid = number_after(message, &format!("outside of {}--", first_valid))?;
+            let (round, first_valid, last_valid) =
+                (Round(round), Round(first_valid), Round(last_valid));
+            return Some(if round.0 < first_valid.0 {
+                TransactionRejection::NotYetValid {
+                    round,
+                    first_valid,
+                    last_valid,
+                }
+            } else {
+                TransactionRejection::Dead {
+                    round,
+                    first_valid,
+                    last_valid,
+                }
+            });
+        }
+        if message.contains("overspend") {
+            return Some(TransactionRejection::Overspend {
+                account: word_after(message, "(account ")?,
+                balance: number_after(message, "MicroAlgos:{Raw:").map(MicroAlgos),
+                amount: number_after(message, "tried to spend {").map(MicroAlgos),
+            });
+        }
+        if message.contains(" below min ") {
+            let account = word_after(message, "account ")?;
+            return Some(TransactionRejection::BelowMinBalance {
+                balance: MicroAlgos(number_after(message, &format!("{} balance ", account))?),
+                min_balance: MicroAlgos(number_after(message, " below min ")?),
+                account,
+            });
+        }
+        if let Some(min_fee) = number_after(message, " below threshold ") {
+            return Some(TransactionRejection::FeeTooSmall {
+                fee: MicroAlgos(number_after(message, "fee ")?),
+                min_fee: MicroAlgos(min_fee),
+            });
+        }
+        if let Some(min_fee) = number_after(message, "which is less than the minimum ") {
+            return Some(TransactionRejection::FeeTooSmall {
+                fee: MicroAlgos(number_after(message, "had fee ")?),
+                min_fee: MicroAlgos(min_fee),
+            });
+        }
+        let group_errors = [
+            "incomplete group",
+            "inconsistent group values",
+            "group size",
+            "transactionGroup",
+        ];
+        if group_errors.iter().any(|e| message.contains(e)) {
+            return Some(TransactionRejection::GroupInvalid {
+                message: message.to_owned(),
+            });
+        }
+        None
+    }
+}
+
+/// The alphanumeric word following the marker.
+fn word_after(message: &str, marker: &str) -> Option<String> {
+    let start = message.find(marker)? + marker.len();
+    let word: String = message[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect();
+    Some(word).filter(|w| !w.is_empty())
+}
+
+/// The number following the marker.
+fn number_after(message: &str, marker: &str) -> Option<u64> {
+    let start = message.find(marker)? + marker.len();
+    let digits: String = message[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rejections() {
+        let account = "LF6V46HVV3XDKDEYYWOCVSUEYCQKS4JVPQW2KYUFQ5IHVCKDCMKBHWPBWM";
+        assert_eq!(
+            Some(TransactionRejection::Overspend {
+                account: account.to_owned(),
+                balance: Some(MicroAlgos(1000)),
+                amount: Some(MicroAlgos(1001000)),
+            }),
+            TransactionRejection::parse(&format!(
+                "TransactionPool.Remember: transaction TXID: overspend (account {}, data \
+                 {{_struct:{{}} Status:Offline MicroAlgos:{{Raw:1000}} RewardsBase:0}}, \
+                 tried to spend {{1001000}})",
+                account
+            ))
+        );
+        assert_eq!(
+            Some(TransactionRejection::BelowMinBalance {
+                account: account.to_owned(),
+                balance: MicroAlgos(100000),
+                min_balance: MicroAlgos(200000),
+            }),
+            TransactionRejection::parse(&format!(
+                "TransactionPool.Remember: transaction TXID: account {} balance 100000 below min \
+                 200000 (1 assets)",
+                account
+            ))
+        );
+        assert_eq!(
+            Some(TransactionRejection::Dead {
+                round: Round(1500),
+                first_valid: Round(100),
+                last_valid: Round(1100),
+            }),
+            TransactionRejection::parse(
+                "TransactionPool.Remember: txn dead: round 1500 outside of 100--1100"
+            )
+        );
+        assert_eq!(
+            Some(TransactionRejection::NotYetValid {
+                round: Round(50),
+                first_valid: Round(100),
+                last_valid: Round(1100),
+            }),
+            TransactionRejection::parse("txn dead: round 50 outside of 100--1100")
+        );
+        assert_eq!(
+            Some(TransactionRejection::AlreadyInLedger {
+                txid: "ABC234".to_owned()
+            }),
+            TransactionRejection::parse(
+                "TransactionPool.Remember: transaction already in ledger: ABC234"
+            )
+        );
+        assert_eq!(
+            Some(TransactionRejection::FeeTooSmall {
+                fee: MicroAlgos(10),
+                min_fee: MicroAlgos(1000),
+            }),
+            TransactionRejection::parse(
+                "TransactionPool.Remember: transaction TXID: fee 10 below threshold 1000 (204 bytes)"
+            )
+        );
+        assert_eq!(
+            Some(TransactionRejection::LogicEval {
+                pc: Some(12),
+                message: "assert failed pc=12".to_owned(),
+            }),
+            TransactionRejection::parse(
+                "TransactionPool.Remember: transaction TXID: logic eval error: assert failed \
+                 pc=12. Details: pc=12, opcodes=int 1; int 0; ==; assert"
+            )
+        );
+        assert_eq!(
+            Some(TransactionRejection::GroupInvalid {
+                message: "transactionGroup: incomplete group: TXID != GROUP".to_owned(),
+            }),
+            TransactionRejection::parse("transactionGroup: incomplete group: TXID != GROUP")
+        );
+        assert_eq!(None, TransactionRejection::parse("unknown error"));
+    }
+}
@@ -40,6 +40,9 @@ pub enum RequestErrorDetails {
     /// Timeout
     #[error("Timeout connecting to the server.")]
     Timeout,
+    /// The request couldn't be sent, e.g. the server is unreachable or closed the connection
+    #[error("Connection error: {}", description)]
+    Connection { description: String },
     /// Client generated errors (while e.g. building request or decoding response)
     #[error("Client error: {}", description)]
     Client { description: String },
@@ -64,6 +67,13 @@ impl From<reqwest::Error> for ClientError {
             )
         } else if error.is_timeout() {
             RequestError::new(url_str, RequestErrorDetails::Timeout)
+        } else if error.is_connect() || error.is_request() {
+            RequestError::new(
+                url_str,
+                RequestErrorDetails::Connection {
+                    description: error.to_string(),
+                },
+            )
         } else {
             RequestError::new(
                 url_str,
@@ -1,35 +1,55 @@
 use crate::error::ClientError;
-use crate::extensions::reqwest::{to_header_map, ResponseExt};
+use crate::extensions::reqwest::to_header_map;
+use crate::retry::RetryPolicy;
+use crate::transport::{HttpClient, HttpTransport, ReqwestTransport};
 use crate::Headers;
 use algonaut_core::{Address, Round};
 use algonaut_model::indexer::v2::{
-    AccountInfoResponse, AccountResponse, AccountTransactionResponse, ApplicationInfoResponse,
-    ApplicationResponse, AssetResponse, AssetTransactionResponse, AssetsInfoResponse,
-    BalancesResponse, Block, QueryAccount, QueryAccountInfo, QueryAccountTransaction,
-    QueryApplicationInfo, QueryApplications, QueryAssetTransaction, QueryAssets, QueryAssetsInfo,
-    QueryBalances, QueryTransaction, TransactionInfoResponse, TransactionResponse,
+    AccountInfoResponse, AccountResponse, AccountTransactionResponse, ApplicationBoxesResponse,
+    ApplicationInfoResponse, ApplicationResponse, AssetResponse, AssetTransactionResponse,
+    AssetsInfoResponse, BalancesResponse, Block, BoxValue, QueryAccount, QueryAccountInfo,
+    QueryAccountTransaction, QueryApplicationBoxes, QueryApplicationInfo, QueryApplications,
+    QueryAssetTransaction, QueryAssets, QueryAssetsInfo, QueryBalances, QueryTransaction,
+    TransactionInfoResponse, TransactionResponse,
 };
+use data_encoding::BASE64;
 use reqwest::header::HeaderMap;
 use reqwest::Url;
+use std::sync::Arc;
 
 /// Client interacting with the Algorand's indexer
 #[derive(Debug)]
 pub struct Client {
     pub(super) url: String,
     pub(super) headers: HeaderMap,
-    pub(super) http_client: reqwest::Client,
+    pub(super) http_client: HttpClient,
 }
 
 impl Client {
     pub fn new(url: &str, headers: Headers) -> Result<Client, ClientError> {
+        Self::with_transport(url, headers, Arc::new(ReqwestTransport::new()))
+    }
+
+    /// Sends the requests with a custom transport.
+    pub fn with_transport(
+        url: &str,
+        headers: Headers,
+        transport: Arc<dyn HttpTransport>,
+    ) -> Result<Client, ClientError> {
         Ok(Client {
             url: Url::parse(url)?.as_ref().into(),
             headers: to_header_map(headers)?,
-            http_client: reqwest::Client::new(),
+            http_client: HttpClient::new(transport),
         })
     }
 
     /// Returns Ok if healthy
+    /// Retries the failed requests as allowed by the policy: by default they aren't retried.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.http_client.set_retry_policy(retry_policy);
+        self
+    }
+
     pub async fn health(&self) -> Result<(), ClientError> {
         let _ = self
             .http_client
@@ -37,8 +57,7 @@ impl Client {
             .headers(self.headers.clone())
             .send()
             .await?
-            .http_error_for_status()
-            .await?;
+            .http_error_for_status()?;
         Ok(())
     }
 
@@ -51,10 +70,8 @@ impl Client {
             .query(query)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -72,10 +89,8 @@ impl Client {
             .query(query)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -93,10 +108,8 @@ impl Client {
             .query(query)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -113,10 +126,8 @@ impl Client {
             .query(query)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -134,10 +145,46 @@ impl Client {
             .query(query)
             .send()
             .await?
-            .http_error_for_status()
+            .http_error_for_status()?
+            .json()?;
+
+        Ok(response)
+    }
+
+    /// Get the box names of an application.
+    pub async fn application_boxes(
+        &self,
+        id: u64,
+        query: &QueryApplicationBoxes,
+    ) -> Result<ApplicationBoxesResponse, ClientError> {
+        let response = self
+            .http_client
+            .get(&format!("{}v2/applications/{}/boxes", self.url, id))
+            .headers(self.headers.clone())
+            .query(query)
+            .send()
             .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
+
+        Ok(response)
+    }
+
+    /// Get an application box by name.
+    pub async fn application_box_by_name(
+        &self,
+        id: u64,
+        name: &[u8],
+    ) -> Result<BoxValue, ClientError> {
+        let response = self
+            .http_client
+            .get(&format!("{}v2/applications/{}/box", self.url, id))
+            .headers(self.headers.clone())
+            .query(&[("name", format!("b64:{}", BASE64.encode(name)))])
+            .send()
+            .await?
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -151,10 +198,8 @@ impl Client {
             .query(query)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -172,10 +217,8 @@ impl Client {
             .query(query)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -193,10 +236,8 @@ impl Client {
             .query(query)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -214,10 +255,8 @@ impl Client {
             .query(query)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -230,10 +269,8 @@ impl Client {
             .headers(self.headers.clone())
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -250,10 +287,8 @@ impl Client {
             .query(query)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
@@ -266,10 +301,8 @@ impl Client {
             .headers(self.headers.clone())
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
 
         Ok(response)
     }
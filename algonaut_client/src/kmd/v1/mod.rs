@@ -1,4 +1,5 @@
-use crate::extensions::reqwest::ResponseExt;
+use crate::retry::RetryPolicy;
+use crate::transport::{HttpClient, HttpTransport, ReqwestTransport};
 use crate::Headers;
 use crate::{error::ClientError, extensions::reqwest::to_header_map};
 use algonaut_core::{Address, MultisigSignature};
@@ -18,24 +19,40 @@ use algonaut_model::kmd::v1::{
 };
 use reqwest::header::HeaderMap;
 use reqwest::Url;
+use std::sync::Arc;
 
 #[derive(Debug)]
 /// Client for interacting with the key management daemon
 pub struct Client {
     pub(super) address: String,
-    pub(super) http_client: reqwest::Client,
+    pub(super) http_client: HttpClient,
     pub(super) headers: HeaderMap,
 }
 
 impl Client {
     pub fn new(address: &str, headers: Headers) -> Result<Client, ClientError> {
+        Self::with_transport(address, headers, Arc::new(ReqwestTransport::new()))
+    }
+
+    /// Sends the requests with a custom transport.
+    pub fn with_transport(
+        address: &str,
+        headers: Headers,
+        transport: Arc<dyn HttpTransport>,
+    ) -> Result<Client, ClientError> {
         Ok(Client {
             address: Url::parse(address)?.as_ref().into(),
-            http_client: reqwest::Client::new(),
+            http_client: HttpClient::new(transport),
             headers: to_header_map(headers)?,
         })
     }
 
+    /// Retries the failed requests as allowed by the policy: by default they aren't retried.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.http_client.set_retry_policy(retry_policy);
+        self
+    }
+
     pub async fn versions(&self) -> Result<VersionsResponse, ClientError> {
         let response = self
             .http_client
@@ -44,10 +61,8 @@ impl Client {
             .headers(self.headers.clone())
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -59,10 +74,8 @@ impl Client {
             .headers(self.headers.clone())
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -88,10 +101,8 @@ impl Client {
             .json(&req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -112,10 +123,8 @@ impl Client {
             .json(&req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -134,10 +143,8 @@ impl Client {
             .json(&req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -156,10 +163,8 @@ impl Client {
             .json(&req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -182,10 +187,8 @@ impl Client {
             .json(&req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -204,10 +207,8 @@ impl Client {
             .json(&req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -228,10 +229,8 @@ impl Client {
             .json(&req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -252,10 +251,8 @@ impl Client {
             .json(&req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -278,10 +275,8 @@ impl Client {
             .json(&req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -301,10 +296,8 @@ impl Client {
             .json(&req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -327,10 +320,8 @@ impl Client {
             .json(&req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -346,10 +337,8 @@ impl Client {
             .json(&req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -372,10 +361,8 @@ impl Client {
             .json(&req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -394,10 +381,8 @@ impl Client {
             .json(&req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -422,10 +407,8 @@ impl Client {
             .json(&req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -446,10 +429,8 @@ impl Client {
             .json(&req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -472,10 +453,8 @@ impl Client {
             .json(&req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 
@@ -502,10 +481,8 @@ impl Client {
             .json(&req)
             .send()
             .await?
-            .http_error_for_status()
-            .await?
-            .json()
-            .await?;
+            .http_error_for_status()?
+            .json()?;
         Ok(response)
     }
 }
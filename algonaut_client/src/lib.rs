@@ -8,7 +8,11 @@ mod extensions;
 pub mod indexer;
 /// Key management daemon
 pub mod kmd;
+/// Retry policy of the clients
+pub mod retry;
 /// Api token management utils
 pub mod token;
+/// Pluggable HTTP transport of the clients
+pub mod transport;
 
 pub type Headers<'a> = Vec<(&'a str, &'a str)>;
@@ -0,0 +1,182 @@
+use crate::error::{ClientError, RequestError, RequestErrorDetails};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// When and how the failed requests of a client are retried.
+///
+/// Only idempotent (GET) requests are retried after a timeout or a retryable status:
+/// the other requests (e.g. broadcasting a transaction) are retried only after connection errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts of a request, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled after each retry.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Whether the delays are randomized (between half and all of the backoff), so that clients
+    /// failing together don't retry together.
+    pub jitter: bool,
+    /// The statuses after which idempotent requests are retried.
+    pub retryable_statuses: Vec<u16>,
+    /// Whether idempotent requests are retried after a timeout.
+    pub retry_timeouts: bool,
+    /// Timeout of each attempt, if any.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts, with a backoff from 100 ms, after server unavailability and rate limiting.
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            jitter: true,
+            retryable_statuses: vec![429, 500, 502, 503, 504],
+            retry_timeouts: true,
+            timeout: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Doesn't retry: the policy of the clients, unless set.
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn retryable_statuses(mut self, statuses: Vec<u16>) -> Self {
+        self.retryable_statuses = statuses;
+        self
+    }
+
+    pub fn retry_timeouts(mut self, retry_timeouts: bool) -> Self {
+        self.retry_timeouts = retry_timeouts;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The delay before the retry following the given (1-based) attempt.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .checked_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff));
+        if self.jitter {
+            backoff.mul_f64(0.5 + random_fraction() / 2.0)
+        } else {
+            backoff
+        }
+    }
+
+    /// Whether a request failing with this status can be retried.
+    pub(crate) fn is_retryable_status(&self, status: u16, idempotent: bool) -> bool {
+        idempotent && self.retryable_statuses.contains(&status)
+    }
+
+    /// Whether a request failing with this error (without response) can be retried.
+    pub(crate) fn is_retryable_error(&self, error: &ClientError, idempotent: bool) -> bool {
+        match error {
+            ClientError::Request(RequestError { details, .. }) => {
+                self.is_retryable(details, idempotent)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether a request failing with these details can be retried, e.g. to retry it outside of
+    /// the client. `idempotent` is false for the requests which may have effects if sent twice,
+    /// e.g. broadcasting a transaction.
+    pub fn is_retryable(&self, details: &RequestErrorDetails, idempotent: bool) -> bool {
+        match details {
+            RequestErrorDetails::Connection { .. } => true,
+            RequestErrorDetails::Timeout => idempotent && self.retry_timeouts,
+            RequestErrorDetails::Http { status, .. } => {
+                self.is_retryable_status(*status, idempotent)
+            }
+            RequestErrorDetails::Client { .. } => false,
+        }
+    }
+}
+
+/// A random number in [0, 1), good enough to spread retries.
+fn random_fraction() -> f64 {
+    // the keys of a new RandomState are different each time
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy::default()
+            .backoff(Duration::from_millis(100), Duration::from_millis(500))
+            .jitter(false);
+        let delays: Vec<_> = (1..=5).map(|attempt| policy.delay(attempt)).collect();
+        assert_eq!(
+            vec![100, 200, 400, 500, 500],
+            delays.iter().map(|d| d.as_millis()).collect::<Vec<_>>()
+        );
+        assert_eq!(Duration::from_millis(500), policy.delay(100));
+
+        let policy = policy.jitter(true);
+        for attempt in 1..=5 {
+            let delay = policy.delay(attempt);
+            assert!(delay >= delays[attempt as usize - 1] / 2);
+            assert!(delay <= delays[attempt as usize - 1]);
+        }
+    }
+
+    #[test]
+    fn test_retryable() {
+        let policy = RetryPolicy::default();
+        let error = |details| ClientError::Request(RequestError::new(None, details));
+        let connection = error(RequestErrorDetails::Connection {
+            description: "refused".to_owned(),
+        });
+        let timeout = error(RequestErrorDetails::Timeout);
+        let unavailable = error(RequestErrorDetails::Http {
+            status: 503,
+            message: "".to_owned(),
+        });
+        let not_found = error(RequestErrorDetails::Http {
+            status: 404,
+            message: "".to_owned(),
+        });
+
+        assert!(policy.is_retryable_error(&connection, false));
+        assert!(policy.is_retryable_error(&timeout, true));
+        assert!(!policy.is_retryable_error(&timeout, false));
+        assert!(policy.is_retryable_error(&unavailable, true));
+        assert!(!policy.is_retryable_error(&unavailable, false));
+        assert!(!policy.is_retryable_error(&not_found, true));
+        assert!(!policy.is_retryable_error(&ClientError::BadToken, true));
+    }
+}
@@ -0,0 +1,950 @@
+use crate::error::{ClientError, RequestError, RequestErrorDetails};
+use crate::retry::RetryPolicy;
+use async_trait::async_trait;
+use futures::future::{self, Either};
+use futures_timer::Delay;
+use reqwest::header::HeaderMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// HTTP method of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Delete,
+}
+
+/// A request of an API client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: HttpMethod,
+    /// The complete url, including the query.
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    /// Empty if the request has no body.
+    pub body: Vec<u8>,
+}
+
+/// The response to a request, whatever its status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// Sends the requests of the API clients, e.g. to record and replay them in tests or to route
+/// them through a proxy.
+///
+/// Returns an error only if there's no response (e.g. connection error or timeout):
+/// the clients handle the error statuses.
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait]
+pub trait HttpTransport: Debug + Send + Sync {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, ClientError>;
+}
+
+/// Sends the requests of the API clients, e.g. to record and replay them in tests or to route
+/// them through a proxy.
+///
+/// Returns an error only if there's no response (e.g. connection error or timeout):
+/// the clients handle the error statuses.
+#[cfg(target_arch = "wasm32")]
+#[async_trait(?Send)]
+pub trait HttpTransport: Debug {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, ClientError>;
+}
+
+/// The default transport, sending the requests with reqwest.
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new() -> ReqwestTransport {
+        Self::default()
+    }
+}
+
+/// Uses a configured client, e.g. with a custom connection pool or proxy.
+impl From<reqwest::Client> for ReqwestTransport {
+    fn from(client: reqwest::Client) -> Self {
+        ReqwestTransport { client }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl HttpTransport for ReqwestTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, ClientError> {
+        let method = match request.method {
+            HttpMethod::Get => reqwest::Method::GET,
+            HttpMethod::Post => reqwest::Method::POST,
+            HttpMethod::Delete => reqwest::Method::DELETE,
+        };
+        let mut builder = self.client.request(method, &request.url);
+        for (name, value) in &request.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        if !request.body.is_empty() {
+            builder = builder.body(request.body);
+        }
+        let response = builder.send().await?;
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+            .collect();
+        let body = response.bytes().await?.to_vec();
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+/// Builds the requests of a client and sends them with its transport.
+#[derive(Debug, Clone)]
+pub(crate) struct HttpClient {
+    transport: Arc<dyn HttpTransport>,
+    retry_policy: RetryPolicy,
+}
+
+impl HttpClient {
+    pub(crate) fn new(transport: Arc<dyn HttpTransport>) -> HttpClient {
+        HttpClient {
+            transport,
+            retry_policy: RetryPolicy::none(),
+        }
+    }
+
+    pub(crate) fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+
+    pub(crate) fn get(&self, url: &str) -> RequestBuilder<'_> {
+        self.request(HttpMethod::Get, url)
+    }
+
+    pub(crate) fn post(&self, url: &str) -> RequestBuilder<'_> {
+        self.request(HttpMethod::Post, url)
+    }
+
+    pub(crate) fn delete(&self, url: &str) -> RequestBuilder<'_> {
+        self.request(HttpMethod::Delete, url)
+    }
+
+    fn request(&self, method: HttpMethod, url: &str) -> RequestBuilder<'_> {
+        RequestBuilder {
+            client: self,
+            request: Ok(HttpRequest {
+                method,
+                url: url.to_owned(),
+                headers: vec![],
+                body: vec![],
+            }),
+        }
+    }
+
+    /// Sends the request once, within the timeout of the retry policy.
+    async fn send_once(&self, request: HttpRequest) -> Result<HttpResponse, ClientError> {
+        send_within(self.transport.as_ref(), request, self.retry_policy.timeout).await
+    }
+}
+
+/// Sends the request with the transport, failing with a timeout error if there's no response
+/// within the timeout.
+pub(crate) async fn send_within(
+    transport: &dyn HttpTransport,
+    request: HttpRequest,
+    timeout: Option<Duration>,
+) -> Result<HttpResponse, ClientError> {
+    let url = request.url.clone();
+    let send = transport.send(request);
+    match timeout {
+        Some(timeout) => match future::select(send, Delay::new(timeout)).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(ClientError::Request(RequestError::new(
+                Some(url),
+                RequestErrorDetails::Timeout,
+            ))),
+        },
+        None => send.await,
+    }
+}
+
+/// A request being built, with the first error building it, if any.
+pub(crate) struct RequestBuilder<'a> {
+    client: &'a HttpClient,
+    request: Result<HttpRequest, ClientError>,
+}
+
+impl RequestBuilder<'_> {
+    /// Sets the headers, replacing the values of headers already set.
+    pub(crate) fn headers(mut self, headers: HeaderMap) -> Self {
+        for (name, value) in &headers {
+            if let Ok(value) = value.to_str() {
+                self = self.header(name.as_str(), value);
+            }
+        }
+        self
+    }
+
+    /// Sets a header, replacing its value if it's already set.
+    pub(crate) fn header(mut self, name: &str, value: &str) -> Self {
+        if let Ok(request) = &mut self.request {
+            request
+                .headers
+                .retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+            request.headers.push((name.to_owned(), value.to_owned()));
+        }
+        self
+    }
+
+    /// Appends the url encoded parameters to the query.
+    pub(crate) fn query<T: Serialize + ?Sized>(mut self, query: &T) -> Self {
+        if let Ok(request) = &mut self.request {
+            match serde_urlencoded::to_string(query) {
+                Ok(query) if query.is_empty() => {}
+                Ok(query) => {
+                    let separator = if request.url.contains('?') { '&' } else { '?' };
+                    request.url = format!("{}{}{}", request.url, separator, query);
+                }
+                Err(e) => self.request = Err(client_error(&request.url, e)),
+            }
+        }
+        self
+    }
+
+    /// Sets the json encoded body.
+    pub(crate) fn json<T: Serialize + ?Sized>(mut self, json: &T) -> Self {
+        if let Ok(request) = &mut self.request {
+            match serde_json::to_vec(json) {
+                Ok(body) => {
+                    let has_content_type = request
+                        .headers
+                        .iter()
+                        .any(|(name, _)| name.eq_ignore_ascii_case("Content-Type"));
+                    if !has_content_type {
+                        request
+                            .headers
+                            .push(("Content-Type".to_owned(), "application/json".to_owned()));
+                    }
+                    request.body = body;
+                }
+                Err(e) => self.request = Err(client_error(&request.url, e)),
+            }
+        }
+        self
+    }
+
+    pub(crate) fn body(mut self, body: Vec<u8>) -> Self {
+        if let Ok(request) = &mut self.request {
+            request.body = body;
+        }
+        self
+    }
+
+    /// Sends the request, retrying it as allowed by the retry policy of the client.
+    pub(crate) async fn send(self) -> Result<Response, ClientError> {
+        let request = self.request?;
+        let policy = &self.client.retry_policy;
+        let idempotent = request.method == HttpMethod::Get;
+        let mut attempt = 1;
+        loop {
+            let result = self.client.send_once(request.clone()).await;
+            let retry = attempt < policy.max_attempts
+                && match &result {
+                    Ok(response) => policy.is_retryable_status(response.status, idempotent),
+                    Err(e) => policy.is_retryable_error(e, idempotent),
+                };
+            if !retry {
+                let response = result?;
+                return Ok(Response {
+                    url: request.url,
+                    response,
+                    attempts: attempt,
+                });
+            }
+            Delay::new(policy.delay(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// A response, with the url of its request.
+pub(crate) struct Response {
+    url: String,
+    response: HttpResponse,
+    /// Attempts of the request, including the one answered.
+    attempts: u32,
+}
+
+impl Response {
+    /// Whether the request was sent again after a failed attempt.
+    pub(crate) fn retried(&self) -> bool {
+        self.attempts > 1
+    }
+
+    /// Maps error statuses to errors, with the message returned by the API, if any.
+    pub(crate) fn http_error_for_status(self) -> Result<Response, RequestError> {
+        match self.response.status {
+            200..=399 => Ok(self),
+            status => Err(RequestError::new(
+                Some(self.url),
+                RequestErrorDetails::Http {
+                    status,
+                    message: serde_json::from_slice::<HttpErrorPayload>(&self.response.body)
+                        .map(|p| p.message)
+                        .unwrap_or_else(|_| "".to_owned()),
+                },
+            )),
+        }
+    }
+
+    pub(crate) fn json<T: DeserializeOwned>(self) -> Result<T, ClientError> {
+        serde_json::from_slice(&self.response.body).map_err(|e| client_error(&self.url, e))
+    }
+
+    /// Whether the content type of the body is msgpack.
+    pub(crate) fn is_msgpack(&self) -> bool {
+        self.response.headers.iter().any(|(name, value)| {
+            name.eq_ignore_ascii_case("Content-Type") && value.contains("msgpack")
+        })
+    }
+
+    pub(crate) fn msgpack<T: DeserializeOwned>(self) -> Result<T, ClientError> {
+        rmp_serde::from_slice(&self.response.body).map_err(|e| client_error(&self.url, e))
+    }
+
+    /// Decodes the body as msgpack if that's its content type, as json otherwise.
+    pub(crate) fn decode<T: DeserializeOwned>(self) -> Result<T, ClientError> {
+        if self.is_msgpack() {
+            self.msgpack()
+        } else {
+            self.json()
+        }
+    }
+
+    pub(crate) fn text(self) -> String {
+        String::from_utf8_lossy(&self.response.body).into_owned()
+    }
+
+    pub(crate) fn bytes(self) -> Vec<u8> {
+        self.response.body
+    }
+}
+
+#[derive(Deserialize)]
+struct HttpErrorPayload {
+    message: String,
+}
+
+fn client_error(url: &str, error: impl ToString) -> ClientError {
+    ClientError::Request(RequestError::new(
+        Some(url.to_owned()),
+        RequestErrorDetails::Client {
+            description: error.to_string(),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algod::v2::{Client, ResponseFormat};
+    use crate::error::RequestErrorDetails;
+    use crate::indexer;
+    use algonaut_core::Round;
+    use algonaut_crypto::merkle::HashType;
+    use algonaut_model::indexer::v2::QueryApplicationBoxes;
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// Replays the responses in order, repeating the last one, recording the requests.
+    #[derive(Debug)]
+    struct ReplayTransport {
+        responses: Mutex<Vec<Result<HttpResponse, ClientError>>>,
+        requests: Mutex<Vec<HttpRequest>>,
+    }
+
+    #[async_trait]
+    impl HttpTransport for ReplayTransport {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse, ClientError> {
+            self.requests.lock().unwrap().push(request);
+            let mut responses = self.responses.lock().unwrap();
+            if responses.len() > 1 {
+                responses.remove(0)
+            } else {
+                responses[0].clone()
+            }
+        }
+    }
+
+    /// Never responds.
+    #[derive(Debug)]
+    struct PendingTransport;
+
+    #[async_trait]
+    impl HttpTransport for PendingTransport {
+        async fn send(&self, _: HttpRequest) -> Result<HttpResponse, ClientError> {
+            future::pending().await
+        }
+    }
+
+    fn response(status: u16, body: &[u8]) -> Result<HttpResponse, ClientError> {
+        Ok(HttpResponse {
+            status,
+            headers: vec![],
+            body: body.to_vec(),
+        })
+    }
+
+    fn connection_error() -> Result<HttpResponse, ClientError> {
+        Err(ClientError::Request(RequestError::new(
+            None,
+            RequestErrorDetails::Connection {
+                description: "connection reset".to_owned(),
+            },
+        )))
+    }
+
+    fn replay(responses: Vec<Result<HttpResponse, ClientError>>) -> Arc<ReplayTransport> {
+        Arc::new(ReplayTransport {
+            responses: Mutex::new(responses),
+            requests: Mutex::new(vec![]),
+        })
+    }
+
+    fn client(transport: Arc<dyn HttpTransport>, max_attempts: u32) -> Client {
+        Client::with_transport("http://localhost:4001", vec![], transport)
+            .unwrap()
+            .with_retry_policy(
+                RetryPolicy::default()
+                    .max_attempts(max_attempts)
+                    .backoff(Duration::from_millis(1), Duration::from_millis(1)),
+            )
+    }
+
+    fn status(result: Result<(), ClientError>) -> Option<u16> {
+        match result {
+            Err(ClientError::Request(RequestError {
+                details: RequestErrorDetails::Http { status, .. },
+                ..
+            })) => Some(status),
+            _ => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_transport() {
+        let transport = replay(vec![response(200, &[1, 2, 3])]);
+        let client = Client::with_transport(
+            "http://localhost:4001",
+            vec![("X-Algo-API-Token", "token")],
+            transport.clone(),
+        )
+        .unwrap();
+
+        let bytes = client.pending_transactions_for("address", 2).await.unwrap();
+        assert_eq!(vec![1, 2, 3], bytes);
+        assert_eq!(
+            vec![HttpRequest {
+                method: HttpMethod::Get,
+                url: "http://localhost:4001/v2/accounts/address/transactions/pending?max=2&format=msgpack"
+                    .to_owned(),
+                headers: vec![("x-algo-api-token".to_owned(), "token".to_owned())],
+                body: vec![],
+            }],
+            *transport.requests.lock().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_status() {
+        let transport = replay(vec![response(404, br#"{"message":"not found"}"#)]);
+        let client = Client::with_transport("http://localhost:4001", vec![], transport).unwrap();
+
+        match client.health().await {
+            Err(ClientError::Request(RequestError {
+                details: RequestErrorDetails::Http { status, message },
+                ..
+            })) => {
+                assert_eq!(404, status);
+                assert_eq!("not found", message);
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_idempotent_request() {
+        let responses = || vec![response(503, b""), response(503, b""), response(200, b"")];
+
+        let transport = replay(responses());
+        assert!(client(transport.clone(), 3).health().await.is_ok());
+        assert_eq!(3, transport.requests.lock().unwrap().len());
+
+        let transport = replay(responses());
+        assert_eq!(
+            Some(503),
+            status(client(transport.clone(), 2).health().await)
+        );
+        assert_eq!(2, transport.requests.lock().unwrap().len());
+
+        let transport = replay(vec![response(404, b""), response(200, b"")]);
+        assert_eq!(
+            Some(404),
+            status(client(transport.clone(), 3).health().await)
+        );
+        assert_eq!(1, transport.requests.lock().unwrap().len());
+    }
+
+    #[tokio::test]
+    async fn test_retry_broadcast() {
+        let transport = replay(vec![response(503, b""), response(200, b"")]);
+        assert!(client(transport.clone(), 3)
+            .broadcast_raw_transaction(&[1])
+            .await
+            .is_err());
+        assert_eq!(1, transport.requests.lock().unwrap().len());
+
+        let transport = replay(vec![
+            connection_error(),
+            response(
+                400,
+                br#"{"message":"TransactionPool.Remember: transaction already in ledger: ABC234"}"#,
+            ),
+        ]);
+        let broadcast = client(transport.clone(), 3)
+            .broadcast_raw_transaction(&[1])
+            .await
+            .unwrap();
+        assert_eq!("ABC234", broadcast.tx_id);
+        assert_eq!(2, transport.requests.lock().unwrap().len());
+
+        // not sent before: the node has it from someone else
+        let transport = replay(vec![response(
+            400,
+            br#"{"message":"TransactionPool.Remember: transaction already in ledger: ABC234"}"#,
+        )]);
+        assert_eq!(
+            Some(400),
+            status(
+                client(transport, 3)
+                    .broadcast_raw_transaction(&[1])
+                    .await
+                    .map(|_| ())
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_box_endpoints() {
+        let transport = replay(vec![
+            response(200, br#"{"name":"AQI=","round":12,"value":"AwQF"}"#),
+            response(200, br#"{"boxes":[{"name":"AQI="},{"name":"Bg=="}]}"#),
+        ]);
+        let algod =
+            Client::with_transport("http://localhost:4001", vec![], transport.clone()).unwrap();
+
+        let value = algod.application_box_by_name(5, &[1, 2]).await.unwrap();
+        assert_eq!(Round(12), value.round);
+        assert_eq!(vec![1, 2], value.name);
+        assert_eq!(vec![3, 4, 5], value.value);
+        let boxes = algod.application_boxes(5, Some(2)).await.unwrap();
+        assert_eq!(
+            vec![vec![1, 2], vec![6]],
+            boxes.boxes.into_iter().map(|b| b.name).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                "http://localhost:4001/v2/applications/5/box?name=b64%3AAQI%3D",
+                "http://localhost:4001/v2/applications/5/boxes?max=2",
+            ],
+            transport
+                .requests
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|r| r.url.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        let transport = replay(vec![
+            response(200, br#"{"name":"AQI=","round":12,"value":"AwQF"}"#),
+            response(
+                200,
+                br#"{"application-id":5,"boxes":[{"name":"AQI="}],"next-token":"AQI="}"#,
+            ),
+        ]);
+        let indexer =
+            indexer::v2::Client::with_transport("http://localhost:8980", vec![], transport.clone())
+                .unwrap();
+
+        let value = indexer.application_box_by_name(5, &[1, 2]).await.unwrap();
+        assert_eq!(Round(12), value.round);
+        assert_eq!(vec![3, 4, 5], value.value);
+        let boxes = indexer
+            .application_boxes(5, &QueryApplicationBoxes::new().limit(1))
+            .await
+            .unwrap();
+        assert_eq!(5, boxes.application_id);
+        assert_eq!(vec![1, 2], boxes.boxes[0].name);
+        assert_eq!(Some("AQI=".to_owned()), boxes.next_token);
+        assert_eq!(
+            vec![
+                "http://localhost:8980/v2/applications/5/box?name=b64%3AAQI%3D",
+                "http://localhost:8980/v2/applications/5/boxes?limit=1",
+            ],
+            transport
+                .requests
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|r| r.url.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transaction_proof() {
+        let txid = "XSWRB3STV4Y4VQJOGWHNNOCG2UHH5RFVM2PPNLWVWVZY7ZRERMLA";
+        let transport = replay(vec![response(
+            200,
+            br#"{"hashtype":"sha256","idx":1,"proof":"47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFVD+h8H1PeeRhXa91ZGYMyZYxLGrTcIm3Gi8tUPJ0ljTw==","stibhash":"RAa7C96aYwmu26J9PoeBFqjSoWgKABfIybqq4zBsO3g=","treedepth":2}"#,
+        )]);
+        let client =
+            Client::with_transport("http://localhost:4001", vec![], transport.clone()).unwrap();
+
+        let proof = client
+            .transaction_proof(Round(5), txid, HashType::Sha256)
+            .await
+            .unwrap();
+        assert_eq!(
+            format!(
+                "http://localhost:4001/v2/blocks/5/transactions/{}/proof?hashtype=sha256",
+                txid
+            ),
+            transport.requests.lock().unwrap()[0].url
+        );
+        assert_eq!(1, proof.index);
+        assert_eq!(2, proof.tree_depth);
+        assert_eq!(HashType::Sha256, proof.hash_type);
+        assert_eq!(64, proof.proof.len());
+        assert_eq!(
+            "RAa7C96aYwmu26J9PoeBFqjSoWgKABfIybqq4zBsO3g=",
+            proof.stib_hash.to_string()
+        );
+    }
+
+    /// Serialized as a msgpack byte array.
+    struct Bin(&'static [u8]);
+
+    impl Serialize for Bin {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+
+    #[derive(Serialize)]
+    struct ApplicationParams {
+        #[serde(rename = "approval-program")]
+        approval_program: Bin,
+        #[serde(rename = "clear-state-program")]
+        clear_state_program: Bin,
+        creator: String,
+    }
+
+    #[derive(Serialize)]
+    struct Application {
+        id: u64,
+        params: ApplicationParams,
+    }
+
+    #[tokio::test]
+    async fn test_msgpack_format() {
+        let body = rmp_serde::to_vec_named(&Application {
+            id: 5,
+            params: ApplicationParams {
+                approval_program: Bin(&[6, 129, 1]),
+                clear_state_program: Bin(&[6]),
+                creator: algonaut_core::Address([1; 32]).to_string(),
+            },
+        })
+        .unwrap();
+        let transport = replay(vec![Ok(HttpResponse {
+            status: 200,
+            headers: vec![("content-type".to_owned(), "application/msgpack".to_owned())],
+            body,
+        })]);
+        let client = Client::with_transport("http://localhost:4001", vec![], transport.clone())
+            .unwrap()
+            .with_format(ResponseFormat::MessagePack);
+
+        let application = client.application_information(5).await.unwrap();
+        assert_eq!(5, application.id);
+        assert_eq!(vec![6, 129, 1], application.params.approval_program);
+        assert_eq!(vec![6], application.params.clear_state_program);
+        assert_eq!(
+            "http://localhost:4001/v2/applications/5?format=msgpack",
+            transport.requests.lock().unwrap()[0].url
+        );
+    }
+
+    /// The account record as algod encodes it: the keys of the codec tags, sorted, and the empty
+    /// fields omitted.
+    #[derive(Serialize)]
+    struct AccountData {
+        algo: u64,
+        apar: BTreeMap<u64, AssetParams>,
+        appl: BTreeMap<u64, AppLocalState>,
+        appp: BTreeMap<u64, AppParams>,
+        asset: BTreeMap<u64, AssetHolding>,
+        ebase: u64,
+        ern: u64,
+        onl: u8,
+        sel: Bin,
+        spend: Bin,
+        tsch: StateSchema,
+        vote: Bin,
+        #[serde(rename = "voteFst")]
+        vote_first: u64,
+        #[serde(rename = "voteKD")]
+        vote_key_dilution: u64,
+        #[serde(rename = "voteLst")]
+        vote_last: u64,
+    }
+
+    #[derive(Serialize)]
+    struct AssetHolding {
+        a: u64,
+    }
+
+    #[derive(Serialize)]
+    struct AssetParams {
+        am: Bin,
+        an: &'static str,
+        dc: u64,
+        m: Bin,
+        t: u64,
+        un: &'static str,
+    }
+
+    #[derive(Serialize)]
+    struct AppLocalState {
+        hsch: StateSchema,
+        tkv: BTreeMap<&'static str, TealValue>,
+    }
+
+    #[derive(Serialize)]
+    struct AppParams {
+        approv: Bin,
+        clearp: Bin,
+        gs: BTreeMap<&'static str, TealValue>,
+        gsch: StateSchema,
+        lsch: StateSchema,
+    }
+
+    #[derive(Serialize)]
+    struct StateSchema {
+        nbs: u64,
+        nui: u64,
+    }
+
+    #[derive(Serialize)]
+    struct TealValue {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tb: Option<&'static str>,
+        tt: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        ui: Option<u64>,
+    }
+
+    #[tokio::test]
+    async fn test_msgpack_account() {
+        let mut body = rmp_serde::to_vec_named(&AccountData {
+            algo: 5_000_000,
+            apar: vec![(
+                10,
+                AssetParams {
+                    am: Bin(&[3; 32]),
+                    an: "Asset",
+                    dc: 2,
+                    m: Bin(&[2; 32]),
+                    t: 1000,
+                    un: "AST",
+                },
+            )]
+            .into_iter()
+            .collect(),
+            appl: vec![(
+                20,
+                AppLocalState {
+                    hsch: StateSchema { nbs: 1, nui: 0 },
+                    tkv: vec![(
+                        "KK",
+                        TealValue {
+                            tb: Some("VV"),
+                            tt: 1,
+                            ui: None,
+                        },
+                    )]
+                    .into_iter()
+                    .collect(),
+                },
+            )]
+            .into_iter()
+            .collect(),
+            appp: vec![(
+                30,
+                AppParams {
+                    approv: Bin(&[6, 129, 1]),
+                    clearp: Bin(&[6]),
+                    gs: vec![(
+                        "counter",
+                        TealValue {
+                            tb: None,
+                            tt: 2,
+                            ui: Some(7),
+                        },
+                    )]
+                    .into_iter()
+                    .collect(),
+                    gsch: StateSchema { nbs: 0, nui: 1 },
+                    lsch: StateSchema { nbs: 1, nui: 0 },
+                },
+            )]
+            .into_iter()
+            .collect(),
+            asset: vec![(40, AssetHolding { a: 15 })].into_iter().collect(),
+            ebase: 218,
+            ern: 12,
+            onl: 1,
+            sel: Bin(&[4; 32]),
+            spend: Bin(&[5; 32]),
+            tsch: StateSchema { nbs: 2, nui: 1 },
+            vote: Bin(&[6; 32]),
+            vote_first: 100,
+            vote_key_dilution: 10_000,
+            vote_last: 3_000_000,
+        })
+        .unwrap();
+        // The TEAL keys and byte values are Go strings, encoded as msgpack strings even if they
+        // aren't valid UTF-8: replaces the placeholders with such bytes.
+        for (placeholder, bytes) in [(b"KK", [0xff, 0x01]), (b"VV", [0xfe, 0x02])].iter() {
+            let at = body
+                .windows(3)
+                .position(|w| w[0] == 0xa2 && &w[1..] == *placeholder)
+                .unwrap();
+            body[at + 1..at + 3].copy_from_slice(bytes);
+        }
+        let address = algonaut_core::Address([1; 32]);
+        let transport = replay(vec![Ok(HttpResponse {
+            status: 200,
+            headers: vec![("content-type".to_owned(), "application/msgpack".to_owned())],
+            body,
+        })]);
+        let client = Client::with_transport("http://localhost:4001", vec![], transport.clone())
+            .unwrap()
+            .with_format(ResponseFormat::MessagePack);
+
+        let account = client
+            .account_information(&address.to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            format!(
+                "http://localhost:4001/v2/accounts/{}?format=msgpack",
+                address
+            ),
+            transport.requests.lock().unwrap()[0].url
+        );
+        assert_eq!(address, account.address);
+        assert_eq!(5_000_000, account.amount.0);
+        assert_eq!(5_000_000, account.amount_without_pending_rewards);
+        assert_eq!(0, account.pending_rewards.0);
+        assert_eq!(12, account.rewards.0);
+        assert_eq!(Some(218), account.reward_base);
+        assert_eq!(Round(0), account.round);
+        assert_eq!("Online", account.status);
+        assert_eq!(Some(algonaut_core::Address([5; 32])), account.auth_addr);
+        let participation = account.participation.unwrap();
+        assert_eq!(vec![4; 32], participation.selection_participation_key);
+        assert_eq!(vec![6; 32], participation.vote_participation_key);
+        assert_eq!(100, participation.vote_first_valid);
+        assert_eq!(3_000_000, participation.vote_last_valid);
+        assert_eq!(10_000, participation.vote_key_dilution);
+        let schema = account.apps_total_schema.unwrap();
+        assert_eq!((2, 1), (schema.num_byte_slice, schema.num_uint));
+
+        assert_eq!(1, account.assets.len());
+        assert_eq!(40, account.assets[0].asset_id);
+        assert_eq!(15, account.assets[0].amount);
+        assert_eq!(None, account.assets[0].creator);
+        assert!(!account.assets[0].is_frozen);
+
+        assert_eq!(1, account.created_assets.len());
+        let asset = &account.created_assets[0];
+        assert_eq!(10, asset.index);
+        assert_eq!(address, asset.params.creator);
+        assert_eq!(Some("Asset".to_owned()), asset.params.name);
+        assert_eq!(Some("AST".to_owned()), asset.params.unit_name);
+        assert_eq!(None, asset.params.url);
+        assert_eq!(1000, asset.params.total);
+        assert_eq!(2, asset.params.decimals);
+        assert_eq!(Some(false), asset.params.default_frozen);
+        assert_eq!(Some(algonaut_core::Address([2; 32])), asset.params.manager);
+        assert_eq!(None, asset.params.clawback);
+        assert_eq!(Some(vec![3; 32]), asset.params.metadata_hash);
+
+        assert_eq!(1, account.apps_local_state.len());
+        let local_state = &account.apps_local_state[0];
+        assert_eq!(20, local_state.id);
+        assert_eq!(1, local_state.schema.num_byte_slice);
+        assert_eq!("/wE=", local_state.key_value[0].key);
+        assert_eq!(vec![0xfe, 0x02], local_state.key_value[0].value.bytes);
+        assert_eq!(1, local_state.key_value[0].value.value_type);
+
+        assert_eq!(1, account.created_apps.len());
+        let app = &account.created_apps[0];
+        assert_eq!(30, app.id);
+        assert_eq!(address, app.params.creator);
+        assert_eq!(vec![6, 129, 1], app.params.approval_program);
+        assert_eq!(vec![6], app.params.clear_state_program);
+        assert_eq!("Y291bnRlcg==", app.params.global_state[0].key);
+        assert_eq!(7, app.params.global_state[0].value.uint);
+        assert_eq!(1, app.params.global_state_schema.as_ref().unwrap().num_uint);
+        assert_eq!(
+            1,
+            app.params
+                .local_state_schema
+                .as_ref()
+                .unwrap()
+                .num_byte_slice
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let client =
+            Client::with_transport("http://localhost:4001", vec![], Arc::new(PendingTransport))
+                .unwrap()
+                .with_retry_policy(RetryPolicy::none().timeout(Duration::from_millis(10)));
+
+        match client.health().await {
+            Err(ClientError::Request(RequestError {
+                details: RequestErrorDetails::Timeout,
+                ..
+            })) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+}
@@ -9,6 +9,15 @@ pub enum CoreError {
     General(String),
 }
 
+impl CoreError {
+    /// A stable, snake case code identifying the kind of error, e.g. for logs and metrics.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::General(_) => "core",
+        }
+    }
+}
+
 impl From<DecodeError> for CoreError {
     fn from(e: DecodeError) -> Self {
         CoreError::General(format!("Decoding error: {}", e))
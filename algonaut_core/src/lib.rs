@@ -17,7 +17,7 @@ pub use multisig::MultisigSignature;
 pub use multisig::MultisigSubsig;
 
 mod address;
-mod error;
+pub mod error;
 mod multisig;
 
 pub const MICRO_ALGO_CONVERSION_FACTOR: f64 = 1e6;
@@ -1,3 +1,4 @@
+use crate::{Address, MultisigAddress};
 use algonaut_crypto::Ed25519PublicKey;
 use algonaut_crypto::Signature;
 use serde::{Deserialize, Serialize, Serializer};
@@ -18,6 +19,16 @@ pub struct MultisigSignature {
 }
 
 impl MultisigSignature {
+    /// The multisig address with the keys, version and threshold of this signature.
+    pub fn address(&self) -> Address {
+        MultisigAddress {
+            version: self.version,
+            threshold: self.threshold,
+            public_keys: self.subsigs.iter().map(|s| s.key).collect(),
+        }
+        .address()
+    }
+
     pub fn verify(&self, message: &[u8]) -> bool {
         if self.version != MULTISIG_VERSION || self.threshold == 0 || self.subsigs.is_empty() {
             return false;
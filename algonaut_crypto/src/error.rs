@@ -13,4 +13,22 @@ pub enum CryptoError {
     InvalidWordsInMnemonic,
     #[display(fmt = "Invalid checksum.")]
     InvalidChecksum,
+    #[display(fmt = "Merkle proof length doesn't match the tree depth.")]
+    InvalidMerkleProofLength,
+    #[display(fmt = "Merkle proof doesn't match the root.")]
+    InvalidMerkleProof,
+}
+
+impl CryptoError {
+    /// A stable, snake case code identifying the kind of error, e.g. for logs and metrics.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidKeyLength => "invalid_key_length",
+            Self::InvalidMnemonicLength => "invalid_mnemonic_length",
+            Self::InvalidWordsInMnemonic => "invalid_words_in_mnemonic",
+            Self::InvalidChecksum => "invalid_checksum",
+            Self::InvalidMerkleProofLength => "invalid_merkle_proof_length",
+            Self::InvalidMerkleProof => "invalid_merkle_proof",
+        }
+    }
 }
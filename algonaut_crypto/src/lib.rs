@@ -9,11 +9,14 @@ use std::str::FromStr;
 /// Support for turning 32 byte keys into human-readable mnemonics and back
 pub mod mnemonic;
 
+/// Verification of Merkle proofs, e.g. of transactions in a block
+pub mod merkle;
+
 ///
 pub mod error;
 
 /// A SHA512_256 hash
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
 pub struct HashDigest(pub [u8; 32]);
 
 impl FromStr for HashDigest {
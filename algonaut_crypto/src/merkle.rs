@@ -0,0 +1,181 @@
+use crate::error::CryptoError;
+use crate::HashDigest;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+
+const DIGEST_SIZE: usize = 32;
+
+/// The hash function of a Merkle tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HashType {
+    #[serde(rename = "sha512_256")]
+    Sha512_256,
+    #[serde(rename = "sha256")]
+    Sha256,
+}
+
+impl HashType {
+    /// Hashes the concatenation of the domain separation prefix and the parts.
+    pub fn hash(&self, prefix: &[u8], parts: &[&[u8]]) -> HashDigest {
+        match self {
+            HashType::Sha512_256 => HashDigest(hash_parts::<sha2::Sha512_256>(prefix, parts)),
+            HashType::Sha256 => HashDigest(hash_parts::<sha2::Sha256>(prefix, parts)),
+        }
+    }
+}
+
+fn hash_parts<D: Digest>(prefix: &[u8], parts: &[&[u8]]) -> [u8; DIGEST_SIZE] {
+    let mut hasher = D::new();
+    hasher.update(prefix);
+    for part in parts {
+        hasher.update(part);
+    }
+    let mut digest = [0; DIGEST_SIZE];
+    digest.copy_from_slice(&hasher.finalize());
+    digest
+}
+
+/// Verifies the proof that a transaction is in the payset of a block, against the transactions
+/// root of the header which uses the same hash type.
+///
+/// `txid` and `stib_hash` (the hash of the signed transaction in block) are computed with the hash
+/// type too. The proof is the concatenation of the sibling hashes from the leaf up to the root, of a
+/// vector commitment: the leaves are positioned by the bit reversed index of the transaction.
+pub fn verify_transaction_proof(
+    root: &HashDigest,
+    txid: &HashDigest,
+    stib_hash: &HashDigest,
+    index: u64,
+    tree_depth: u64,
+    proof: &[u8],
+    hash_type: HashType,
+) -> Result<(), CryptoError> {
+    if tree_depth > 64 || proof.len() as u64 != tree_depth * DIGEST_SIZE as u64 {
+        return Err(CryptoError::InvalidMerkleProofLength);
+    }
+    if tree_depth < 64 && index >> tree_depth != 0 {
+        return Err(CryptoError::InvalidMerkleProof);
+    }
+    let position = vector_commitment_position(index, tree_depth);
+    let mut node = hash_type.hash(b"TL", &[&txid.0, &stib_hash.0]);
+    for (level, sibling) in proof.chunks(DIGEST_SIZE).enumerate() {
+        node = if (position >> level) & 1 == 0 {
+            hash_type.hash(b"MA", &[&node.0, sibling])
+        } else {
+            hash_type.hash(b"MA", &[sibling, &node.0])
+        };
+    }
+    if node == *root {
+        Ok(())
+    } else {
+        Err(CryptoError::InvalidMerkleProof)
+    }
+}
+
+/// The position of the leaf in the tree: the index with its `tree_depth` bits reversed.
+fn vector_commitment_position(index: u64, tree_depth: u64) -> u64 {
+    if tree_depth == 0 {
+        0
+    } else {
+        index.reverse_bits() >> (64 - tree_depth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds the tree of the leaves, returning the root and the proofs by index.
+    fn build_tree(leaves: &[HashDigest], hash_type: HashType) -> (HashDigest, Vec<Vec<u8>>) {
+        let mut depth = 0;
+        while (1 << depth) < leaves.len() {
+            depth += 1;
+        }
+        let empty = hash_type.hash(b"", &[]);
+        let mut level: Vec<HashDigest> = (0..1u64 << depth)
+            .map(|position| {
+                let index = vector_commitment_position(position, depth);
+                leaves.get(index as usize).copied().unwrap_or(empty)
+            })
+            .collect();
+        let mut proofs = vec![vec![]; leaves.len()];
+        while level.len() > 1 {
+            for (index, proof) in proofs.iter_mut().enumerate() {
+                let position = vector_commitment_position(index as u64, depth);
+                let shift = depth - level.len().trailing_zeros() as u64;
+                proof.extend_from_slice(&level[((position >> shift) ^ 1) as usize].0);
+            }
+            level = level
+                .chunks(2)
+                .map(|pair| hash_type.hash(b"MA", &[&pair[0].0, &pair[1].0]))
+                .collect();
+        }
+        (level[0], proofs)
+    }
+
+    #[test]
+    fn test_verify_transaction_proof() {
+        for hash_type in [HashType::Sha512_256, HashType::Sha256] {
+            let txns: Vec<(HashDigest, HashDigest)> = (0..5)
+                .map(|i| (HashDigest([i; 32]), HashDigest([i + 100; 32])))
+                .collect();
+            let leaves: Vec<HashDigest> = txns
+                .iter()
+                .map(|(txid, stib_hash)| hash_type.hash(b"TL", &[&txid.0, &stib_hash.0]))
+                .collect();
+            let (root, proofs) = build_tree(&leaves, hash_type);
+
+            for (index, (txid, stib_hash)) in txns.iter().enumerate() {
+                let proof = &proofs[index];
+                assert_eq!(3 * 32, proof.len());
+                assert!(verify_transaction_proof(
+                    &root,
+                    txid,
+                    stib_hash,
+                    index as u64,
+                    3,
+                    proof,
+                    hash_type
+                )
+                .is_ok());
+                // another index
+                assert!(verify_transaction_proof(
+                    &root,
+                    txid,
+                    stib_hash,
+                    (index as u64 + 1) % 5,
+                    3,
+                    proof,
+                    hash_type
+                )
+                .is_err());
+            }
+
+            let (txid, stib_hash) = &txns[0];
+            assert!(matches!(
+                verify_transaction_proof(&root, txid, stib_hash, 0, 2, &proofs[0], hash_type),
+                Err(CryptoError::InvalidMerkleProofLength)
+            ));
+            assert!(matches!(
+                verify_transaction_proof(&root, stib_hash, txid, 0, 3, &proofs[0], hash_type),
+                Err(CryptoError::InvalidMerkleProof)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_single_transaction_proof() {
+        let (txid, stib_hash) = (HashDigest([1; 32]), HashDigest([2; 32]));
+        let root = HashType::Sha512_256.hash(b"TL", &[&txid.0, &stib_hash.0]);
+        assert!(verify_transaction_proof(
+            &root,
+            &txid,
+            &stib_hash,
+            0,
+            0,
+            &[],
+            HashType::Sha512_256
+        )
+        .is_ok());
+    }
+}
@@ -1,6 +1,7 @@
 use data_encoding::BASE64;
 use serde::de::Visitor;
 use serde::{Deserialize, Deserializer, Serializer};
+use std::convert::TryFrom;
 
 pub struct SignatureVisitor;
 
@@ -48,34 +49,93 @@ impl<'de> Visitor<'de> for U8_32Visitor {
     }
 }
 
+/// Visits base64 encoded strings (JSON) and byte arrays (msgpack).
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("base64 encoded bytes or a byte array")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        BASE64.decode(v.as_bytes()).map_err(E::custom)
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(v)
+    }
+}
+
+/// Bytes, base64 encoded in JSON and a byte array in msgpack.
+struct Bytes(Vec<u8>);
+
+impl<'de> Deserialize<'de> for Bytes {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(BytesVisitor).map(Bytes)
+    }
+}
+
+fn deserialize_array<'de, D, const N: usize>(deserializer: D) -> Result<[u8; N], D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::de::Error;
+    let bytes = deserialize_bytes(deserializer)?;
+    <[u8; N]>::try_from(bytes.as_slice())
+        .map_err(|_| D::Error::custom(format!("Invalid byte array length: {}", bytes.len())))
+}
+
+/// Deserializes 32 bytes, base64 encoded (JSON) or as byte array (msgpack).
 pub fn deserialize_bytes32<'de, D>(deserializer: D) -> Result<[u8; 32], D::Error>
 where
     D: Deserializer<'de>,
 {
-    let s = <&str>::deserialize(deserializer)?;
-    let mut decoded = [0; 32];
-    decoded.copy_from_slice(&BASE64.decode(s.as_bytes()).unwrap());
-    Ok(decoded)
+    deserialize_array(deserializer)
 }
 
+/// Deserializes 64 bytes, base64 encoded (JSON) or as byte array (msgpack).
 pub fn deserialize_bytes64<'de, D>(deserializer: D) -> Result<[u8; 64], D::Error>
 where
     D: Deserializer<'de>,
 {
-    use serde::de::Error;
-    let s = <&str>::deserialize(deserializer)?;
-    let mut decoded = [0; 64];
-    let bytes = BASE64.decode(s.as_bytes()).map_err(D::Error::custom)?;
-    decoded.copy_from_slice(&bytes);
-    Ok(decoded)
+    deserialize_array(deserializer)
 }
 
+/// Deserializes bytes, base64 encoded (JSON) or as byte array (msgpack).
 pub fn deserialize_bytes<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let s = <&str>::deserialize(deserializer)?;
-    Ok(BASE64.decode(s.as_bytes()).unwrap())
+    deserializer.deserialize_any(BytesVisitor)
+}
+
+/// Deserializes a list of bytes, base64 encoded (JSON) or as byte arrays (msgpack).
+pub fn deserialize_vec_bytes<'de, D>(deserializer: D) -> Result<Vec<Vec<u8>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(<Vec<Bytes>>::deserialize(deserializer)?
+        .into_iter()
+        .map(|bytes| bytes.0)
+        .collect())
 }
 
 pub fn serialize_bytes<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
@@ -88,3 +148,92 @@ where
 pub fn decode_base64(bytes: &[u8]) -> Result<Vec<u8>, String> {
     BASE64.decode(bytes).map_err(|e| e.to_string())
 }
+
+/// The encoded key and value of a msgpack map entry.
+pub type MsgpackEntry<'a> = (&'a [u8], &'a [u8]);
+
+/// The entries of a msgpack map, in the order of the encoding.
+///
+/// Used to keep the exact encoding of (parts of) a map, e.g. to hash it.
+pub fn msgpack_map_entries(bytes: &[u8]) -> Result<Vec<MsgpackEntry<'_>>, String> {
+    let (entries, mut offset) = match bytes.first() {
+        Some(marker @ 0x80..=0x8f) => ((marker & 0x0f) as usize, 1),
+        Some(0xde) => (be_len(bytes, 1, 2)?, 3),
+        Some(0xdf) => (be_len(bytes, 1, 4)?, 5),
+        _ => return Err("Expected a msgpack map".to_owned()),
+    };
+    let mut result = vec![];
+    for _ in 0..entries {
+        let key_end = offset + msgpack_value_len(&bytes[offset..])?;
+        let value_end = key_end + msgpack_value_len(&bytes[key_end..])?;
+        result.push((&bytes[offset..key_end], &bytes[key_end..value_end]));
+        offset = value_end;
+    }
+    Ok(result)
+}
+
+/// Encodes a msgpack map with the encoded keys and values.
+pub fn msgpack_map(entries: &[MsgpackEntry]) -> Vec<u8> {
+    let mut bytes = match entries.len() {
+        len if len < 16 => vec![0x80 | len as u8],
+        len if len <= u16::MAX as usize => [&[0xde][..], &(len as u16).to_be_bytes()].concat(),
+        len => [&[0xdf][..], &(len as u32).to_be_bytes()].concat(),
+    };
+    for (key, value) in entries {
+        bytes.extend_from_slice(key);
+        bytes.extend_from_slice(value);
+    }
+    bytes
+}
+
+/// The length of the first msgpack value of the bytes.
+fn msgpack_value_len(bytes: &[u8]) -> Result<usize, String> {
+    // header length, data length and number of nested values
+    let (header, data, values) = match *bytes
+        .first()
+        .ok_or_else(|| "Unexpected end of msgpack".to_owned())?
+    {
+        0x00..=0x7f | 0xc0 | 0xc2 | 0xc3 | 0xe0..=0xff => (1, 0, 0),
+        marker @ 0x80..=0x8f => (1, 0, 2 * (marker & 0x0f) as usize),
+        marker @ 0x90..=0x9f => (1, 0, (marker & 0x0f) as usize),
+        marker @ 0xa0..=0xbf => (1, (marker & 0x1f) as usize, 0),
+        0xc4 | 0xd9 => (2, be_len(bytes, 1, 1)?, 0),
+        0xc5 | 0xda => (3, be_len(bytes, 1, 2)?, 0),
+        0xc6 | 0xdb => (5, be_len(bytes, 1, 4)?, 0),
+        0xc7 => (3, be_len(bytes, 1, 1)?, 0),
+        0xc8 => (4, be_len(bytes, 1, 2)?, 0),
+        0xc9 => (6, be_len(bytes, 1, 4)?, 0),
+        0xcc | 0xd0 => (1, 1, 0),
+        0xcd | 0xd1 => (1, 2, 0),
+        0xca | 0xce | 0xd2 => (1, 4, 0),
+        0xcb | 0xcf | 0xd3 => (1, 8, 0),
+        0xd4 => (2, 1, 0),
+        0xd5 => (2, 2, 0),
+        0xd6 => (2, 4, 0),
+        0xd7 => (2, 8, 0),
+        0xd8 => (2, 16, 0),
+        0xdc => (3, 0, be_len(bytes, 1, 2)?),
+        0xdd => (5, 0, be_len(bytes, 1, 4)?),
+        0xde => (3, 0, 2 * be_len(bytes, 1, 2)?),
+        0xdf => (5, 0, 2 * be_len(bytes, 1, 4)?),
+        marker => return Err(format!("Invalid msgpack marker: {:#x}", marker)),
+    };
+    let mut len = header + data;
+    if len > bytes.len() {
+        return Err("Unexpected end of msgpack".to_owned());
+    }
+    for _ in 0..values {
+        len += msgpack_value_len(&bytes[len..])?;
+    }
+    Ok(len)
+}
+
+/// The big endian number of `size` bytes at `offset`.
+fn be_len(bytes: &[u8], offset: usize, size: usize) -> Result<usize, String> {
+    let number = bytes
+        .get(offset..offset + size)
+        .ok_or_else(|| "Unexpected end of msgpack".to_owned())?;
+    Ok(number
+        .iter()
+        .fold(0, |len, byte| (len << 8) | *byte as usize))
+}
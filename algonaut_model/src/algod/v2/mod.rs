@@ -1,8 +1,12 @@
 use algonaut_core::{Address, MicroAlgos, Round};
-use algonaut_crypto::{deserialize_hash, HashDigest};
-use algonaut_encoding::deserialize_bytes;
+use algonaut_crypto::{deserialize_hash, merkle::HashType, HashDigest};
+use algonaut_encoding::{decode_base64, deserialize_bytes};
+use data_encoding::BASE64;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
+use std::convert::TryFrom;
+
+pub mod raw;
 
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -263,8 +267,11 @@ pub struct AssetHolding {
 
     /// Address that created this asset. This is the address where the parameters for this asset can
     /// be found, and also the address where unwanted asset units can be sent in the worst case.
-    #[serde_as(as = "DisplayFromStr")]
-    pub creator: Address,
+    ///
+    /// None if the account was requested as msgpack, which doesn't have it.
+    #[serde(default)]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub creator: Option<Address>,
 
     /// `f` whether or not the holding is frozen.
     #[serde(rename = "is-frozen")]
@@ -342,6 +349,36 @@ pub struct AssetParams {
     pub url: Option<String>,
 }
 
+/// Box name and its content.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BoxValue {
+    /// The round for which this information is relevant.
+    pub round: Round,
+
+    /// The box name.
+    #[serde(default, deserialize_with = "deserialize_bytes")]
+    pub name: Vec<u8>,
+
+    /// The box value.
+    #[serde(default, deserialize_with = "deserialize_bytes")]
+    pub value: Vec<u8>,
+}
+
+/// Box descriptor describes a box.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BoxDescriptor {
+    /// The box name.
+    #[serde(default, deserialize_with = "deserialize_bytes")]
+    pub name: Vec<u8>,
+}
+
+/// Box names of an application.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Boxes {
+    #[serde(default)]
+    pub boxes: Vec<BoxDescriptor>,
+}
+
 /// BuildVersion
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BuildVersion {
@@ -469,21 +506,73 @@ pub struct ErrorResponse<T> {
 
 /// Represents a TEAL value delta.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
-pub struct EvalDelta {
+#[serde(try_from = "ApiEvalDelta", into = "ApiEvalDelta")]
+pub enum EvalDelta {
+    /// The key was set to a bytes value.
+    SetBytes(Vec<u8>),
+    /// The key was set to an uint value.
+    SetUint(u64),
+    /// The key was deleted.
+    Delete,
+}
+
+/// The encoding of [EvalDelta]: an action with the bytes (base64) or uint value it sets.
+#[derive(Serialize, Deserialize, Clone)]
+struct ApiEvalDelta {
     /// `at` delta action.
-    pub action: u64,
+    action: u64,
 
     /// `bs` bytes value.
-    pub bytes: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bytes: Option<String>,
 
     /// `ui` uint value.
-    pub uint: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uint: Option<u64>,
+}
+
+impl TryFrom<ApiEvalDelta> for EvalDelta {
+    type Error = String;
+
+    fn try_from(api_delta: ApiEvalDelta) -> Result<Self, Self::Error> {
+        match (api_delta.action, api_delta.bytes, api_delta.uint) {
+            (1, bytes, _) => Ok(EvalDelta::SetBytes(decode_base64(
+                bytes.unwrap_or_default().as_bytes(),
+            )?)),
+            (2, _, uint) => Ok(EvalDelta::SetUint(uint.unwrap_or_default())),
+            (3, _, _) => Ok(EvalDelta::Delete),
+            (action, _, _) => Err(format!("Invalid eval delta action: {}", action)),
+        }
+    }
+}
+
+impl From<EvalDelta> for ApiEvalDelta {
+    fn from(delta: EvalDelta) -> Self {
+        match delta {
+            EvalDelta::SetBytes(bytes) => ApiEvalDelta {
+                action: 1,
+                bytes: Some(BASE64.encode(&bytes)),
+                uint: None,
+            },
+            EvalDelta::SetUint(uint) => ApiEvalDelta {
+                action: 2,
+                bytes: None,
+                uint: Some(uint),
+            },
+            EvalDelta::Delete => ApiEvalDelta {
+                action: 3,
+                bytes: None,
+                uint: None,
+            },
+        }
+    }
 }
 
 /// Key-value pairs for StateDelta.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct EvalDeltaKeyValue {
-    pub key: String,
+    #[serde(deserialize_with = "deserialize_bytes")]
+    pub key: Vec<u8>,
     pub value: EvalDelta,
 }
 
@@ -534,83 +623,6 @@ pub struct GenesisBlock {
     pub addr: Option<String>,
 }
 
-/// A transaction.
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Transaction {}
-
-/// A potentially truncated list of transactions currently in the node's transaction pool.
-/// You can compute whether or not the list is truncated if the number of elements in the
-/// top-transactions array is fewer than total-transactions.
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct PendingTransactions {
-    /// An array of signed transaction objects.
-    #[serde(rename = "top-transactions")]
-    pub top_transactions: Vec<Transaction>,
-
-    /// Total number of transactions in the pool.
-    #[serde(rename = "total-transactions")]
-    pub total_transactions: u64,
-}
-
-/// A specific pending transaction.
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct PendingTransaction {
-    /// The application index if the transaction was found and it created an application.
-    #[serde(rename = "application-index")]
-    pub application_index: Option<u64>,
-
-    /// The asset index if the transaction was found and it created an asset.
-    #[serde(rename = "asset-index")]
-    pub asset_index: Option<u64>,
-
-    /// Rewards in microalgos applied to the close remainder to account.
-    #[serde(rename = "close-rewards")]
-    pub close_rewards: Option<u64>,
-
-    /// Closing amount for the transaction.
-    #[serde(rename = "closing-amount")]
-    pub closing_amount: Option<u64>,
-
-    /// The round where this transaction was confirmed, if present.
-    #[serde(rename = "confirmed-round")]
-    pub confirmed_round: Option<u64>,
-
-    /// `gd` Global state key/value changes for the application being executed by this
-    /// transaction.
-    #[serde(
-        default,
-        rename = "global-state-delta",
-        skip_serializing_if = "Vec::is_empty"
-    )]
-    pub global_state_delta: Vec<EvalDeltaKeyValue>,
-
-    /// `ld` Local state key/value changes for the application being executed by this
-    /// transaction.
-    #[serde(
-        default,
-        rename = "local-state-delta",
-        skip_serializing_if = "Vec::is_empty"
-    )]
-    pub local_state_delta: Vec<AccountStateDelta>,
-
-    /// Indicates that the transaction was kicked out of this node's transaction pool
-    /// (and specifies why that happened). An empty string indicates the transaction
-    /// wasn't kicked out of this node's txpool due to an error.
-    #[serde(rename = "pool-error")]
-    pub pool_error: String,
-
-    /// Rewards in microalgos applied to the receiver account.
-    #[serde(rename = "receiver-rewards")]
-    pub receiver_rewards: Option<u64>,
-
-    /// Rewards in microalgos applied to the sender account.
-    #[serde(rename = "sender-rewards")]
-    pub sender_rewards: Option<u64>,
-
-    /// The raw signed transaction.
-    pub txn: Transaction,
-}
-
 /// Information about the status of a node
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct NodeStatus {
@@ -677,35 +689,6 @@ pub struct NodeStatus {
     pub time_since_last_round: u64,
 }
 
-/// Block
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Block {
-    /// Block header data.
-    pub block: BlockHeader,
-    /// Optional certificate object. This is only included when the format is set to message pack.
-    #[serde(rename = "cert", skip_serializing_if = "Option::is_none")]
-    pub cert: Option<serde_json::Value>,
-}
-
-/// BlockHeader
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct BlockHeader {
-    pub earn: Option<u64>,
-    pub fees: String,
-    pub frac: u64,
-    pub gen: String,
-    pub gh: String,
-    pub prev: String,
-    pub proto: String,
-    pub rate: u64,
-    pub rnd: u64,
-    pub rwcalr: u64,
-    pub rwd: String,
-    pub seed: String,
-    pub ts: u64,
-    pub txn: Option<String>,
-}
-
 /// Catchup
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Catchup {
@@ -800,8 +783,321 @@ pub struct TransactionParams {
     pub min_fee: MicroAlgos,
 }
 
+/// Proof of the membership of a transaction in a block.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransactionProof {
+    /// Index of the transaction in the block's payset.
+    #[serde(rename = "idx")]
+    pub index: u64,
+
+    /// Concatenated sibling hashes of the vector commitment, from the leaf up to the root.
+    #[serde(default, deserialize_with = "deserialize_bytes")]
+    pub proof: Vec<u8>,
+
+    /// Hash of the signed transaction in block.
+    #[serde(rename = "stibhash", deserialize_with = "deserialize_hash")]
+    pub stib_hash: HashDigest,
+
+    /// Depth of the tree, i.e. the number of hashes in the proof.
+    #[serde(rename = "treedepth")]
+    pub tree_depth: u64,
+
+    /// Hash function used to build the tree.
+    #[serde(rename = "hashtype")]
+    pub hash_type: HashType,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TransactionResponse {
     #[serde(rename = "txId")]
     pub tx_id: String,
 }
+
+/// Transaction groups to simulate, with the simulation options.
+///
+/// Generic over the signed transaction type, which has to serialize to its msgpack encoding.
+#[derive(Debug, Serialize, Clone)]
+pub struct SimulateRequest<T> {
+    /// The transaction groups to simulate.
+    #[serde(rename = "txn-groups")]
+    pub txn_groups: Vec<SimulateRequestTransactionGroup<T>>,
+
+    #[serde(flatten)]
+    pub options: SimulateOptions,
+}
+
+/// A transaction group to simulate.
+#[derive(Debug, Serialize, Clone)]
+pub struct SimulateRequestTransactionGroup<T> {
+    /// An atomic transaction group.
+    pub txns: Vec<T>,
+}
+
+/// Options of a simulation.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SimulateOptions {
+    /// Allows transactions without signatures to be simulated as if they had correct signatures.
+    #[serde(
+        rename = "allow-empty-signatures",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub allow_empty_signatures: Option<bool>,
+
+    /// Lifts limits on log opcode usage during simulation.
+    #[serde(rename = "allow-more-logging", skip_serializing_if = "Option::is_none")]
+    pub allow_more_logging: Option<bool>,
+
+    /// Allows access to unnamed resources during simulation.
+    #[serde(
+        rename = "allow-unnamed-resources",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub allow_unnamed_resources: Option<bool>,
+
+    /// Applies extra opcode budget during simulation for each transaction group.
+    #[serde(
+        rename = "extra-opcode-budget",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub extra_opcode_budget: Option<u64>,
+
+    /// Which execution trace information to return.
+    #[serde(rename = "exec-trace-config", skip_serializing_if = "Option::is_none")]
+    pub exec_trace_config: Option<SimulateTraceConfig>,
+
+    /// The round to simulate the transactions at, instead of the latest round.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub round: Option<Round>,
+}
+
+/// Which execution trace information a simulation returns.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct SimulateTraceConfig {
+    /// Enables the execution trace.
+    #[serde(default)]
+    pub enable: bool,
+
+    /// Enables the stack changes in the execution trace.
+    #[serde(rename = "stack-change", default)]
+    pub stack_change: bool,
+
+    /// Enables the scratch slot changes in the execution trace.
+    #[serde(rename = "scratch-change", default)]
+    pub scratch_change: bool,
+
+    /// Enables the application state changes in the execution trace.
+    #[serde(rename = "state-change", default)]
+    pub state_change: bool,
+}
+
+/// Result of a simulation, with the result of each transaction as `R`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SimulateResponse<R> {
+    /// The version of this response object.
+    pub version: u64,
+
+    /// The round immediately preceding this simulation.
+    /// State changes through this round were used to run this simulation.
+    #[serde(rename = "last-round")]
+    pub last_round: Round,
+
+    /// The results of the simulated transaction groups.
+    #[serde(rename = "txn-groups")]
+    pub txn_groups: Vec<SimulateTransactionGroupResult<R>>,
+
+    /// The evaluation settings that differ from the defaults.
+    #[serde(rename = "eval-overrides")]
+    pub eval_overrides: Option<SimulationEvalOverrides>,
+
+    /// The execution trace configuration of the request.
+    #[serde(rename = "exec-trace-config")]
+    pub exec_trace_config: Option<SimulateTraceConfig>,
+}
+
+/// Result of a simulated transaction group.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SimulateTransactionGroupResult<R> {
+    /// The results of the transactions in the group.
+    #[serde(rename = "txn-results")]
+    pub txn_results: Vec<SimulateTransactionResult<R>>,
+
+    /// Why the group failed, if it did.
+    #[serde(rename = "failure-message")]
+    pub failure_message: Option<String>,
+
+    /// The path to the failing transaction, if the group failed: the index in the group, followed by
+    /// the indexes of the inner transactions.
+    #[serde(rename = "failed-at", default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_at: Vec<u64>,
+
+    /// Total budget added during execution of app calls in the group.
+    #[serde(rename = "app-budget-added")]
+    pub app_budget_added: Option<u64>,
+
+    /// Total budget consumed during execution of app calls in the group.
+    #[serde(rename = "app-budget-consumed")]
+    pub app_budget_consumed: Option<u64>,
+}
+
+/// Result of a simulated transaction.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SimulateTransactionResult<R> {
+    /// The transaction result, like for a pending transaction: logs, state deltas, etc.
+    #[serde(rename = "txn-result")]
+    pub txn_result: R,
+
+    /// Budget used during execution of the app call, if it's one.
+    #[serde(rename = "app-budget-consumed")]
+    pub app_budget_consumed: Option<u64>,
+
+    /// Budget used during execution of the logic sig, if it has one.
+    #[serde(rename = "logic-sig-budget-consumed")]
+    pub logic_sig_budget_consumed: Option<u64>,
+
+    /// The execution trace, if enabled in the request.
+    #[serde(rename = "exec-trace")]
+    pub exec_trace: Option<SimulationTransactionExecTrace>,
+}
+
+/// The evaluation settings of a simulation that differ from the defaults.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SimulationEvalOverrides {
+    #[serde(rename = "allow-empty-signatures")]
+    pub allow_empty_signatures: Option<bool>,
+
+    #[serde(rename = "allow-unnamed-resources")]
+    pub allow_unnamed_resources: Option<bool>,
+
+    /// The maximum log calls one can make during simulation.
+    #[serde(rename = "max-log-calls")]
+    pub max_log_calls: Option<u64>,
+
+    /// The maximum byte number to log during simulation.
+    #[serde(rename = "max-log-size")]
+    pub max_log_size: Option<u64>,
+
+    #[serde(rename = "extra-opcode-budget")]
+    pub extra_opcode_budget: Option<u64>,
+}
+
+/// The execution trace of a simulated transaction.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SimulationTransactionExecTrace {
+    /// Program trace of the approval program, if it's an app call.
+    #[serde(
+        rename = "approval-program-trace",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub approval_program_trace: Vec<SimulationOpcodeTraceUnit>,
+
+    /// Program trace of the clear state program, if it's an app call.
+    #[serde(
+        rename = "clear-state-program-trace",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub clear_state_program_trace: Vec<SimulationOpcodeTraceUnit>,
+
+    /// Program trace of the logic sig, if the transaction has one.
+    #[serde(
+        rename = "logic-sig-trace",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub logic_sig_trace: Vec<SimulationOpcodeTraceUnit>,
+
+    /// Execution traces of the inner transactions.
+    #[serde(rename = "inner-trace", default, skip_serializing_if = "Vec::is_empty")]
+    pub inner_trace: Vec<SimulationTransactionExecTrace>,
+}
+
+/// The effects of an executed opcode.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SimulationOpcodeTraceUnit {
+    /// The program counter of the opcode.
+    pub pc: u64,
+
+    /// Indexes of the inner transactions spawned by the opcode, if any.
+    #[serde(
+        rename = "spawned-inners",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub spawned_inners: Vec<u64>,
+
+    /// Number of values popped from the stack.
+    #[serde(rename = "stack-pop-count")]
+    pub stack_pop_count: Option<u64>,
+
+    /// Values pushed to the stack.
+    #[serde(
+        rename = "stack-additions",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub stack_additions: Vec<AvmValue>,
+
+    /// Writes to scratch slots.
+    #[serde(
+        rename = "scratch-changes",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub scratch_changes: Vec<ScratchChange>,
+
+    /// Changes to application state.
+    #[serde(
+        rename = "state-changes",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub state_changes: Vec<ApplicationStateOperation>,
+}
+
+/// A value of the AVM.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AvmValue {
+    /// Value type: 1 for bytes, 2 for uint.
+    #[serde(rename = "type")]
+    pub value_type: u64,
+
+    /// Bytes value.
+    #[serde(default, deserialize_with = "deserialize_bytes")]
+    pub bytes: Vec<u8>,
+
+    /// Uint value.
+    pub uint: Option<u64>,
+}
+
+/// A write to a scratch slot.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScratchChange {
+    /// The scratch slot written.
+    pub slot: u64,
+
+    #[serde(rename = "new-value")]
+    pub new_value: AvmValue,
+}
+
+/// A change to application state.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApplicationStateOperation {
+    /// "w" for write, "d" for delete.
+    pub operation: String,
+
+    /// "g" for global, "l" for local, "b" for box state.
+    #[serde(rename = "app-state-type")]
+    pub app_state_type: String,
+
+    /// The key of the changed value.
+    #[serde(default, deserialize_with = "deserialize_bytes")]
+    pub key: Vec<u8>,
+
+    /// The written value.
+    #[serde(rename = "new-value")]
+    pub new_value: Option<AvmValue>,
+
+    /// The account of the local state, if it's local state.
+    pub account: Option<String>,
+}
@@ -0,0 +1,321 @@
+//! The account as algod encodes it in msgpack: the ledger record, with other keys and maps
+//! instead of the lists of the json model.
+//!
+//! Definition: data/basics/userBalance.go : AccountData
+
+use crate::algod::v2 as model;
+use algonaut_core::{Address, MicroAlgos, Round};
+use data_encoding::BASE64;
+use serde::Deserialize;
+use serde_bytes::ByteBuf;
+use std::collections::BTreeMap;
+
+/// Account record, converted with [AccountData::into_account].
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct AccountData {
+    /// `onl` 0: offline, 1: online, 2: not participating.
+    #[serde(rename = "onl")]
+    pub status: u8,
+
+    /// `algo` MicroAlgos, including the rewards.
+    #[serde(rename = "algo")]
+    pub micro_algos: u64,
+
+    /// `ebase` rewards base.
+    #[serde(rename = "ebase")]
+    pub rewards_base: u64,
+
+    /// `ern` total rewards received.
+    #[serde(rename = "ern")]
+    pub rewarded_micro_algos: u64,
+
+    /// `vote` root participation public key.
+    #[serde(rename = "vote")]
+    pub vote_id: ByteBuf,
+
+    /// `sel` selection public key.
+    #[serde(rename = "sel")]
+    pub selection_id: ByteBuf,
+
+    /// `voteFst` first round of the participation key.
+    #[serde(rename = "voteFst")]
+    pub vote_first_valid: u64,
+
+    /// `voteLst` last round of the participation key.
+    #[serde(rename = "voteLst")]
+    pub vote_last_valid: u64,
+
+    /// `voteKD` number of subkeys in each batch of participation keys.
+    #[serde(rename = "voteKD")]
+    pub vote_key_dilution: u64,
+
+    /// `apar` parameters of the assets created by the account, by asset id.
+    #[serde(rename = "apar")]
+    pub asset_params: BTreeMap<u64, AssetParams>,
+
+    /// `asset` assets held by the account, by asset id.
+    #[serde(rename = "asset")]
+    pub assets: BTreeMap<u64, AssetHolding>,
+
+    /// `spend` the address against which signing is checked, if rekeyed.
+    #[serde(rename = "spend")]
+    pub auth_addr: Option<Address>,
+
+    /// `appl` local states of the applications the account opted in, by application id.
+    #[serde(rename = "appl")]
+    pub app_local_states: BTreeMap<u64, AppLocalState>,
+
+    /// `appp` parameters of the applications created by the account, by application id.
+    #[serde(rename = "appp")]
+    pub app_params: BTreeMap<u64, AppParams>,
+
+    /// `tsch` sum of the local and global schemas of the account.
+    #[serde(rename = "tsch")]
+    pub total_app_schema: StateSchema,
+}
+
+/// Definition: data/basics/userBalance.go : AssetHolding
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct AssetHolding {
+    /// `a` number of units held.
+    #[serde(rename = "a")]
+    pub amount: u64,
+
+    /// `f` whether the holding is frozen.
+    #[serde(rename = "f")]
+    pub frozen: bool,
+}
+
+/// Definition: data/basics/userBalance.go : AssetParams
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct AssetParams {
+    #[serde(rename = "t")]
+    pub total: u64,
+
+    #[serde(rename = "dc")]
+    pub decimals: u64,
+
+    #[serde(rename = "df")]
+    pub default_frozen: bool,
+
+    /// Go strings, which don't have to be valid UTF-8.
+    #[serde(rename = "un")]
+    pub unit_name: ByteBuf,
+
+    #[serde(rename = "an")]
+    pub asset_name: ByteBuf,
+
+    #[serde(rename = "au")]
+    pub url: ByteBuf,
+
+    #[serde(rename = "am")]
+    pub metadata_hash: ByteBuf,
+
+    #[serde(rename = "m")]
+    pub manager: Option<Address>,
+
+    #[serde(rename = "r")]
+    pub reserve: Option<Address>,
+
+    #[serde(rename = "f")]
+    pub freeze: Option<Address>,
+
+    #[serde(rename = "c")]
+    pub clawback: Option<Address>,
+}
+
+/// Definition: data/basics/userBalance.go : AppLocalState
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct AppLocalState {
+    #[serde(rename = "hsch")]
+    pub schema: StateSchema,
+
+    /// Keyed by the raw key, which doesn't have to be valid UTF-8.
+    #[serde(rename = "tkv")]
+    pub key_value: BTreeMap<ByteBuf, TealValue>,
+}
+
+/// Definition: data/basics/userBalance.go : AppParams
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct AppParams {
+    #[serde(rename = "approv")]
+    pub approval_program: ByteBuf,
+
+    #[serde(rename = "clearp")]
+    pub clear_state_program: ByteBuf,
+
+    #[serde(rename = "gs")]
+    pub global_state: BTreeMap<ByteBuf, TealValue>,
+
+    #[serde(rename = "lsch")]
+    pub local_state_schema: StateSchema,
+
+    #[serde(rename = "gsch")]
+    pub global_state_schema: StateSchema,
+
+    #[serde(rename = "epp")]
+    pub extra_program_pages: u32,
+}
+
+/// Definition: data/basics/teal.go : StateSchema
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct StateSchema {
+    #[serde(rename = "nui")]
+    pub num_uint: u64,
+
+    #[serde(rename = "nbs")]
+    pub num_byte_slice: u64,
+}
+
+/// Definition: data/basics/teal.go : TealValue
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct TealValue {
+    #[serde(rename = "tt")]
+    pub value_type: u64,
+
+    #[serde(rename = "tb")]
+    pub bytes: ByteBuf,
+
+    #[serde(rename = "ui")]
+    pub uint: u64,
+}
+
+impl AccountData {
+    /// Converts the record into the json model of the account.
+    ///
+    /// The record has neither the round nor the pending rewards nor the signature type, and the
+    /// held assets don't have their creator: `round` is 0, `pending_rewards` is 0 and
+    /// `amount_without_pending_rewards` is the amount.
+    pub fn into_account(self, address: Address) -> model::Account {
+        let participation = if self.vote_id.iter().any(|b| *b != 0) {
+            Some(model::AccountParticipation {
+                selection_participation_key: self.selection_id.into_vec(),
+                vote_first_valid: self.vote_first_valid,
+                vote_key_dilution: self.vote_key_dilution,
+                vote_last_valid: self.vote_last_valid,
+                vote_participation_key: self.vote_id.into_vec(),
+            })
+        } else {
+            None
+        };
+        let status = match self.status {
+            1 => "Online",
+            2 => "Not Participating",
+            _ => "Offline",
+        };
+        model::Account {
+            address,
+            amount: MicroAlgos(self.micro_algos),
+            amount_without_pending_rewards: self.micro_algos,
+            apps_local_state: self
+                .app_local_states
+                .into_iter()
+                .map(|(id, state)| model::ApplicationLocalState {
+                    id,
+                    key_value: key_values(state.key_value),
+                    schema: state.schema.into(),
+                })
+                .collect(),
+            apps_total_schema: Some(self.total_app_schema.into()),
+            assets: self
+                .assets
+                .into_iter()
+                .map(|(asset_id, holding)| model::AssetHolding {
+                    amount: holding.amount,
+                    asset_id,
+                    creator: None,
+                    is_frozen: holding.frozen,
+                })
+                .collect(),
+            auth_addr: self.auth_addr,
+            created_apps: self
+                .app_params
+                .into_iter()
+                .map(|(id, params)| model::Application {
+                    id,
+                    params: model::ApplicationParams {
+                        approval_program: params.approval_program.into_vec(),
+                        clear_state_program: params.clear_state_program.into_vec(),
+                        creator: address,
+                        global_state: key_values(params.global_state),
+                        global_state_schema: Some(params.global_state_schema.into()),
+                        local_state_schema: Some(params.local_state_schema.into()),
+                    },
+                })
+                .collect(),
+            created_assets: self
+                .asset_params
+                .into_iter()
+                .map(|(index, params)| model::Asset {
+                    index,
+                    params: model::AssetParams {
+                        clawback: params.clawback,
+                        creator: address,
+                        decimals: params.decimals,
+                        default_frozen: Some(params.default_frozen),
+                        freeze: params.freeze,
+                        manager: params.manager,
+                        metadata_hash: Some(params.metadata_hash.into_vec())
+                            .filter(|hash| !hash.is_empty()),
+                        name: utf8(params.asset_name),
+                        reserve: params.reserve,
+                        total: params.total,
+                        unit_name: utf8(params.unit_name),
+                        url: utf8(params.url),
+                    },
+                })
+                .collect(),
+            participation,
+            pending_rewards: MicroAlgos(0),
+            reward_base: Some(self.rewards_base),
+            rewards: MicroAlgos(self.rewarded_micro_algos),
+            round: Round(0),
+            sig_type: None,
+            status: status.to_owned(),
+        }
+    }
+}
+
+impl From<StateSchema> for model::ApplicationStateSchema {
+    fn from(schema: StateSchema) -> Self {
+        model::ApplicationStateSchema {
+            num_byte_slice: schema.num_byte_slice,
+            num_uint: schema.num_uint,
+        }
+    }
+}
+
+impl From<TealValue> for model::TealValue {
+    fn from(value: TealValue) -> Self {
+        model::TealValue {
+            bytes: value.bytes.into_vec(),
+            value_type: value.value_type,
+            uint: value.uint,
+        }
+    }
+}
+
+/// The keys are base64 encoded, like in the json model.
+fn key_values(state: BTreeMap<ByteBuf, TealValue>) -> Vec<model::TealKeyValue> {
+    state
+        .into_iter()
+        .map(|(key, value)| model::TealKeyValue {
+            key: BASE64.encode(&key),
+            value: value.into(),
+        })
+        .collect()
+}
+
+/// Like the json model, which has only the names that are valid UTF-8.
+fn utf8(bytes: ByteBuf) -> Option<String> {
+    String::from_utf8(bytes.into_vec())
+        .ok()
+        .filter(|s| !s.is_empty())
+}
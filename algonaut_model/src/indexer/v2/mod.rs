@@ -2,9 +2,13 @@ use algonaut_core::{Address, MicroAlgos, Round};
 use algonaut_crypto::HashDigest;
 use algonaut_encoding::deserialize_bytes;
 use serde::{Deserialize, Serialize};
-use serde_with::{serde_as, DisplayFromStr};
+use serde_with::{base64::Base64, serde_as, DisplayFromStr};
+
+mod timestamp;
+pub use timestamp::Timestamp;
 
 ///
+#[serde_as]
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
 pub struct QueryAccount {
     /// Application ID.
@@ -16,8 +20,9 @@ pub struct QueryAccount {
     pub asset_id: Option<u64>,
 
     /// Include accounts configured to use this spending key.
-    #[serde(rename = "auth-addr")]
-    pub auth_addr: Option<String>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default, rename = "auth-addr")]
+    pub auth_addr: Option<Address>,
 
     /// Results should have an amount greater than this value. MicroAlgos are the default currency
     /// unless an asset-id is provided, in which case the asset will be used.
@@ -40,6 +45,52 @@ pub struct QueryAccount {
     pub round: Option<Round>,
 }
 
+impl QueryAccount {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn application_id(mut self, application_id: u64) -> Self {
+        self.application_id = Some(application_id);
+        self
+    }
+
+    pub fn asset_id(mut self, asset_id: u64) -> Self {
+        self.asset_id = Some(asset_id);
+        self
+    }
+
+    pub fn auth_addr(mut self, auth_addr: Address) -> Self {
+        self.auth_addr = Some(auth_addr);
+        self
+    }
+
+    pub fn currency_greater_than(mut self, currency_greater_than: u64) -> Self {
+        self.currency_greater_than = Some(currency_greater_than);
+        self
+    }
+
+    pub fn currency_less_than(mut self, currency_less_than: u64) -> Self {
+        self.currency_less_than = Some(currency_less_than);
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn next(mut self, next: &str) -> Self {
+        self.next = Some(next.to_owned());
+        self
+    }
+
+    pub fn round(mut self, round: Round) -> Self {
+        self.round = Some(round);
+        self
+    }
+}
+
 ///
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct AccountResponse {
@@ -68,6 +119,22 @@ pub struct QueryAccountInfo {
     pub round: Option<Round>,
 }
 
+impl QueryAccountInfo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn include_all(mut self, include_all: bool) -> Self {
+        self.include_all = Some(include_all);
+        self
+    }
+
+    pub fn round(mut self, round: Round) -> Self {
+        self.round = Some(round);
+        self
+    }
+}
+
 ///
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct AccountInfoResponse {
@@ -80,19 +147,30 @@ pub struct AccountInfoResponse {
 }
 
 /// Query account transactions.
+#[serde_as]
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
 pub struct QueryAccountTransaction {
-    /// Include results after the given time. Must be an RFC 3339 formatted string.
-    #[serde(rename = "after-time", skip_serializing_if = "Option::is_none")]
-    pub after_time: Option<String>,
+    /// Include results after the given time.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(
+        default,
+        rename = "after-time",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub after_time: Option<Timestamp>,
 
     /// Asset ID
     #[serde(rename = "asset-id", skip_serializing_if = "Option::is_none")]
     pub asset_id: Option<u64>,
 
-    /// Include results before the given time. Must be an RFC 3339 formatted string.
-    #[serde(rename = "before-time", skip_serializing_if = "Option::is_none")]
-    pub before_time: Option<String>,
+    /// Include results before the given time.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(
+        default,
+        rename = "before-time",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub before_time: Option<Timestamp>,
 
     /// Results should have an amount greater than this value. MicroAlgos are the default currency
     /// unless an asset-id is provided, in which case the asset will be used.
@@ -124,8 +202,13 @@ pub struct QueryAccountTransaction {
     pub next: Option<String>,
 
     /// Specifies a prefix which must be contained in the note field.
-    #[serde(rename = "note-prefix", skip_serializing_if = "Option::is_none")]
-    pub note_prefix: Option<String>,
+    #[serde_as(as = "Option<Base64>")]
+    #[serde(
+        default,
+        rename = "note-prefix",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub note_prefix: Option<Vec<u8>>,
 
     /// Include results which include the rekey-to field.
     #[serde(rename = "rekey-to", skip_serializing_if = "Option::is_none")]
@@ -133,7 +216,7 @@ pub struct QueryAccountTransaction {
 
     /// Include results for the specified round.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub round: Option<u64>,
+    pub round: Option<Round>,
 
     /// SigType filters just results using the specified type of signature:
     /// * sig - Standard
@@ -151,6 +234,87 @@ pub struct QueryAccountTransaction {
     pub txid: Option<String>,
 }
 
+impl QueryAccountTransaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn after_time(mut self, after_time: Timestamp) -> Self {
+        self.after_time = Some(after_time);
+        self
+    }
+
+    pub fn asset_id(mut self, asset_id: u64) -> Self {
+        self.asset_id = Some(asset_id);
+        self
+    }
+
+    pub fn before_time(mut self, before_time: Timestamp) -> Self {
+        self.before_time = Some(before_time);
+        self
+    }
+
+    pub fn currency_greater_than(mut self, currency_greater_than: u64) -> Self {
+        self.currency_greater_than = Some(currency_greater_than);
+        self
+    }
+
+    pub fn currency_less_than(mut self, currency_less_than: u64) -> Self {
+        self.currency_less_than = Some(currency_less_than);
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn max_round(mut self, max_round: Round) -> Self {
+        self.max_round = Some(max_round);
+        self
+    }
+
+    pub fn min_round(mut self, min_round: Round) -> Self {
+        self.min_round = Some(min_round);
+        self
+    }
+
+    pub fn next(mut self, next: &str) -> Self {
+        self.next = Some(next.to_owned());
+        self
+    }
+
+    pub fn note_prefix(mut self, note_prefix: Vec<u8>) -> Self {
+        self.note_prefix = Some(note_prefix);
+        self
+    }
+
+    pub fn rekey_to(mut self, rekey_to: bool) -> Self {
+        self.rekey_to = Some(rekey_to);
+        self
+    }
+
+    pub fn round(mut self, round: Round) -> Self {
+        self.round = Some(round);
+        self
+    }
+
+    pub fn sig_type(mut self, sig_type: SignatureType) -> Self {
+        self.sig_type = Some(sig_type);
+        self
+    }
+
+    pub fn tx_type(mut self, tx_type: TransactionType) -> Self {
+        self.tx_type = Some(tx_type);
+        self
+    }
+
+    pub fn txid(mut self, txid: &str) -> Self {
+        self.txid = Some(txid.to_owned());
+        self
+    }
+}
+
 /// Resonse to account transactions' endpoint.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct AccountTransactionResponse {
@@ -183,6 +347,27 @@ pub struct QueryApplications {
     pub next: Option<String>,
 }
 
+impl QueryApplications {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn application_id(mut self, application_id: u64) -> Self {
+        self.application_id = Some(application_id);
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn next(mut self, next: &str) -> Self {
+        self.next = Some(next.to_owned());
+        self
+    }
+}
+
 /// Response for applications/ endpoint.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ApplicationResponse {
@@ -206,6 +391,17 @@ pub struct QueryApplicationInfo {
     pub include_all: Option<bool>,
 }
 
+impl QueryApplicationInfo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn include_all(mut self, include_all: bool) -> Self {
+        self.include_all = Some(include_all);
+        self
+    }
+}
+
 /// Response for applications/id endpoint.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ApplicationInfoResponse {
@@ -217,7 +413,51 @@ pub struct ApplicationInfoResponse {
     pub current_round: i32,
 }
 
+/// Query application boxes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub struct QueryApplicationBoxes {
+    /// Maximum number of results to return.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+
+    /// The next page of results. Use the next token provided by the previous results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+}
+
+impl QueryApplicationBoxes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn next(mut self, next: &str) -> Self {
+        self.next = Some(next.to_owned());
+        self
+    }
+}
+
+/// Response for applications/id/boxes endpoint.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ApplicationBoxesResponse {
+    /// The application the boxes belong to.
+    #[serde(rename = "application-id")]
+    pub application_id: u64,
+
+    #[serde(default)]
+    pub boxes: Vec<BoxDescriptor>,
+
+    /// Used for pagination, when making another request provide this token with the next parameter.
+    #[serde(rename = "next-token", skip_serializing_if = "Option::is_none")]
+    pub next_token: Option<String>,
+}
+
 /// Query assets.
+#[serde_as]
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
 pub struct QueryAssets {
     /// Asset ID.
@@ -225,8 +465,9 @@ pub struct QueryAssets {
     pub asset_id: Option<u64>,
 
     /// Filter just assets with the given creator address.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub creator: Option<String>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator: Option<Address>,
 
     /// Maximum number of results to return.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -245,6 +486,42 @@ pub struct QueryAssets {
     pub unit: Option<String>,
 }
 
+impl QueryAssets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn asset_id(mut self, asset_id: u64) -> Self {
+        self.asset_id = Some(asset_id);
+        self
+    }
+
+    pub fn creator(mut self, creator: Address) -> Self {
+        self.creator = Some(creator);
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_owned());
+        self
+    }
+
+    pub fn next(mut self, next: &str) -> Self {
+        self.next = Some(next.to_owned());
+        self
+    }
+
+    pub fn unit(mut self, unit: &str) -> Self {
+        self.unit = Some(unit.to_owned());
+        self
+    }
+}
+
 /// Assets response.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct AssetResponse {
@@ -268,6 +545,17 @@ pub struct QueryAssetsInfo {
     pub include_all: Option<bool>,
 }
 
+impl QueryAssetsInfo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn include_all(mut self, include_all: bool) -> Self {
+        self.include_all = Some(include_all);
+        self
+    }
+}
+
 /// Assets info response.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct AssetsInfoResponse {
@@ -305,7 +593,38 @@ pub struct QueryBalances {
 
     /// Include results for the specified round.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub round: Option<u64>,
+    pub round: Option<Round>,
+}
+
+impl QueryBalances {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn currency_greater_than(mut self, currency_greater_than: u64) -> Self {
+        self.currency_greater_than = Some(currency_greater_than);
+        self
+    }
+
+    pub fn currency_less_than(mut self, currency_less_than: u64) -> Self {
+        self.currency_less_than = Some(currency_less_than);
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn next(mut self, next: &str) -> Self {
+        self.next = Some(next.to_owned());
+        self
+    }
+
+    pub fn round(mut self, round: Round) -> Self {
+        self.round = Some(round);
+        self
+    }
 }
 
 /// Balances response.
@@ -324,23 +643,35 @@ pub struct BalancesResponse {
 }
 
 /// Query assets transactions.
+#[serde_as]
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
 pub struct QueryAssetTransaction {
     /// Only include transactions with this address in one of the transaction fields.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub address: Option<String>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
 
     /// Combine with the address parameter to define what type of address to search for.
     #[serde(rename = "address-role", skip_serializing_if = "Option::is_none")]
     pub address_role: Option<Role>,
 
-    /// Include results after the given time. Must be an RFC 3339 formatted string.
-    #[serde(rename = "after-time", skip_serializing_if = "Option::is_none")]
-    pub after_time: Option<String>,
+    /// Include results after the given time.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(
+        default,
+        rename = "after-time",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub after_time: Option<Timestamp>,
 
-    /// Include results before the given time. Must be an RFC 3339 formatted string.
-    #[serde(rename = "before-time", skip_serializing_if = "Option::is_none")]
-    pub before_time: Option<String>,
+    /// Include results before the given time.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(
+        default,
+        rename = "before-time",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub before_time: Option<Timestamp>,
 
     /// Results should have an amount greater than this value. MicroAlgos are the default currency
     /// unless an asset-id is provided, in which case the asset will be used.
@@ -378,8 +709,13 @@ pub struct QueryAssetTransaction {
     pub next: Option<String>,
 
     /// Specifies a prefix which must be contained in the note field.
-    #[serde(rename = "note-prefix", skip_serializing_if = "Option::is_none")]
-    pub note_prefix: Option<String>,
+    #[serde_as(as = "Option<Base64>")]
+    #[serde(
+        default,
+        rename = "note-prefix",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub note_prefix: Option<Vec<u8>>,
 
     /// Include results which include the rekey-to field.
     #[serde(rename = "rekey-to", skip_serializing_if = "Option::is_none")]
@@ -387,7 +723,7 @@ pub struct QueryAssetTransaction {
 
     /// Include results for the specified round.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub round: Option<u64>,
+    pub round: Option<Round>,
 
     /// SigType filters just results using the specified type of signature:
     /// * sig - Standard
@@ -401,10 +737,101 @@ pub struct QueryAssetTransaction {
     pub tx_type: Option<TransactionType>,
 
     /// Lookup the specific transaction by ID.
-    #[serde(rename = "tx-type", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "txid", skip_serializing_if = "Option::is_none")]
     pub txid: Option<String>,
 }
 
+impl QueryAssetTransaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn address(mut self, address: Address) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    pub fn address_role(mut self, address_role: Role) -> Self {
+        self.address_role = Some(address_role);
+        self
+    }
+
+    pub fn after_time(mut self, after_time: Timestamp) -> Self {
+        self.after_time = Some(after_time);
+        self
+    }
+
+    pub fn before_time(mut self, before_time: Timestamp) -> Self {
+        self.before_time = Some(before_time);
+        self
+    }
+
+    pub fn currency_greater_than(mut self, currency_greater_than: u64) -> Self {
+        self.currency_greater_than = Some(currency_greater_than);
+        self
+    }
+
+    pub fn currency_less_than(mut self, currency_less_than: u64) -> Self {
+        self.currency_less_than = Some(currency_less_than);
+        self
+    }
+
+    pub fn exclude_close_to(mut self, exclude_close_to: bool) -> Self {
+        self.exclude_close_to = Some(exclude_close_to);
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn max_round(mut self, max_round: Round) -> Self {
+        self.max_round = Some(max_round);
+        self
+    }
+
+    pub fn min_round(mut self, min_round: Round) -> Self {
+        self.min_round = Some(min_round);
+        self
+    }
+
+    pub fn next(mut self, next: &str) -> Self {
+        self.next = Some(next.to_owned());
+        self
+    }
+
+    pub fn note_prefix(mut self, note_prefix: Vec<u8>) -> Self {
+        self.note_prefix = Some(note_prefix);
+        self
+    }
+
+    pub fn rekey_to(mut self, rekey_to: bool) -> Self {
+        self.rekey_to = Some(rekey_to);
+        self
+    }
+
+    pub fn round(mut self, round: Round) -> Self {
+        self.round = Some(round);
+        self
+    }
+
+    pub fn sig_type(mut self, sig_type: SignatureType) -> Self {
+        self.sig_type = Some(sig_type);
+        self
+    }
+
+    pub fn tx_type(mut self, tx_type: TransactionType) -> Self {
+        self.tx_type = Some(tx_type);
+        self
+    }
+
+    pub fn txid(mut self, txid: &str) -> Self {
+        self.txid = Some(txid.to_owned());
+        self
+    }
+}
+
 /// Resonse to asset transactions' endpoint.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct AssetTransactionResponse {
@@ -422,19 +849,26 @@ pub struct AssetTransactionResponse {
 }
 
 /// Query transactions.
+#[serde_as]
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
 pub struct QueryTransaction {
     /// Only include transactions with this address in one of the transaction fields.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub address: Option<String>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
 
     /// Combine with the address parameter to define what type of address to search for.
     #[serde(rename = "address-role", skip_serializing_if = "Option::is_none")]
     pub address_role: Option<Role>,
 
-    /// Include results after the given time. Must be an RFC 3339 formatted string.
-    #[serde(rename = "after-time", skip_serializing_if = "Option::is_none")]
-    pub after_time: Option<String>,
+    /// Include results after the given time.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(
+        default,
+        rename = "after-time",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub after_time: Option<Timestamp>,
 
     /// Application ID.
     #[serde(rename = "application-id", skip_serializing_if = "Option::is_none")]
@@ -444,9 +878,14 @@ pub struct QueryTransaction {
     #[serde(rename = "asset-id", skip_serializing_if = "Option::is_none")]
     pub asset_id: Option<u64>,
 
-    /// Include results before the given time. Must be an RFC 3339 formatted string.
-    #[serde(rename = "before-time", skip_serializing_if = "Option::is_none")]
-    pub before_time: Option<String>,
+    /// Include results before the given time.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(
+        default,
+        rename = "before-time",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub before_time: Option<Timestamp>,
 
     /// Results should have an amount greater than this value. MicroAlgos are the default currency
     /// unless an asset-id is provided, in which case the asset will be used.
@@ -484,8 +923,13 @@ pub struct QueryTransaction {
     pub next: Option<String>,
 
     /// Specifies a prefix which must be contained in the note field.
-    #[serde(rename = "note-prefix", skip_serializing_if = "Option::is_none")]
-    pub note_prefix: Option<String>,
+    #[serde_as(as = "Option<Base64>")]
+    #[serde(
+        default,
+        rename = "note-prefix",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub note_prefix: Option<Vec<u8>>,
 
     /// Include results which include the rekey-to field.
     #[serde(rename = "rekey-to", skip_serializing_if = "Option::is_none")]
@@ -493,7 +937,7 @@ pub struct QueryTransaction {
 
     /// Include results for the specified round.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub round: Option<u64>,
+    pub round: Option<Round>,
 
     /// SigType filters just results using the specified type of signature:
     /// * sig - Standard
@@ -507,10 +951,111 @@ pub struct QueryTransaction {
     pub tx_type: Option<TransactionType>,
 
     /// Lookup the specific transaction by ID.
-    #[serde(rename = "tx-type", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "txid", skip_serializing_if = "Option::is_none")]
     pub txid: Option<String>,
 }
 
+impl QueryTransaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn address(mut self, address: Address) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    pub fn address_role(mut self, address_role: Role) -> Self {
+        self.address_role = Some(address_role);
+        self
+    }
+
+    pub fn after_time(mut self, after_time: Timestamp) -> Self {
+        self.after_time = Some(after_time);
+        self
+    }
+
+    pub fn application_id(mut self, application_id: u64) -> Self {
+        self.application_id = Some(application_id);
+        self
+    }
+
+    pub fn asset_id(mut self, asset_id: u64) -> Self {
+        self.asset_id = Some(asset_id);
+        self
+    }
+
+    pub fn before_time(mut self, before_time: Timestamp) -> Self {
+        self.before_time = Some(before_time);
+        self
+    }
+
+    pub fn currency_greater_than(mut self, currency_greater_than: u64) -> Self {
+        self.currency_greater_than = Some(currency_greater_than);
+        self
+    }
+
+    pub fn currency_less_than(mut self, currency_less_than: u64) -> Self {
+        self.currency_less_than = Some(currency_less_than);
+        self
+    }
+
+    pub fn exclude_close_to(mut self, exclude_close_to: bool) -> Self {
+        self.exclude_close_to = Some(exclude_close_to);
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn max_round(mut self, max_round: Round) -> Self {
+        self.max_round = Some(max_round);
+        self
+    }
+
+    pub fn min_round(mut self, min_round: Round) -> Self {
+        self.min_round = Some(min_round);
+        self
+    }
+
+    pub fn next(mut self, next: &str) -> Self {
+        self.next = Some(next.to_owned());
+        self
+    }
+
+    pub fn note_prefix(mut self, note_prefix: Vec<u8>) -> Self {
+        self.note_prefix = Some(note_prefix);
+        self
+    }
+
+    pub fn rekey_to(mut self, rekey_to: bool) -> Self {
+        self.rekey_to = Some(rekey_to);
+        self
+    }
+
+    pub fn round(mut self, round: Round) -> Self {
+        self.round = Some(round);
+        self
+    }
+
+    pub fn sig_type(mut self, sig_type: SignatureType) -> Self {
+        self.sig_type = Some(sig_type);
+        self
+    }
+
+    pub fn tx_type(mut self, tx_type: TransactionType) -> Self {
+        self.tx_type = Some(tx_type);
+        self
+    }
+
+    pub fn txid(mut self, txid: &str) -> Self {
+        self.txid = Some(txid.to_owned());
+        self
+    }
+}
+
 /// Response to transactions/ endpoint.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TransactionResponse {
@@ -926,6 +1471,40 @@ pub struct AssetParams {
     pub url: Option<String>,
 }
 
+/// Box name and its content.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BoxValue {
+    /// The round for which this information is relevant.
+    pub round: Round,
+
+    /// The box name.
+    #[serde(default, deserialize_with = "deserialize_bytes")]
+    pub name: Vec<u8>,
+
+    /// The box value.
+    #[serde(default, deserialize_with = "deserialize_bytes")]
+    pub value: Vec<u8>,
+}
+
+/// Box descriptor describes a box.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BoxDescriptor {
+    /// The box name.
+    #[serde(default, deserialize_with = "deserialize_bytes")]
+    pub name: Vec<u8>,
+}
+
+/// A box of an application, referenced by an application call.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BoxReference {
+    /// The application owning the box, 0 for the called application.
+    pub app: u64,
+
+    /// The box name.
+    #[serde(default, deserialize_with = "deserialize_bytes")]
+    pub name: Vec<u8>,
+}
+
 /// Block information.
 #[serde_as]
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -1372,7 +1951,7 @@ pub enum TransactionType {
     AssetConfigurationTransaction,
     #[serde(rename = "axfer")]
     AssetTransferTransaction,
-    #[serde(rename = "axfrz")]
+    #[serde(rename = "afrz")]
     AssetFreezeTransaction,
     #[serde(rename = "appl")]
     ApplicationTransaction,
@@ -1401,6 +1980,15 @@ pub struct TransactionApplication {
     #[serde(rename = "application-id")]
     pub application_id: u64,
 
+    /// `apbx` the boxes that may be accessed by this application's approval-program and
+    /// clear-state-program.
+    #[serde(
+        default,
+        rename = "box-references",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub box_references: Vec<BoxReference>,
+
     /// `apap` Logic executed for every application transaction, except when on-completion is set
     /// to "clear". It can read and write global state for the application, as well as
     /// account-specific local state. Approval programs may reject the transaction.
@@ -1417,6 +2005,10 @@ pub struct TransactionApplication {
     #[serde(rename = "clear-state-program")]
     pub clear_state_program: Option<String>,
 
+    /// `apep` Number of additional pages allocated to the application's programs.
+    #[serde(default, rename = "extra-program-pages")]
+    pub extra_program_pages: u64,
+
     /// `apfa` Lists the applications in addition to the application-id whose global states may be
     /// accessed by this application's approval-program and clear-state-program. The access is read-only.
     #[serde(
@@ -1529,7 +2121,7 @@ pub struct TransactionKeyreg {
     pub vote_key_dilution: Option<u64>,
 
     /// `votelst` Last round this participation key is valid.
-    #[serde(rename = "vote-key-dilution")]
+    #[serde(rename = "vote-last-valid")]
     pub vote_last_valid: Option<u64>,
 
     /// `votekey` Participation public key used in key registration transactions.
@@ -1636,3 +2228,34 @@ pub enum Role {
     #[serde(rename = "freeze-target")]
     FreezeTarget,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_serialize_query_transaction() {
+        let address = Address([1; 32]);
+        let query = QueryTransaction::new()
+            .address(address)
+            .after_time(Timestamp(1_643_632_200))
+            .note_prefix(b"hello".to_vec())
+            .round(Round(10))
+            .txid("TXID");
+        assert_eq!(
+            json!({
+                "address": address.to_string(),
+                "after-time": "2022-01-31T12:30:00Z",
+                "note-prefix": "aGVsbG8=",
+                "round": 10,
+                "txid": "TXID",
+            }),
+            serde_json::to_value(&query).unwrap()
+        );
+        assert_eq!(
+            query,
+            serde_json::from_value(serde_json::to_value(&query).unwrap()).unwrap()
+        );
+    }
+}
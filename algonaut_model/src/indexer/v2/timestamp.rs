@@ -0,0 +1,253 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// A point in time, in seconds since the Unix epoch.
+///
+/// Formatted as RFC 3339 in UTC, e.g. `2022-01-31T12:30:00Z`, which is what the indexer expects.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(pub u64);
+
+impl From<SystemTime> for Timestamp {
+    /// Times before the Unix epoch are clamped to the epoch.
+    fn from(time: SystemTime) -> Self {
+        Timestamp(
+            time.duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        )
+    }
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = civil_from_days(self.0 / SECONDS_PER_DAY);
+        let seconds = self.0 % SECONDS_PER_DAY;
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            year,
+            month,
+            day,
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = String;
+
+    /// Parses an RFC 3339 date and time, e.g. `2022-01-31T12:30:00Z` or
+    /// `2022-01-31T14:30:00.5+02:00`. Fractions of a second are truncated.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid RFC 3339 timestamp: {}", s);
+        let b = s.as_bytes();
+        if !s.is_ascii()
+            || b.len() < 20
+            || b[4] != b'-'
+            || b[7] != b'-'
+            || !matches!(b[10], b'T' | b't' | b' ')
+            || b[13] != b':'
+            || b[16] != b':'
+        {
+            return Err(invalid());
+        }
+        let number = |range: std::ops::Range<usize>| -> Result<u64, String> {
+            let digits = &s[range];
+            if digits.bytes().all(|c| c.is_ascii_digit()) {
+                digits.parse().map_err(|_| invalid())
+            } else {
+                Err(invalid())
+            }
+        };
+        let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+        let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+        if year < 1970
+            || !(1..=12).contains(&month)
+            || day < 1
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 60
+        {
+            return Err(invalid());
+        }
+
+        let mut rest = &s[19..];
+        if let Some(fraction) = rest.strip_prefix('.') {
+            let digits = fraction.bytes().take_while(|c| c.is_ascii_digit()).count();
+            if digits == 0 {
+                return Err(invalid());
+            }
+            rest = &fraction[digits..];
+        }
+        let offset: i64 = match rest {
+            "Z" | "z" => 0,
+            _ if rest.len() == 6 && matches!(&rest[3..4], ":") => {
+                let sign = match &rest[..1] {
+                    "+" => 1,
+                    "-" => -1,
+                    _ => return Err(invalid()),
+                };
+                let (hours, minutes) = (
+                    number(s.len() - 5..s.len() - 3)?,
+                    number(s.len() - 2..s.len())?,
+                );
+                if hours > 23 || minutes > 59 {
+                    return Err(invalid());
+                }
+                sign * (hours * 3600 + minutes * 60) as i64
+            }
+            _ => return Err(invalid()),
+        };
+
+        // a leap second is represented as the following second
+        let local = days_from_civil(year, month, day) * SECONDS_PER_DAY
+            + hour * 3600
+            + minute * 60
+            + second;
+        let utc = local as i64 - offset;
+        if utc < 0 {
+            return Err(invalid());
+        }
+        Ok(Timestamp(utc as u64))
+    }
+}
+
+fn is_leap_year(year: u64) -> bool {
+    matches!(
+        (year % 4, year % 100, year % 400),
+        (_, _, 0) | (0, 1..=99, _)
+    )
+}
+
+fn days_in_month(year: u64, month: u64) -> u64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days from 0000-03-01 to the Unix epoch.
+const EPOCH_DAYS: u64 = 719_468;
+/// Days in a 400 years era of the Gregorian calendar.
+const DAYS_PER_ERA: u64 = 146_097;
+
+/// Days since the Unix epoch of a date from 1970.
+///
+/// Constant time, counting the years from March, so that the leap day is the last of the year
+/// (<http://howardhinnant.github.io/date_algorithms.html#days_from_civil>).
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let month_from_march = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_from_march + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * DAYS_PER_ERA + day_of_era - EPOCH_DAYS
+}
+
+/// Date (year, month, day) of the days since the Unix epoch.
+///
+/// Constant time, the inverse of [days_from_civil]
+/// (<http://howardhinnant.github.io/date_algorithms.html#civil_from_days>).
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + EPOCH_DAYS;
+    let era = days / DAYS_PER_ERA;
+    let day_of_era = days - era * DAYS_PER_ERA;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        assert_eq!("1970-01-01T00:00:00Z", Timestamp(0).to_string());
+        assert_eq!("2000-02-29T23:59:59Z", Timestamp(951_868_799).to_string());
+        assert_eq!("2022-01-31T12:30:00Z", Timestamp(1_643_632_200).to_string());
+        assert_eq!(
+            "9999-12-31T23:59:59Z",
+            Timestamp(253_402_300_799).to_string()
+        );
+        assert_eq!(
+            "584554051223-11-09T07:00:15Z",
+            Timestamp(u64::MAX).to_string()
+        );
+    }
+
+    #[test]
+    fn test_days() {
+        // every day of the first 400 years, to compare with the civil calendar
+        let (mut year, mut month, mut day) = (1970, 1, 1);
+        for days in 0..DAYS_PER_ERA {
+            assert_eq!((year, month, day), civil_from_days(days));
+            assert_eq!(days, days_from_civil(year, month, day));
+            day += 1;
+            if day > days_in_month(year, month) {
+                day = 1;
+                month += 1;
+                if month > 12 {
+                    month = 1;
+                    year += 1;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        for timestamp in [
+            0,
+            951_868_799,
+            1_643_632_200,
+            4_102_444_800,
+            253_402_300_799,
+        ] {
+            let timestamp = Timestamp(timestamp);
+            assert_eq!(Ok(timestamp), timestamp.to_string().parse());
+        }
+        assert_eq!(
+            Ok(Timestamp(1_643_632_200)),
+            "2022-01-31T14:30:00.123+02:00".parse()
+        );
+        assert_eq!(
+            Ok(Timestamp(1_643_632_200)),
+            "2022-01-31t07:30:00-05:00".parse()
+        );
+
+        for invalid in [
+            "",
+            "2022-01-31",
+            "2022-01-31T12:30:00",
+            "2022-02-29T12:30:00Z",
+            "2022-13-01T12:30:00Z",
+            "2022-01-31T24:00:00Z",
+            "2022-01-31T12:30:00.Z",
+            "2022-01-31T12:30:00+0200",
+            "1969-12-31T23:59:59Z",
+            "1970-01-01T00:00:00+01:00",
+            "+022-01-31T12:30:00Z",
+        ] {
+            assert!(invalid.parse::<Timestamp>().is_err(), "{}", invalid);
+        }
+    }
+}
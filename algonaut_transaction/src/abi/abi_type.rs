@@ -0,0 +1,300 @@
+use crate::error::TransactionError;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// Length in bytes of an encoded address.
+pub const ADDRESS_BYTE_LEN: usize = 32;
+/// Length in bytes of the length prefix of dynamic arrays and strings, and of tuple offsets.
+pub const LENGTH_ENCODE_BYTE_LEN: usize = 2;
+
+const MAX_UINT_BITS: usize = 512;
+const MAX_UFIXED_PRECISION: usize = 160;
+
+/// An ARC-4 ABI type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AbiType {
+    /// `uint<N>`, 8 <= N <= 512, N % 8 = 0.
+    Uint(usize),
+    /// `ufixed<N>x<M>`, 8 <= N <= 512, N % 8 = 0, 0 < M <= 160.
+    UFixed { bits: usize, precision: usize },
+    /// `byte`, an alias for `uint8` with a different display.
+    Byte,
+    /// `bool`, packed into bits when it appears consecutively in a tuple or array.
+    Bool,
+    /// `address`, equivalent to `byte[32]`.
+    Address,
+    /// `string`, equivalent to `byte[]` containing UTF-8.
+    String,
+    /// `<type>[<N>]`
+    StaticArray(Box<AbiType>, usize),
+    /// `<type>[]`
+    DynamicArray(Box<AbiType>),
+    /// `(<type 1>,...,<type N>)`
+    Tuple(Vec<AbiType>),
+}
+
+impl AbiType {
+    pub fn uint(bits: usize) -> Result<AbiType, TransactionError> {
+        if !(8..=MAX_UINT_BITS).contains(&bits) || bits % 8 != 0 {
+            return Err(TransactionError::Abi(format!(
+                "unsupported uint bit size: {}",
+                bits
+            )));
+        }
+        Ok(AbiType::Uint(bits))
+    }
+
+    pub fn ufixed(bits: usize, precision: usize) -> Result<AbiType, TransactionError> {
+        if !(8..=MAX_UINT_BITS).contains(&bits) || bits % 8 != 0 {
+            return Err(TransactionError::Abi(format!(
+                "unsupported ufixed bit size: {}",
+                bits
+            )));
+        }
+        if !(1..=MAX_UFIXED_PRECISION).contains(&precision) {
+            return Err(TransactionError::Abi(format!(
+                "unsupported ufixed precision: {}",
+                precision
+            )));
+        }
+        Ok(AbiType::UFixed { bits, precision })
+    }
+
+    /// Whether the encoding length of this type depends on the encoded value.
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            AbiType::String | AbiType::DynamicArray(_) => true,
+            AbiType::StaticArray(child, _) => child.is_dynamic(),
+            AbiType::Tuple(children) => children.iter().any(AbiType::is_dynamic),
+            _ => false,
+        }
+    }
+
+    /// Length in bytes of the encoding of a static type.
+    pub fn byte_len(&self) -> Result<usize, TransactionError> {
+        match self {
+            AbiType::Uint(bits) => Ok(bits / 8),
+            AbiType::UFixed { bits, .. } => Ok(bits / 8),
+            AbiType::Byte | AbiType::Bool => Ok(1),
+            AbiType::Address => Ok(ADDRESS_BYTE_LEN),
+            AbiType::StaticArray(child, len) => match child.as_ref() {
+                AbiType::Bool => Ok((len + 7) / 8),
+                _ => Ok(child.byte_len()? * len),
+            },
+            AbiType::Tuple(children) => {
+                let mut size = 0;
+                let mut i = 0;
+                while i < children.len() {
+                    if children[i] == AbiType::Bool {
+                        let bools = consecutive_bool_count(&children[i..]);
+                        size += (bools + 7) / 8;
+                        i += bools;
+                    } else {
+                        size += children[i].byte_len()?;
+                        i += 1;
+                    }
+                }
+                Ok(size)
+            }
+            AbiType::String | AbiType::DynamicArray(_) => Err(TransactionError::Abi(format!(
+                "dynamic type {} has no static byte length",
+                self
+            ))),
+        }
+    }
+}
+
+/// Number of consecutive `bool` types at the start of `types`.
+pub(crate) fn consecutive_bool_count(types: &[AbiType]) -> usize {
+    types.iter().take_while(|t| **t == AbiType::Bool).count()
+}
+
+impl Display for AbiType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AbiType::Uint(bits) => write!(f, "uint{}", bits),
+            AbiType::UFixed { bits, precision } => write!(f, "ufixed{}x{}", bits, precision),
+            AbiType::Byte => write!(f, "byte"),
+            AbiType::Bool => write!(f, "bool"),
+            AbiType::Address => write!(f, "address"),
+            AbiType::String => write!(f, "string"),
+            AbiType::StaticArray(child, len) => write!(f, "{}[{}]", child, len),
+            AbiType::DynamicArray(child) => write!(f, "{}[]", child),
+            AbiType::Tuple(children) => {
+                let children: Vec<String> = children.iter().map(|c| c.to_string()).collect();
+                write!(f, "({})", children.join(","))
+            }
+        }
+    }
+}
+
+impl FromStr for AbiType {
+    type Err = TransactionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(stripped) = s.strip_suffix(']') {
+            let open = stripped
+                .rfind('[')
+                .ok_or_else(|| invalid_type(s, "unbalanced brackets"))?;
+            let child: AbiType = stripped[..open].parse()?;
+            let len = &stripped[open + 1..];
+            if len.is_empty() {
+                return Ok(AbiType::DynamicArray(Box::new(child)));
+            }
+            if !len.bytes().all(|b| b.is_ascii_digit()) || (len.starts_with('0') && len != "0") {
+                return Err(invalid_type(s, "invalid static array length"));
+            }
+            let len = len
+                .parse()
+                .map_err(|_| invalid_type(s, "invalid static array length"))?;
+            return Ok(AbiType::StaticArray(Box::new(child), len));
+        }
+        if let Some(bits) = s.strip_prefix("uint") {
+            return AbiType::uint(parse_number(s, bits)?);
+        }
+        if let Some(rest) = s.strip_prefix("ufixed") {
+            let mut parts = rest.splitn(2, 'x');
+            let bits = parse_number(s, parts.next().unwrap_or_default())?;
+            let precision = parse_number(s, parts.next().unwrap_or_default())?;
+            return AbiType::ufixed(bits, precision);
+        }
+        if s.starts_with('(') {
+            let inner = s
+                .strip_prefix('(')
+                .and_then(|s| s.strip_suffix(')'))
+                .ok_or_else(|| invalid_type(s, "unbalanced parentheses"))?;
+            let children = split_tuple(inner)
+                .ok_or_else(|| invalid_type(s, "unbalanced parentheses"))?
+                .into_iter()
+                .map(|c| c.parse())
+                .collect::<Result<Vec<AbiType>, _>>()?;
+            return Ok(AbiType::Tuple(children));
+        }
+        match s {
+            "byte" => Ok(AbiType::Byte),
+            "bool" => Ok(AbiType::Bool),
+            "address" => Ok(AbiType::Address),
+            "string" => Ok(AbiType::String),
+            _ => Err(invalid_type(s, "unknown type")),
+        }
+    }
+}
+
+fn invalid_type(s: &str, reason: &str) -> TransactionError {
+    TransactionError::Abi(format!("invalid ABI type '{}': {}", s, reason))
+}
+
+fn parse_number(s: &str, digits: &str) -> Result<usize, TransactionError> {
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) || digits.starts_with('0') {
+        return Err(invalid_type(s, "invalid size"));
+    }
+    digits.parse().map_err(|_| invalid_type(s, "invalid size"))
+}
+
+/// Splits the content of a tuple type string at its top level commas.
+/// Returns `None` if the parentheses are unbalanced.
+pub(crate) fn split_tuple(s: &str) -> Option<Vec<&str>> {
+    if s.is_empty() {
+        return Some(vec![]);
+    }
+    let mut parts = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return None;
+    }
+    parts.push(&s[start..]);
+    Some(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        for s in &[
+            "uint8",
+            "uint512",
+            "ufixed64x2",
+            "ufixed512x160",
+            "byte",
+            "bool",
+            "address",
+            "string",
+            "bool[3]",
+            "uint64[]",
+            "byte[32][]",
+            "()",
+            "(uint64,(bool,byte[]),string)[3][]",
+            "((uint8,()),bool[],(address,string))",
+        ] {
+            let parsed: AbiType = s.parse().unwrap();
+            assert_eq!(s, &parsed.to_string());
+        }
+    }
+
+    #[test]
+    fn test_parse_structure() {
+        assert_eq!(
+            AbiType::Tuple(vec![
+                AbiType::Uint(64),
+                AbiType::StaticArray(Box::new(AbiType::Bool), 2),
+                AbiType::DynamicArray(Box::new(AbiType::Tuple(vec![
+                    AbiType::String,
+                    AbiType::Byte
+                ]))),
+            ]),
+            "(uint64,bool[2],(string,byte)[])".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for s in &[
+            "uint",
+            "uint7",
+            "uint520",
+            "uint08",
+            "ufixed8",
+            "ufixed8x0",
+            "ufixed8x161",
+            "ufixed7x2",
+            "int64",
+            "bool[",
+            "bool]",
+            "bool[01]",
+            "bool[-1]",
+            "(uint64",
+            "(uint64))",
+            "(uint64,)",
+            "(,)",
+        ] {
+            assert!(s.parse::<AbiType>().is_err(), "{} should not parse", s);
+        }
+    }
+
+    #[test]
+    fn test_is_dynamic_and_byte_len() {
+        let t: AbiType = "(uint64,bool,bool,address,byte[4],bool[9])"
+            .parse()
+            .unwrap();
+        assert!(!t.is_dynamic());
+        assert_eq!(8 + 1 + 32 + 4 + 2, t.byte_len().unwrap());
+
+        let t: AbiType = "(uint64,string)[2]".parse().unwrap();
+        assert!(t.is_dynamic());
+        assert!(t.byte_len().is_err());
+    }
+}
@@ -0,0 +1,479 @@
+use super::abi_type::{consecutive_bool_count, AbiType, ADDRESS_BYTE_LEN, LENGTH_ENCODE_BYTE_LEN};
+use crate::error::TransactionError;
+use algonaut_core::Address;
+use num_bigint::BigUint;
+use std::convert::TryInto;
+
+/// A value that can be encoded as, or has been decoded from, an ARC-4 ABI type.
+///
+/// `ufixed<N>x<M>` values are represented by their unscaled integer: `1.23` as `ufixed64x2` is `Int(123)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbiValue {
+    Int(BigUint),
+    Byte(u8),
+    Bool(bool),
+    Address(Address),
+    String(String),
+    /// Elements of a static or dynamic array.
+    Array(Vec<AbiValue>),
+    Tuple(Vec<AbiValue>),
+}
+
+impl AbiValue {
+    /// Convenience to create a `byte[]` or `byte[N]` value.
+    pub fn bytes(bytes: &[u8]) -> AbiValue {
+        AbiValue::Array(bytes.iter().map(|b| AbiValue::Byte(*b)).collect())
+    }
+
+    /// Encodes the value as `abi_type`.
+    pub fn encode(&self, abi_type: &AbiType) -> Result<Vec<u8>, TransactionError> {
+        match (abi_type, self) {
+            (AbiType::Uint(bits), AbiValue::Int(n))
+            | (AbiType::UFixed { bits, .. }, AbiValue::Int(n)) => encode_int(n, *bits),
+            (AbiType::Byte, AbiValue::Byte(b)) => Ok(vec![*b]),
+            (AbiType::Bool, AbiValue::Bool(b)) => Ok(vec![if *b { 0x80 } else { 0x00 }]),
+            (AbiType::Address, AbiValue::Address(address)) => Ok(address.0.to_vec()),
+            (AbiType::String, AbiValue::String(s)) => {
+                let mut encoded = encode_length(s.len())?;
+                encoded.extend_from_slice(s.as_bytes());
+                Ok(encoded)
+            }
+            (AbiType::StaticArray(child, len), AbiValue::Array(values)) => {
+                if values.len() != *len {
+                    return Err(TransactionError::Abi(format!(
+                        "expected {} values for {}, got {}",
+                        len,
+                        abi_type,
+                        values.len()
+                    )));
+                }
+                encode_tuple(&vec![child.as_ref().clone(); *len], values)
+            }
+            (AbiType::DynamicArray(child), AbiValue::Array(values)) => {
+                let mut encoded = encode_length(values.len())?;
+                encoded.extend(encode_tuple(
+                    &vec![child.as_ref().clone(); values.len()],
+                    values,
+                )?);
+                Ok(encoded)
+            }
+            (AbiType::Tuple(children), AbiValue::Tuple(values)) => {
+                if values.len() != children.len() {
+                    return Err(TransactionError::Abi(format!(
+                        "expected {} values for {}, got {}",
+                        children.len(),
+                        abi_type,
+                        values.len()
+                    )));
+                }
+                encode_tuple(children, values)
+            }
+            _ => Err(TransactionError::Abi(format!(
+                "value {:?} can't be encoded as {}",
+                self, abi_type
+            ))),
+        }
+    }
+
+    /// Decodes `bytes` as `abi_type`. All bytes have to be consumed.
+    pub fn decode(abi_type: &AbiType, bytes: &[u8]) -> Result<AbiValue, TransactionError> {
+        match abi_type {
+            AbiType::Uint(_) | AbiType::UFixed { .. } => {
+                expect_len(abi_type, bytes, abi_type.byte_len()?)?;
+                Ok(AbiValue::Int(BigUint::from_bytes_be(bytes)))
+            }
+            AbiType::Byte => {
+                expect_len(abi_type, bytes, 1)?;
+                Ok(AbiValue::Byte(bytes[0]))
+            }
+            AbiType::Bool => {
+                expect_len(abi_type, bytes, 1)?;
+                match bytes[0] {
+                    0x80 => Ok(AbiValue::Bool(true)),
+                    0x00 => Ok(AbiValue::Bool(false)),
+                    b => Err(TransactionError::Abi(format!(
+                        "invalid bool encoding: {:#04x}",
+                        b
+                    ))),
+                }
+            }
+            AbiType::Address => {
+                expect_len(abi_type, bytes, ADDRESS_BYTE_LEN)?;
+                Ok(AbiValue::Address(Address::new(bytes.try_into().map_err(
+                    |_| TransactionError::Abi("invalid address length".to_owned()),
+                )?)))
+            }
+            AbiType::String => {
+                let (len, content) = decode_length(bytes)?;
+                expect_len(abi_type, content, len)?;
+                String::from_utf8(content.to_vec())
+                    .map(AbiValue::String)
+                    .map_err(|e| TransactionError::Abi(format!("invalid string: {}", e)))
+            }
+            AbiType::StaticArray(child, len) => Ok(AbiValue::Array(decode_tuple(
+                &vec![child.as_ref().clone(); *len],
+                bytes,
+            )?)),
+            AbiType::DynamicArray(child) => {
+                let (len, content) = decode_length(bytes)?;
+                Ok(AbiValue::Array(decode_tuple(
+                    &vec![child.as_ref().clone(); len],
+                    content,
+                )?))
+            }
+            AbiType::Tuple(children) => Ok(AbiValue::Tuple(decode_tuple(children, bytes)?)),
+        }
+    }
+}
+
+impl From<bool> for AbiValue {
+    fn from(b: bool) -> Self {
+        AbiValue::Bool(b)
+    }
+}
+
+impl From<u64> for AbiValue {
+    fn from(n: u64) -> Self {
+        AbiValue::Int(n.into())
+    }
+}
+
+impl From<BigUint> for AbiValue {
+    fn from(n: BigUint) -> Self {
+        AbiValue::Int(n)
+    }
+}
+
+impl From<Address> for AbiValue {
+    fn from(address: Address) -> Self {
+        AbiValue::Address(address)
+    }
+}
+
+impl From<String> for AbiValue {
+    fn from(s: String) -> Self {
+        AbiValue::String(s)
+    }
+}
+
+impl From<&str> for AbiValue {
+    fn from(s: &str) -> Self {
+        AbiValue::String(s.to_owned())
+    }
+}
+
+fn encode_int(n: &BigUint, bits: usize) -> Result<Vec<u8>, TransactionError> {
+    if n.bits() as usize > bits {
+        return Err(TransactionError::Abi(format!(
+            "{} doesn't fit in {} bits",
+            n, bits
+        )));
+    }
+    let bytes = n.to_bytes_be();
+    let mut encoded = vec![0; bits / 8 - bytes.len()];
+    encoded.extend(bytes);
+    Ok(encoded)
+}
+
+fn encode_length(len: usize) -> Result<Vec<u8>, TransactionError> {
+    let len: u16 = len
+        .try_into()
+        .map_err(|_| TransactionError::Abi(format!("length {} exceeds {}", len, u16::MAX)))?;
+    Ok(len.to_be_bytes().to_vec())
+}
+
+fn decode_length(bytes: &[u8]) -> Result<(usize, &[u8]), TransactionError> {
+    if bytes.len() < LENGTH_ENCODE_BYTE_LEN {
+        return Err(TransactionError::Abi(
+            "not enough bytes to decode length".to_owned(),
+        ));
+    }
+    let len = u16::from_be_bytes([bytes[0], bytes[1]]) as usize;
+    Ok((len, &bytes[LENGTH_ENCODE_BYTE_LEN..]))
+}
+
+fn expect_len(abi_type: &AbiType, bytes: &[u8], len: usize) -> Result<(), TransactionError> {
+    if bytes.len() != len {
+        return Err(TransactionError::Abi(format!(
+            "expected {} bytes to decode {}, got {}",
+            len,
+            abi_type,
+            bytes.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Head/tail encoding of a tuple (static arrays and the content of dynamic arrays are encoded as tuples).
+fn encode_tuple(types: &[AbiType], values: &[AbiValue]) -> Result<Vec<u8>, TransactionError> {
+    let mut heads: Vec<Vec<u8>> = vec![];
+    let mut tails: Vec<Option<Vec<u8>>> = vec![];
+    let mut i = 0;
+    while i < types.len() {
+        if types[i] == AbiType::Bool {
+            let bools = consecutive_bool_count(&types[i..]).min(8);
+            let mut byte = 0u8;
+            for (bit, value) in values[i..i + bools].iter().enumerate() {
+                match value {
+                    AbiValue::Bool(true) => byte |= 0x80 >> bit,
+                    AbiValue::Bool(false) => {}
+                    _ => {
+                        return Err(TransactionError::Abi(format!(
+                            "value {:?} can't be encoded as bool",
+                            value
+                        )))
+                    }
+                }
+            }
+            heads.push(vec![byte]);
+            tails.push(None);
+            i += bools;
+        } else if types[i].is_dynamic() {
+            // Placeholder, replaced with the offset once all heads are known
+            heads.push(vec![0; LENGTH_ENCODE_BYTE_LEN]);
+            tails.push(Some(values[i].encode(&types[i])?));
+            i += 1;
+        } else {
+            heads.push(values[i].encode(&types[i])?);
+            tails.push(None);
+            i += 1;
+        }
+    }
+
+    let mut offset: usize = heads.iter().map(Vec::len).sum();
+    for (head, tail) in heads.iter_mut().zip(tails.iter()) {
+        if let Some(tail) = tail {
+            *head = encode_length(offset)?;
+            offset += tail.len();
+        }
+    }
+
+    Ok(heads
+        .into_iter()
+        .chain(tails.into_iter().flatten())
+        .flatten()
+        .collect())
+}
+
+fn decode_tuple(types: &[AbiType], bytes: &[u8]) -> Result<Vec<AbiValue>, TransactionError> {
+    let not_enough_bytes = || TransactionError::Abi("not enough bytes to decode tuple".to_owned());
+
+    let mut values: Vec<Option<AbiValue>> = vec![];
+    // Element index and offset of dynamic elements
+    let mut dynamic: Vec<(usize, usize)> = vec![];
+    let mut pos = 0;
+    let mut i = 0;
+    while i < types.len() {
+        if types[i] == AbiType::Bool {
+            let bools = consecutive_bool_count(&types[i..]).min(8);
+            let byte = *bytes.get(pos).ok_or_else(not_enough_bytes)?;
+            for bit in 0..bools {
+                values.push(Some(AbiValue::Bool(byte & (0x80 >> bit) != 0)));
+            }
+            pos += 1;
+            i += bools;
+        } else if types[i].is_dynamic() {
+            let (offset, _) = decode_length(bytes.get(pos..).ok_or_else(not_enough_bytes)?)?;
+            dynamic.push((i, offset));
+            values.push(None);
+            pos += LENGTH_ENCODE_BYTE_LEN;
+            i += 1;
+        } else {
+            let len = types[i].byte_len()?;
+            let element = bytes.get(pos..pos + len).ok_or_else(not_enough_bytes)?;
+            values.push(Some(AbiValue::decode(&types[i], element)?));
+            pos += len;
+            i += 1;
+        }
+    }
+
+    for (k, (index, offset)) in dynamic.iter().enumerate() {
+        let end = dynamic.get(k + 1).map_or(bytes.len(), |(_, next)| *next);
+        if *offset != pos || end < *offset || end > bytes.len() {
+            return Err(TransactionError::Abi(format!(
+                "invalid dynamic element offset: {}",
+                offset
+            )));
+        }
+        values[*index] = Some(AbiValue::decode(&types[*index], &bytes[*offset..end])?);
+        pos = end;
+    }
+
+    if pos != bytes.len() {
+        return Err(TransactionError::Abi(format!(
+            "{} unexpected trailing bytes",
+            bytes.len() - pos
+        )));
+    }
+
+    Ok(values.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(abi_type: &str, value: AbiValue, expected: &[u8]) {
+        let abi_type: AbiType = abi_type.parse().unwrap();
+        let encoded = value.encode(&abi_type).unwrap();
+        assert_eq!(expected, encoded.as_slice());
+        assert_eq!(value, AbiValue::decode(&abi_type, &encoded).unwrap());
+    }
+
+    fn bools(values: &[bool]) -> AbiValue {
+        AbiValue::Array(values.iter().map(|b| AbiValue::Bool(*b)).collect())
+    }
+
+    #[test]
+    fn test_uint() {
+        round_trip("uint8", 0u64.into(), &[0]);
+        round_trip("uint16", 3u64.into(), &[0, 3]);
+        round_trip("uint64", 256u64.into(), &[0, 0, 0, 0, 0, 0, 1, 0]);
+        round_trip(
+            "uint64",
+            u64::MAX.into(),
+            &[255, 255, 255, 255, 255, 255, 255, 255],
+        );
+        let max_512: BigUint = (BigUint::from(1u8) << 512) - 1u8;
+        round_trip("uint512", max_512.into(), &[255; 64]);
+    }
+
+    #[test]
+    fn test_uint_overflow() {
+        let t: AbiType = "uint8".parse().unwrap();
+        assert!(AbiValue::from(256u64).encode(&t).is_err());
+        let t: AbiType = "uint512".parse().unwrap();
+        assert!(AbiValue::Int(BigUint::from(1u8) << 512).encode(&t).is_err());
+    }
+
+    #[test]
+    fn test_ufixed() {
+        round_trip("ufixed8x1", 10u64.into(), &[10]);
+        round_trip(
+            "ufixed64x10",
+            12345u64.into(),
+            &[0, 0, 0, 0, 0, 0, 0x30, 0x39],
+        );
+    }
+
+    #[test]
+    fn test_byte_bool_address() {
+        round_trip("byte", AbiValue::Byte(10), &[10]);
+        round_trip("bool", true.into(), &[0x80]);
+        round_trip("bool", false.into(), &[0x00]);
+        round_trip("address", Address::new([7; 32]).into(), &[7; 32]);
+    }
+
+    #[test]
+    fn test_invalid_bool() {
+        let t: AbiType = "bool".parse().unwrap();
+        assert!(AbiValue::decode(&t, &[0x01]).is_err());
+    }
+
+    #[test]
+    fn test_string() {
+        round_trip("string", "".into(), &[0, 0]);
+        round_trip("string", "asdf".into(), &[0, 4, b'a', b's', b'd', b'f']);
+        round_trip(
+            "string",
+            "What's new".into(),
+            &[
+                0x00, 0x0a, 0x57, 0x68, 0x61, 0x74, 0x27, 0x73, 0x20, 0x6e, 0x65, 0x77,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_bool_arrays() {
+        round_trip("bool[3]", bools(&[true, true, false]), &[0xc0]);
+        round_trip(
+            "bool[]",
+            bools(&[true, false, false, true, true]),
+            &[0x00, 0x05, 0x98],
+        );
+        round_trip(
+            "bool[10]",
+            bools(&[
+                false, false, false, true, false, false, true, true, true, false,
+            ]),
+            &[0x13, 0x80],
+        );
+        round_trip("bool[]", bools(&[]), &[0, 0]);
+    }
+
+    #[test]
+    fn test_static_and_dynamic_arrays() {
+        round_trip(
+            "uint64[2]",
+            AbiValue::Array(vec![1u64.into(), 2u64.into()]),
+            &[0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2],
+        );
+        round_trip("byte[]", AbiValue::bytes(&[1, 2, 3]), &[0, 3, 1, 2, 3]);
+        round_trip("byte[3]", AbiValue::bytes(&[1, 2, 3]), &[1, 2, 3]);
+        round_trip(
+            "string[2]",
+            AbiValue::Array(vec!["ab".into(), "cd".into()]),
+            &[0, 4, 0, 8, 0, 2, b'a', b'b', 0, 2, b'c', b'd'],
+        );
+    }
+
+    #[test]
+    fn test_tuples() {
+        round_trip("()", AbiValue::Tuple(vec![]), &[]);
+        // Example from the ARC-4 specification
+        round_trip(
+            "(string,bool,bool,bool,bool,string)",
+            AbiValue::Tuple(vec![
+                "AB".into(),
+                true.into(),
+                false.into(),
+                true.into(),
+                false.into(),
+                "DE".into(),
+            ]),
+            &[
+                0x00, 0x05, 0xa0, 0x00, 0x09, 0x00, 0x02, 0x41, 0x42, 0x00, 0x02, 0x44, 0x45,
+            ],
+        );
+        round_trip(
+            "(uint16,(byte,bool)[],bool)",
+            AbiValue::Tuple(vec![
+                4u64.into(),
+                AbiValue::Array(vec![
+                    AbiValue::Tuple(vec![AbiValue::Byte(1), true.into()]),
+                    AbiValue::Tuple(vec![AbiValue::Byte(2), false.into()]),
+                ]),
+                true.into(),
+            ]),
+            &[0, 4, 0, 5, 0x80, 0, 2, 1, 0x80, 2, 0],
+        );
+        round_trip(
+            "(bool[2],bool[])",
+            AbiValue::Tuple(vec![bools(&[true, true]), bools(&[true, true])]),
+            &[0xc0, 0, 3, 0, 2, 0xc0],
+        );
+    }
+
+    #[test]
+    fn test_mismatching_values() {
+        let t: AbiType = "(uint64,bool)".parse().unwrap();
+        assert!(AbiValue::Tuple(vec![1u64.into()]).encode(&t).is_err());
+        assert!(AbiValue::Tuple(vec![true.into(), 1u64.into()])
+            .encode(&t)
+            .is_err());
+        let t: AbiType = "uint64[2]".parse().unwrap();
+        assert!(AbiValue::Array(vec![1u64.into()]).encode(&t).is_err());
+    }
+
+    #[test]
+    fn test_decode_invalid_lengths() {
+        let t: AbiType = "uint64".parse().unwrap();
+        assert!(AbiValue::decode(&t, &[0; 7]).is_err());
+        let t: AbiType = "string".parse().unwrap();
+        assert!(AbiValue::decode(&t, &[0, 3, b'a']).is_err());
+        let t: AbiType = "(uint8,string)".parse().unwrap();
+        assert!(AbiValue::decode(&t, &[1, 0, 4, 0, 0]).is_err());
+        let t: AbiType = "byte[2]".parse().unwrap();
+        assert!(AbiValue::decode(&t, &[1, 2, 3]).is_err());
+    }
+}
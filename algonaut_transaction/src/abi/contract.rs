@@ -0,0 +1,147 @@
+use super::method::Method;
+use crate::error::TransactionError;
+use algonaut_crypto::HashDigest;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Network specific information of a contract.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractNetworkInfo {
+    #[serde(rename = "appID")]
+    pub app_id: u64,
+}
+
+/// An ARC-4 contract description: the methods of an application and its ids per network.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contract {
+    pub name: String,
+    #[serde(rename = "desc", default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Network information by base64 encoded genesis hash.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub networks: HashMap<String, ContractNetworkInfo>,
+    pub methods: Vec<Method>,
+}
+
+/// An ARC-4 interface description: a group of methods that can be implemented by contracts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interface {
+    pub name: String,
+    #[serde(rename = "desc", default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub methods: Vec<Method>,
+}
+
+impl Contract {
+    pub fn from_json(json: &str) -> Result<Contract, TransactionError> {
+        serde_json::from_str(json)
+            .map_err(|e| TransactionError::Abi(format!("invalid contract json: {}", e)))
+    }
+
+    /// The application id of the contract in the network with this genesis hash.
+    pub fn app_id(&self, genesis_hash: &HashDigest) -> Option<u64> {
+        self.networks
+            .get(&genesis_hash.to_string())
+            .map(|info| info.app_id)
+    }
+
+    pub fn method_by_name(&self, name: &str) -> Result<&Method, TransactionError> {
+        method_by_name(&self.methods, name)
+    }
+}
+
+impl Interface {
+    pub fn from_json(json: &str) -> Result<Interface, TransactionError> {
+        serde_json::from_str(json)
+            .map_err(|e| TransactionError::Abi(format!("invalid interface json: {}", e)))
+    }
+
+    pub fn method_by_name(&self, name: &str) -> Result<&Method, TransactionError> {
+        method_by_name(&self.methods, name)
+    }
+}
+
+/// Finds the method with this name, failing if there's none or it's overloaded.
+fn method_by_name<'a>(methods: &'a [Method], name: &str) -> Result<&'a Method, TransactionError> {
+    let mut found = methods.iter().filter(|m| m.name == name);
+    match (found.next(), found.next()) {
+        (Some(method), None) => Ok(method),
+        (None, _) => Err(TransactionError::Abi(format!("method not found: {}", name))),
+        (Some(_), Some(_)) => Err(TransactionError::Abi(format!(
+            "method {} is overloaded, select it by signature",
+            name
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const CONTRACT: &str = r#"{
+        "name": "Calculator",
+        "desc": "Calculator contract",
+        "networks": {
+            "wGHE2Pwdvd7S12BL5FaOP20EGYesN73ktiC1qzkkit8=": { "appID": 1234 },
+            "SGO1GKSzyE7IEPItTxCByw9x8FmnrCDexi9/cOUJOiI=": { "appID": 5678 }
+        },
+        "methods": [
+            {
+                "name": "add",
+                "desc": "Calculate the sum of two 64-bit integers",
+                "args": [
+                    { "type": "uint64", "name": "a" },
+                    { "type": "uint64", "name": "b" }
+                ],
+                "returns": { "type": "uint128" }
+            },
+            {
+                "name": "pay",
+                "args": [{ "type": "pay" }, { "type": "account" }],
+                "returns": { "type": "void" }
+            },
+            { "name": "pay", "args": [], "returns": { "type": "void" } }
+        ]
+    }"#;
+
+    #[test]
+    fn test_contract_from_json() {
+        let contract = Contract::from_json(CONTRACT).unwrap();
+        assert_eq!("Calculator", contract.name);
+        assert_eq!(3, contract.methods.len());
+
+        let mainnet = HashDigest::from_str("wGHE2Pwdvd7S12BL5FaOP20EGYesN73ktiC1qzkkit8=").unwrap();
+        assert_eq!(Some(1234), contract.app_id(&mainnet));
+        assert_eq!(None, contract.app_id(&HashDigest([0; 32])));
+
+        let add = contract.method_by_name("add").unwrap();
+        assert_eq!("add(uint64,uint64)uint128", add.signature());
+        assert!(contract.method_by_name("pay").is_err());
+        assert!(contract.method_by_name("sub").is_err());
+    }
+
+    #[test]
+    fn test_interface_from_json() {
+        let interface = Interface::from_json(
+            r#"{
+                "name": "Calculator",
+                "methods": [
+                    { "name": "add", "args": [{ "type": "uint64" }, { "type": "uint64" }], "returns": { "type": "uint128" } }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(None, interface.description);
+        assert_eq!(
+            [0x8a, 0xa3, 0xb6, 0x1f],
+            interface.method_by_name("add").unwrap().selector()
+        );
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        assert!(Contract::from_json(r#"{ "name": "C", "methods": [{ "name": "m", "args": [{ "type": "uint7" }], "returns": { "type": "void" } }] }"#).is_err());
+        assert!(Interface::from_json(r#"{ "methods": [] }"#).is_err());
+    }
+}
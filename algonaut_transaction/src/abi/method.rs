@@ -0,0 +1,554 @@
+use super::abi_type::{split_tuple, AbiType};
+use super::abi_value::AbiValue;
+use crate::error::TransactionError;
+use crate::transaction::TransactionType;
+use algonaut_core::Address;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// Max number of application arguments. Method arguments beyond the 15th are packed into a tuple.
+const MAX_APP_ARGS: usize = 16;
+
+/// Transaction argument types: the transaction is placed in the group before the method call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransactionArgType {
+    /// Any transaction type (`txn`).
+    Any,
+    Payment,
+    KeyRegistration,
+    AssetConfig,
+    AssetTransfer,
+    AssetFreeze,
+    ApplicationCall,
+}
+
+/// Reference argument types: the value is placed in the corresponding foreign array and encoded as `uint8` index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReferenceArgType {
+    Account,
+    Asset,
+    Application,
+}
+
+/// Type of a method argument.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum AbiArgType {
+    AbiObj(AbiType),
+    Tx(TransactionArgType),
+    Ref(ReferenceArgType),
+}
+
+/// Type of a method return value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum AbiReturnType {
+    Some(AbiType),
+    Void,
+}
+
+/// Value of a non-transaction method argument.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbiArgValue {
+    AbiValue(AbiValue),
+    Account(Address),
+    Asset(u64),
+    Application(u64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MethodArg {
+    #[serde(rename = "type")]
+    pub arg_type: AbiArgType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "desc", default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MethodReturn {
+    #[serde(rename = "type")]
+    pub return_type: AbiReturnType,
+    #[serde(rename = "desc", default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// An ARC-4 method description.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Method {
+    pub name: String,
+    #[serde(rename = "desc", default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub args: Vec<MethodArg>,
+    pub returns: MethodReturn,
+}
+
+/// Accounts, assets and applications referenced by an application call.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AppCallReferences {
+    pub accounts: Vec<Address>,
+    pub foreign_assets: Vec<u64>,
+    pub foreign_apps: Vec<u64>,
+}
+
+impl Method {
+    /// Parses a method signature, e.g. `add(uint64,uint64)uint128`.
+    pub fn from_signature(signature: &str) -> Result<Method, TransactionError> {
+        let invalid = || TransactionError::Abi(format!("invalid method signature: {}", signature));
+
+        let open = signature.find('(').ok_or_else(invalid)?;
+        let mut depth = 0usize;
+        let mut close = None;
+        for (i, c) in signature[open..].char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        close = Some(open + i);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let close = close.ok_or_else(invalid)?;
+
+        let name = &signature[..open];
+        if name.is_empty() {
+            return Err(invalid());
+        }
+        let args = split_tuple(&signature[open + 1..close])
+            .ok_or_else(invalid)?
+            .into_iter()
+            .map(|arg| {
+                Ok(MethodArg {
+                    arg_type: arg.parse()?,
+                    name: None,
+                    description: None,
+                })
+            })
+            .collect::<Result<Vec<_>, TransactionError>>()?;
+        let returns = MethodReturn {
+            return_type: signature[close + 1..].parse()?,
+            description: None,
+        };
+
+        Ok(Method {
+            name: name.to_owned(),
+            description: None,
+            args,
+            returns,
+        })
+    }
+
+    /// The method signature, e.g. `add(uint64,uint64)uint128`.
+    pub fn signature(&self) -> String {
+        let args: Vec<String> = self.args.iter().map(|a| a.arg_type.to_string()).collect();
+        format!(
+            "{}({}){}",
+            self.name,
+            args.join(","),
+            self.returns.return_type
+        )
+    }
+
+    /// The first 4 bytes of the SHA-512/256 hash of the method signature.
+    pub fn selector(&self) -> [u8; 4] {
+        let hash = sha2::Sha512_256::digest(self.signature().as_bytes());
+        let mut selector = [0; 4];
+        selector.copy_from_slice(&hash[..4]);
+        selector
+    }
+
+    /// Number of transaction arguments, which have to precede the method call in the group.
+    pub fn transaction_arg_count(&self) -> usize {
+        self.args
+            .iter()
+            .filter(|a| matches!(a.arg_type, AbiArgType::Tx(_)))
+            .count()
+    }
+
+    /// Encodes the application arguments to call this method.
+    ///
+    /// `args` contains a value for each non-transaction argument, in order.
+    /// Referenced accounts, assets and applications are added to `references` if not already present
+    /// and replaced by their index. The sender and the called application have index 0.
+    pub fn encode_app_arguments(
+        &self,
+        args: &[AbiArgValue],
+        sender: &Address,
+        app_id: u64,
+        references: &mut AppCallReferences,
+    ) -> Result<Vec<Vec<u8>>, TransactionError> {
+        let arg_types: Vec<&AbiArgType> = self
+            .args
+            .iter()
+            .map(|a| &a.arg_type)
+            .filter(|t| !matches!(t, AbiArgType::Tx(_)))
+            .collect();
+        if arg_types.len() != args.len() {
+            return Err(TransactionError::Abi(format!(
+                "method {} expects {} non-transaction arguments, got {}",
+                self.signature(),
+                arg_types.len(),
+                args.len()
+            )));
+        }
+
+        let mut types = vec![];
+        let mut values = vec![];
+        for (arg_type, arg) in arg_types.into_iter().zip(args) {
+            let (abi_type, value) = match (arg_type, arg) {
+                (AbiArgType::AbiObj(abi_type), AbiArgValue::AbiValue(value)) => {
+                    (abi_type.clone(), value.clone())
+                }
+                (AbiArgType::Ref(ReferenceArgType::Account), AbiArgValue::Account(address)) => {
+                    let index = if address == sender {
+                        0
+                    } else {
+                        1 + index_or_push(&mut references.accounts, *address)
+                    };
+                    (AbiType::Uint(8), AbiValue::from(index as u64))
+                }
+                (AbiArgType::Ref(ReferenceArgType::Asset), AbiArgValue::Asset(asset_id)) => {
+                    let index = index_or_push(&mut references.foreign_assets, *asset_id);
+                    (AbiType::Uint(8), AbiValue::from(index as u64))
+                }
+                (AbiArgType::Ref(ReferenceArgType::Application), AbiArgValue::Application(id)) => {
+                    let index = if *id == app_id {
+                        0
+                    } else {
+                        1 + index_or_push(&mut references.foreign_apps, *id)
+                    };
+                    (AbiType::Uint(8), AbiValue::from(index as u64))
+                }
+                _ => {
+                    return Err(TransactionError::Abi(format!(
+                        "argument {:?} doesn't match type {}",
+                        arg, arg_type
+                    )))
+                }
+            };
+            types.push(abi_type);
+            values.push(value);
+        }
+
+        // Arguments beyond the 15th are encoded as a tuple in the last application argument
+        if types.len() > MAX_APP_ARGS - 1 {
+            let tuple_type = AbiType::Tuple(types.split_off(MAX_APP_ARGS - 2));
+            let tuple_value = AbiValue::Tuple(values.split_off(MAX_APP_ARGS - 2));
+            types.push(tuple_type);
+            values.push(tuple_value);
+        }
+
+        let mut app_arguments = vec![self.selector().to_vec()];
+        for (abi_type, value) in types.iter().zip(values.iter()) {
+            app_arguments.push(value.encode(abi_type)?);
+        }
+        Ok(app_arguments)
+    }
+}
+
+impl TransactionArgType {
+    /// Whether a transaction of this type can be passed as argument of this type.
+    pub fn accepts(&self, txn_type: &TransactionType) -> bool {
+        matches!(
+            (self, txn_type),
+            (TransactionArgType::Any, _)
+                | (TransactionArgType::Payment, TransactionType::Payment(_))
+                | (
+                    TransactionArgType::KeyRegistration,
+                    TransactionType::KeyRegistration(_)
+                )
+                | (
+                    TransactionArgType::AssetConfig,
+                    TransactionType::AssetConfigurationTransaction(_)
+                )
+                | (
+                    TransactionArgType::AssetTransfer,
+                    TransactionType::AssetTransferTransaction(_)
+                        | TransactionType::AssetAcceptTransaction(_)
+                        | TransactionType::AssetClawbackTransaction(_)
+                )
+                | (
+                    TransactionArgType::AssetFreeze,
+                    TransactionType::AssetFreezeTransaction(_)
+                )
+                | (
+                    TransactionArgType::ApplicationCall,
+                    TransactionType::ApplicationCallTransaction(_)
+                )
+        )
+    }
+}
+
+fn index_or_push<T: PartialEq>(items: &mut Vec<T>, item: T) -> usize {
+    match items.iter().position(|i| *i == item) {
+        Some(index) => index,
+        None => {
+            items.push(item);
+            items.len() - 1
+        }
+    }
+}
+
+impl Display for TransactionArgType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            TransactionArgType::Any => "txn",
+            TransactionArgType::Payment => "pay",
+            TransactionArgType::KeyRegistration => "keyreg",
+            TransactionArgType::AssetConfig => "acfg",
+            TransactionArgType::AssetTransfer => "axfer",
+            TransactionArgType::AssetFreeze => "afrz",
+            TransactionArgType::ApplicationCall => "appl",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl Display for ReferenceArgType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ReferenceArgType::Account => "account",
+            ReferenceArgType::Asset => "asset",
+            ReferenceArgType::Application => "application",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl Display for AbiArgType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AbiArgType::AbiObj(abi_type) => write!(f, "{}", abi_type),
+            AbiArgType::Tx(tx_type) => write!(f, "{}", tx_type),
+            AbiArgType::Ref(ref_type) => write!(f, "{}", ref_type),
+        }
+    }
+}
+
+impl Display for AbiReturnType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AbiReturnType::Some(abi_type) => write!(f, "{}", abi_type),
+            AbiReturnType::Void => write!(f, "void"),
+        }
+    }
+}
+
+impl FromStr for AbiArgType {
+    type Err = TransactionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "txn" => AbiArgType::Tx(TransactionArgType::Any),
+            "pay" => AbiArgType::Tx(TransactionArgType::Payment),
+            "keyreg" => AbiArgType::Tx(TransactionArgType::KeyRegistration),
+            "acfg" => AbiArgType::Tx(TransactionArgType::AssetConfig),
+            "axfer" => AbiArgType::Tx(TransactionArgType::AssetTransfer),
+            "afrz" => AbiArgType::Tx(TransactionArgType::AssetFreeze),
+            "appl" => AbiArgType::Tx(TransactionArgType::ApplicationCall),
+            "account" => AbiArgType::Ref(ReferenceArgType::Account),
+            "asset" => AbiArgType::Ref(ReferenceArgType::Asset),
+            "application" => AbiArgType::Ref(ReferenceArgType::Application),
+            _ => AbiArgType::AbiObj(s.parse()?),
+        })
+    }
+}
+
+impl FromStr for AbiReturnType {
+    type Err = TransactionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "void" => Ok(AbiReturnType::Void),
+            _ => Ok(AbiReturnType::Some(s.parse()?)),
+        }
+    }
+}
+
+impl TryFrom<String> for AbiArgType {
+    type Error = TransactionError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<AbiArgType> for String {
+    fn from(t: AbiArgType) -> Self {
+        t.to_string()
+    }
+}
+
+impl TryFrom<String> for AbiReturnType {
+    type Error = TransactionError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<AbiReturnType> for String {
+    fn from(t: AbiReturnType) -> Self {
+        t.to_string()
+    }
+}
+
+impl From<AbiValue> for AbiArgValue {
+    fn from(value: AbiValue) -> Self {
+        AbiArgValue::AbiValue(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_and_selector() {
+        let method = Method::from_signature("add(uint64,uint64)uint128").unwrap();
+        assert_eq!("add", method.name);
+        assert_eq!(2, method.args.len());
+        assert_eq!(
+            AbiReturnType::Some(AbiType::Uint(128)),
+            method.returns.return_type
+        );
+        assert_eq!("add(uint64,uint64)uint128", method.signature());
+        assert_eq!([0x8a, 0xa3, 0xb6, 0x1f], method.selector());
+    }
+
+    #[test]
+    fn test_signature_with_tuples_and_references() {
+        let signature = "swap((uint64,bool)[],pay,account,asset,application)(string,byte[])";
+        let method = Method::from_signature(signature).unwrap();
+        assert_eq!(signature, method.signature());
+        assert_eq!(1, method.transaction_arg_count());
+        assert_eq!(
+            AbiArgType::Ref(ReferenceArgType::Application),
+            method.args[4].arg_type
+        );
+
+        let method = Method::from_signature("optIn()void").unwrap();
+        assert!(method.args.is_empty());
+        assert_eq!(AbiReturnType::Void, method.returns.return_type);
+    }
+
+    #[test]
+    fn test_invalid_signatures() {
+        for s in &[
+            "add",
+            "(uint64)void",
+            "add(uint64",
+            "add(uint64)",
+            "add(uint7)void",
+        ] {
+            assert!(Method::from_signature(s).is_err(), "{} should not parse", s);
+        }
+    }
+
+    #[test]
+    fn test_json() {
+        let json = r#"{
+            "name": "add",
+            "desc": "Calculate the sum of two 64-bit integers",
+            "args": [
+                { "type": "uint64", "name": "a", "desc": "The first term to add" },
+                { "type": "uint64", "name": "b", "desc": "The second term to add" }
+            ],
+            "returns": { "type": "uint128", "desc": "The sum of a and b" }
+        }"#;
+        let method: Method = serde_json::from_str(json).unwrap();
+        assert_eq!("add(uint64,uint64)uint128", method.signature());
+        assert_eq!(Some("a".to_owned()), method.args[0].name);
+
+        let serialized = serde_json::to_string(&method).unwrap();
+        assert_eq!(method, serde_json::from_str(&serialized).unwrap());
+    }
+
+    #[test]
+    fn test_encode_app_arguments_with_references() {
+        let method =
+            Method::from_signature("foo(account,uint16,asset,application,account,pay)void")
+                .unwrap();
+        let sender = Address::new([1; 32]);
+        let other = Address::new([2; 32]);
+        let mut references = AppCallReferences {
+            accounts: vec![other],
+            foreign_assets: vec![],
+            foreign_apps: vec![],
+        };
+        let app_arguments = method
+            .encode_app_arguments(
+                &[
+                    AbiArgValue::Account(sender),
+                    AbiValue::from(5u64).into(),
+                    AbiArgValue::Asset(10),
+                    AbiArgValue::Application(20),
+                    AbiArgValue::Account(other),
+                ],
+                &sender,
+                123,
+                &mut references,
+            )
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                method.selector().to_vec(),
+                vec![0],
+                vec![0, 5],
+                vec![0],
+                vec![1],
+                vec![1]
+            ],
+            app_arguments
+        );
+        assert_eq!(vec![other], references.accounts);
+        assert_eq!(vec![10], references.foreign_assets);
+        assert_eq!(vec![20], references.foreign_apps);
+    }
+
+    #[test]
+    fn test_encode_app_arguments_packs_extra_args() {
+        let args = vec!["uint8"; 17].join(",");
+        let method = Method::from_signature(&format!("many({})void", args)).unwrap();
+        let values: Vec<AbiArgValue> = (0..17u64).map(|i| AbiValue::from(i).into()).collect();
+        let app_arguments = method
+            .encode_app_arguments(
+                &values,
+                &Address::new([0; 32]),
+                1,
+                &mut AppCallReferences::default(),
+            )
+            .unwrap();
+
+        assert_eq!(16, app_arguments.len());
+        assert_eq!(vec![13], app_arguments[14]);
+        assert_eq!(vec![14, 15, 16], app_arguments[15]);
+    }
+
+    #[test]
+    fn test_encode_app_arguments_mismatch() {
+        let method = Method::from_signature("foo(uint64,asset)void").unwrap();
+        let sender = Address::new([0; 32]);
+        let mut references = AppCallReferences::default();
+        assert!(method
+            .encode_app_arguments(&[AbiValue::from(1u64).into()], &sender, 1, &mut references)
+            .is_err());
+        assert!(method
+            .encode_app_arguments(
+                &[AbiValue::from(1u64).into(), AbiArgValue::Application(2)],
+                &sender,
+                1,
+                &mut references
+            )
+            .is_err());
+    }
+}
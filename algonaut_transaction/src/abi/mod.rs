@@ -0,0 +1,15 @@
+//! ARC-4 application binary interface (ABI) types and value encoding.
+//!
+//! See <https://github.com/algorandfoundation/ARCs/blob/main/ARCs/arc-0004.md>
+pub mod abi_type;
+pub mod abi_value;
+pub mod contract;
+pub mod method;
+
+pub use abi_type::AbiType;
+pub use abi_value::AbiValue;
+pub use contract::{Contract, ContractNetworkInfo, Interface};
+pub use method::{
+    AbiArgType, AbiArgValue, AbiReturnType, AppCallReferences, Method, MethodArg, MethodReturn,
+    ReferenceArgType, TransactionArgType,
+};
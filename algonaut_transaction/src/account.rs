@@ -2,7 +2,7 @@ use std::convert::TryInto;
 
 use crate::auction::{Bid, SignedBid};
 use crate::error::TransactionError;
-use crate::transaction::{SignedTransaction, Transaction, TransactionSignature};
+use crate::transaction::{auth_address, SignedTransaction, Transaction, TransactionSignature};
 use algonaut_core::{
     Address, CompiledTeal, MultisigAddress, MultisigSignature, MultisigSubsig, ToMsgPack,
 };
@@ -109,16 +109,19 @@ impl Account {
     }
 
     /// Sign transaction and generate a single signature SignedTransaction
+    /// If the sender was rekeyed to this account, the auth address is set.
     pub fn sign_transaction(
         &self,
         transaction: Transaction,
     ) -> Result<SignedTransaction, TransactionError> {
         let transaction_id = transaction.id()?;
         let sig = TransactionSignature::Single(self.generate_transaction_sig(&transaction)?);
+        let auth_address = auth_address(&transaction, self.address);
         Ok(SignedTransaction {
             transaction,
             transaction_id,
             sig,
+            auth_address,
         })
     }
 
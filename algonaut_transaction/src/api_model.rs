@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::convert::{TryFrom, TryInto};
 
 use algonaut_core::{
@@ -5,15 +6,21 @@ use algonaut_core::{
     ToMsgPack, VotePk, VrfPk,
 };
 use algonaut_crypto::{HashDigest, Signature};
+use algonaut_model::algod::v2::EvalDelta;
 use num_traits::Num;
 use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
 
 use crate::{
+    block::{
+        ApplyData, Block, BlockHeader, RewardsState, SignedTransactionInBlock,
+        StateProofTrackingData, TransactionWithApplyData, UpgradeState, UpgradeVote,
+    },
     error::TransactionError,
     transaction::{
         ApplicationCallOnComplete, ApplicationCallTransaction, AssetAcceptTransaction,
         AssetClawbackTransaction, AssetConfigurationTransaction, AssetFreezeTransaction,
-        AssetParams, AssetTransferTransaction, KeyRegistration, Payment, StateSchema,
+        AssetParams, AssetTransferTransaction, BoxReference, KeyRegistration, Payment, StateSchema,
         TransactionSignature,
     },
     tx_group::TxGroup,
@@ -70,6 +77,9 @@ pub struct ApiTransaction {
     #[serde(rename = "apat", skip_serializing_if = "Option::is_none")]
     pub accounts: Option<Vec<Address>>,
 
+    #[serde(rename = "apbx", skip_serializing_if = "Option::is_none")]
+    pub boxes: Option<Vec<ApiBoxReference>>,
+
     #[serde(rename = "apep", skip_serializing_if = "Option::is_none")]
     pub extra_pages: Option<u64>,
 
@@ -120,7 +130,8 @@ pub struct ApiTransaction {
     #[serde(rename = "gen", skip_serializing_if = "Option::is_none")]
     pub genesis_id: Option<String>,
 
-    #[serde(rename = "gh")]
+    // omitted in inner transactions, which take it from the outer transaction
+    #[serde(rename = "gh", default)]
     pub genesis_hash: HashDigest,
 
     #[serde(rename = "grp", skip_serializing_if = "Option::is_none")]
@@ -177,8 +188,10 @@ pub struct ApiTransaction {
 #[derive(Default, Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub struct AppArgument(#[serde(with = "serde_bytes")] Vec<u8>);
 
-impl From<Transaction> for ApiTransaction {
-    fn from(t: Transaction) -> Self {
+impl TryFrom<Transaction> for ApiTransaction {
+    type Error = TransactionError;
+
+    fn try_from(t: Transaction) -> Result<Self, Self::Error> {
         let mut api_t = ApiTransaction {
             // Common fields
             fee: num_as_api_option(t.fee.0).map(MicroAlgos),
@@ -203,6 +216,7 @@ impl From<Transaction> for ApiTransaction {
             asset_params: None,
             foreign_assets: None,
             accounts: None,
+            boxes: None,
             foreign_apps: None,
             global_state_schema: None,
             app_id: None,
@@ -285,7 +299,28 @@ impl From<Transaction> for ApiTransaction {
                     .to_owned()
                     .map(|c| c.0)
                     .and_then(vec_as_api_option);
-                api_t.foreign_apps = call.foreign_apps.clone().and_then(vec_as_api_option);
+                let foreign_apps = call.foreign_apps.clone().unwrap_or_default();
+                api_t.boxes = call
+                    .boxes
+                    .as_ref()
+                    .map(|boxes| {
+                        boxes
+                            .iter()
+                            .map(|b| {
+                                Ok(ApiBoxReference {
+                                    index: num_as_api_option(box_app_index(
+                                        b.app_id,
+                                        call.app_id,
+                                        &foreign_apps,
+                                    )?),
+                                    name: vec_as_api_option(b.name.clone()),
+                                })
+                            })
+                            .collect::<Result<Vec<_>, TransactionError>>()
+                    })
+                    .transpose()?
+                    .and_then(vec_as_api_option);
+                api_t.foreign_apps = vec_as_api_option(foreign_apps);
                 api_t.foreign_assets = call.foreign_assets.clone().and_then(vec_as_api_option);
                 api_t.global_state_schema =
                     call.to_owned().global_state_schema.and_then(|s| s.into());
@@ -294,7 +329,7 @@ impl From<Transaction> for ApiTransaction {
                 api_t.extra_pages = num_as_api_option(call.extra_pages);
             }
         }
-        api_t
+        Ok(api_t)
     }
 }
 
@@ -342,6 +377,16 @@ impl TryFrom<ApiTransaction> for Transaction {
             "appl" => {
                 let on_complete =
                     int_to_application_call_on_complete(num_from_api_option(api_t.on_complete))?;
+                let foreign_apps = &api_t.foreign_apps;
+                let boxes = api_t
+                    .boxes
+                    .map(|boxes| {
+                        boxes
+                            .into_iter()
+                            .map(|b| parse_box_reference(b, foreign_apps))
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                    .transpose()?;
                 TransactionType::ApplicationCallTransaction(ApplicationCallTransaction {
                     sender: api_t.sender,
                     app_id: api_t.app_id,
@@ -354,6 +399,7 @@ impl TryFrom<ApiTransaction> for Transaction {
                     clear_state_program: api_t.clear_state_program.map(CompiledTeal),
                     foreign_apps: api_t.foreign_apps,
                     foreign_assets: api_t.foreign_assets,
+                    boxes,
 
                     global_state_schema: parse_state_schema(
                         on_complete.clone(),
@@ -459,10 +505,13 @@ impl TryFrom<ApiSignedTransaction> for SignedTransaction {
     type Error = TransactionError;
 
     fn try_from(api_t: ApiSignedTransaction) -> Result<Self, Self::Error> {
+        let transaction: Transaction = api_t.transaction.clone().try_into()?;
         Ok(SignedTransaction {
-            transaction: api_t.transaction.clone().try_into()?,
-            transaction_id: api_t.transaction_id.clone(),
+            // The id isn't part of the encoding
+            transaction_id: transaction.id()?,
+            transaction,
             sig: transaction_signature(&api_t)?,
+            auth_address: api_t.auth_address,
         })
     }
 }
@@ -574,6 +623,9 @@ pub struct ApiSignedTransaction {
     #[serde(rename = "msig", skip_serializing_if = "Option::is_none")]
     pub msig: Option<MultisigSignature>,
 
+    #[serde(rename = "sgnr", skip_serializing_if = "Option::is_none")]
+    pub auth_address: Option<Address>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sig: Option<Signature>,
 
@@ -584,23 +636,520 @@ pub struct ApiSignedTransaction {
     pub transaction_id: String,
 }
 
-impl From<SignedTransaction> for ApiSignedTransaction {
-    fn from(t: SignedTransaction) -> Self {
+impl TryFrom<SignedTransaction> for ApiSignedTransaction {
+    type Error = TransactionError;
+
+    fn try_from(t: SignedTransaction) -> Result<Self, Self::Error> {
         let (sig, msig, lsig) = match t.sig {
             TransactionSignature::Single(sig) => (Some(sig), None, None),
             TransactionSignature::Multi(msig) => (None, Some(msig), None),
             TransactionSignature::Logic(lsig) => (None, None, Some(lsig)),
         };
-        ApiSignedTransaction {
+        Ok(ApiSignedTransaction {
             sig,
             msig,
             lsig: lsig.map(|l| l.into()),
-            transaction: t.transaction.into(),
+            auth_address: t.auth_address,
+            transaction: t.transaction.try_into()?,
             transaction_id: t.transaction_id,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ApiBoxReference {
+    #[serde(rename = "i", skip_serializing_if = "Option::is_none")]
+    pub index: Option<u64>,
+
+    #[serde(
+        default,
+        rename = "n",
+        with = "serde_bytes",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub name: Option<Vec<u8>>,
+}
+
+/// An unsigned transaction wrapped like goal does it: a signed transaction without signature.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiUnsignedTransaction {
+    #[serde(rename = "txn")]
+    pub transaction: ApiTransaction,
+}
+
+impl TryFrom<Transaction> for ApiUnsignedTransaction {
+    type Error = TransactionError;
+
+    fn try_from(t: Transaction) -> Result<Self, Self::Error> {
+        Ok(ApiUnsignedTransaction {
+            transaction: t.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<ApiUnsignedTransaction> for Transaction {
+    type Error = TransactionError;
+
+    fn try_from(api_t: ApiUnsignedTransaction) -> Result<Self, Self::Error> {
+        api_t.transaction.try_into()
+    }
+}
+
+/// A block: the header fields and the payset in the same map.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiBlock {
+    #[serde(flatten)]
+    pub header: ApiBlockHeader,
+
+    #[serde(rename = "txns", skip_serializing_if = "Option::is_none")]
+    pub payset: Option<Vec<ApiSignedTransactionInBlock>>,
+}
+
+/// See [ApiTransaction] doc
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ApiBlockHeader {
+    #[serde(rename = "bi", skip_serializing_if = "Option::is_none")]
+    pub bonus: Option<MicroAlgos>,
+
+    #[serde(rename = "earn", skip_serializing_if = "Option::is_none")]
+    pub rewards_level: Option<u64>,
+
+    #[serde(rename = "fc", skip_serializing_if = "Option::is_none")]
+    pub fees_collected: Option<MicroAlgos>,
+
+    #[serde(rename = "fees", skip_serializing_if = "Option::is_none")]
+    pub fee_sink: Option<Address>,
+
+    #[serde(rename = "frac", skip_serializing_if = "Option::is_none")]
+    pub rewards_residue: Option<u64>,
+
+    #[serde(rename = "gen", skip_serializing_if = "Option::is_none")]
+    pub genesis_id: Option<String>,
+
+    #[serde(rename = "gh", skip_serializing_if = "Option::is_none")]
+    pub genesis_hash: Option<HashDigest>,
+
+    #[serde(rename = "nextbefore", skip_serializing_if = "Option::is_none")]
+    pub next_protocol_vote_before: Option<Round>,
+
+    #[serde(rename = "nextproto", skip_serializing_if = "Option::is_none")]
+    pub next_protocol: Option<String>,
+
+    #[serde(rename = "nextswitch", skip_serializing_if = "Option::is_none")]
+    pub next_protocol_switch_on: Option<Round>,
+
+    #[serde(rename = "nextyes", skip_serializing_if = "Option::is_none")]
+    pub next_protocol_approvals: Option<u64>,
+
+    #[serde(rename = "partupdabs", skip_serializing_if = "Option::is_none")]
+    pub absent_participation_accounts: Option<Vec<Address>>,
+
+    #[serde(rename = "partupdrmv", skip_serializing_if = "Option::is_none")]
+    pub expired_participation_accounts: Option<Vec<Address>>,
+
+    #[serde(rename = "pp", skip_serializing_if = "Option::is_none")]
+    pub proposer_payout: Option<MicroAlgos>,
+
+    #[serde(rename = "prev", skip_serializing_if = "Option::is_none")]
+    pub previous_block_hash: Option<HashDigest>,
+
+    #[serde(rename = "proto", skip_serializing_if = "Option::is_none")]
+    pub current_protocol: Option<String>,
+
+    #[serde(rename = "prp", skip_serializing_if = "Option::is_none")]
+    pub proposer: Option<Address>,
+
+    #[serde(rename = "rate", skip_serializing_if = "Option::is_none")]
+    pub rewards_rate: Option<u64>,
+
+    #[serde(rename = "rnd", skip_serializing_if = "Option::is_none")]
+    pub round: Option<Round>,
+
+    #[serde(rename = "rwcalr", skip_serializing_if = "Option::is_none")]
+    pub rewards_recalculation_round: Option<Round>,
+
+    #[serde(rename = "rwd", skip_serializing_if = "Option::is_none")]
+    pub rewards_pool: Option<Address>,
+
+    #[serde(rename = "seed", skip_serializing_if = "Option::is_none")]
+    pub seed: Option<HashDigest>,
+
+    #[serde(rename = "spt", skip_serializing_if = "Option::is_none")]
+    pub state_proof_tracking: Option<BTreeMap<u64, ApiStateProofTrackingData>>,
+
+    #[serde(rename = "tc", skip_serializing_if = "Option::is_none")]
+    pub transaction_counter: Option<u64>,
+
+    #[serde(rename = "ts", skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+
+    #[serde(rename = "txn", skip_serializing_if = "Option::is_none")]
+    pub transactions_root: Option<HashDigest>,
+
+    #[serde(rename = "txn256", skip_serializing_if = "Option::is_none")]
+    pub transactions_root_sha256: Option<HashDigest>,
+
+    #[serde(rename = "upgradedelay", skip_serializing_if = "Option::is_none")]
+    pub upgrade_delay: Option<Round>,
+
+    #[serde(rename = "upgradeprop", skip_serializing_if = "Option::is_none")]
+    pub upgrade_propose: Option<String>,
+
+    #[serde(rename = "upgradeyes", skip_serializing_if = "Option::is_none")]
+    pub upgrade_approve: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiStateProofTrackingData {
+    #[serde(rename = "n", skip_serializing_if = "Option::is_none")]
+    pub next_round: Option<Round>,
+
+    #[serde(rename = "t", skip_serializing_if = "Option::is_none")]
+    pub online_total_weight: Option<MicroAlgos>,
+
+    #[serde(rename = "v", skip_serializing_if = "Option::is_none")]
+    pub voters_commitment: Option<ByteBuf>,
+}
+
+/// A signed transaction with its apply data. The genesis id and hash of the transaction are
+/// omitted, flags indicate if they're the ones of the block.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiSignedTransactionInBlock {
+    #[serde(flatten)]
+    pub apply_data: ApiApplyData,
+
+    #[serde(rename = "hgh", skip_serializing_if = "Option::is_none")]
+    pub has_genesis_hash: Option<bool>,
+
+    #[serde(rename = "hgi", skip_serializing_if = "Option::is_none")]
+    pub has_genesis_id: Option<bool>,
+
+    #[serde(rename = "lsig", skip_serializing_if = "Option::is_none")]
+    pub lsig: Option<ApiSignedLogic>,
+
+    #[serde(rename = "msig", skip_serializing_if = "Option::is_none")]
+    pub msig: Option<MultisigSignature>,
+
+    #[serde(rename = "sgnr", skip_serializing_if = "Option::is_none")]
+    pub auth_address: Option<Address>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sig: Option<Signature>,
+
+    #[serde(rename = "txn")]
+    pub transaction: ApiTransaction,
+}
+
+/// An inner transaction with its apply data.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiTransactionWithApplyData {
+    #[serde(flatten)]
+    pub apply_data: ApiApplyData,
+
+    #[serde(rename = "txn")]
+    pub transaction: ApiTransaction,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ApiApplyData {
+    #[serde(rename = "aca", skip_serializing_if = "Option::is_none")]
+    pub asset_closing_amount: Option<u64>,
+
+    #[serde(rename = "apid", skip_serializing_if = "Option::is_none")]
+    pub application_id: Option<u64>,
+
+    #[serde(rename = "ca", skip_serializing_if = "Option::is_none")]
+    pub closing_amount: Option<MicroAlgos>,
+
+    #[serde(rename = "caid", skip_serializing_if = "Option::is_none")]
+    pub config_asset: Option<u64>,
+
+    #[serde(rename = "dt", skip_serializing_if = "Option::is_none")]
+    pub eval_delta: Option<ApiEvalDelta>,
+
+    #[serde(rename = "rc", skip_serializing_if = "Option::is_none")]
+    pub close_rewards: Option<MicroAlgos>,
+
+    #[serde(rename = "rr", skip_serializing_if = "Option::is_none")]
+    pub receiver_rewards: Option<MicroAlgos>,
+
+    #[serde(rename = "rs", skip_serializing_if = "Option::is_none")]
+    pub sender_rewards: Option<MicroAlgos>,
+}
+
+/// The application state changes, logs and inner transactions of an application call.
+/// State keys and logs aren't necessarily valid UTF-8, so they're decoded as bytes.
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ApiEvalDelta {
+    #[serde(rename = "gd", skip_serializing_if = "Option::is_none")]
+    pub global_delta: Option<BTreeMap<ByteBuf, ApiValueDelta>>,
+
+    #[serde(rename = "itx", skip_serializing_if = "Option::is_none")]
+    pub inner_txns: Option<Vec<ApiTransactionWithApplyData>>,
+
+    #[serde(rename = "ld", skip_serializing_if = "Option::is_none")]
+    pub local_deltas: Option<BTreeMap<u64, BTreeMap<ByteBuf, ApiValueDelta>>>,
+
+    #[serde(rename = "lg", skip_serializing_if = "Option::is_none")]
+    pub logs: Option<Vec<ByteBuf>>,
+
+    #[serde(rename = "sa", skip_serializing_if = "Option::is_none")]
+    pub shared_accounts: Option<Vec<Address>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiValueDelta {
+    #[serde(rename = "at")]
+    pub action: u64,
+
+    #[serde(rename = "bs", skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<ByteBuf>,
+
+    #[serde(rename = "ui", skip_serializing_if = "Option::is_none")]
+    pub uint: Option<u64>,
+}
+
+impl TryFrom<ApiBlock> for Block {
+    type Error = TransactionError;
+
+    fn try_from(api_block: ApiBlock) -> Result<Self, Self::Error> {
+        let header: BlockHeader = api_block.header.into();
+        let payset = api_block
+            .payset
+            .unwrap_or_default()
+            .into_iter()
+            .map(|api_t| signed_transaction_in_block(api_t, &header))
+            .collect::<Result<_, _>>()?;
+        Ok(Block { header, payset })
+    }
+}
+
+impl From<ApiBlockHeader> for BlockHeader {
+    fn from(api_h: ApiBlockHeader) -> Self {
+        BlockHeader {
+            round: Round(num_from_api_option(api_h.round.map(|r| r.0))),
+            previous_block_hash: api_h.previous_block_hash.unwrap_or_default(),
+            seed: api_h.seed.unwrap_or_default(),
+            transactions_root: api_h.transactions_root.unwrap_or_default(),
+            transactions_root_sha256: api_h.transactions_root_sha256.unwrap_or_default(),
+            timestamp: num_from_api_option(api_h.timestamp),
+            genesis_id: api_h.genesis_id.unwrap_or_default(),
+            genesis_hash: api_h.genesis_hash.unwrap_or_default(),
+            proposer: api_h.proposer,
+            fees_collected: MicroAlgos(num_from_api_option(api_h.fees_collected.map(|a| a.0))),
+            bonus: MicroAlgos(num_from_api_option(api_h.bonus.map(|a| a.0))),
+            proposer_payout: MicroAlgos(num_from_api_option(api_h.proposer_payout.map(|a| a.0))),
+            rewards_state: RewardsState {
+                fee_sink: api_h.fee_sink.unwrap_or(Address([0; 32])),
+                rewards_pool: api_h.rewards_pool.unwrap_or(Address([0; 32])),
+                rewards_level: num_from_api_option(api_h.rewards_level),
+                rewards_rate: num_from_api_option(api_h.rewards_rate),
+                rewards_residue: num_from_api_option(api_h.rewards_residue),
+                rewards_recalculation_round: Round(num_from_api_option(
+                    api_h.rewards_recalculation_round.map(|r| r.0),
+                )),
+            },
+            upgrade_state: UpgradeState {
+                current_protocol: api_h.current_protocol.unwrap_or_default(),
+                next_protocol: api_h.next_protocol,
+                next_protocol_approvals: num_from_api_option(api_h.next_protocol_approvals),
+                next_protocol_vote_before: Round(num_from_api_option(
+                    api_h.next_protocol_vote_before.map(|r| r.0),
+                )),
+                next_protocol_switch_on: Round(num_from_api_option(
+                    api_h.next_protocol_switch_on.map(|r| r.0),
+                )),
+            },
+            upgrade_vote: UpgradeVote {
+                upgrade_propose: api_h.upgrade_propose,
+                upgrade_delay: Round(num_from_api_option(api_h.upgrade_delay.map(|r| r.0))),
+                upgrade_approve: bool_from_api_option(api_h.upgrade_approve),
+            },
+            transaction_counter: num_from_api_option(api_h.transaction_counter),
+            state_proof_tracking: api_h
+                .state_proof_tracking
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(proof_type, data)| {
+                    (
+                        proof_type,
+                        StateProofTrackingData {
+                            voters_commitment: data
+                                .voters_commitment
+                                .map(ByteBuf::into_vec)
+                                .unwrap_or_default(),
+                            online_total_weight: MicroAlgos(num_from_api_option(
+                                data.online_total_weight.map(|a| a.0),
+                            )),
+                            next_round: Round(num_from_api_option(data.next_round.map(|r| r.0))),
+                        },
+                    )
+                })
+                .collect(),
+            expired_participation_accounts: api_h
+                .expired_participation_accounts
+                .unwrap_or_default(),
+            absent_participation_accounts: api_h.absent_participation_accounts.unwrap_or_default(),
+            encoded: None,
+        }
+    }
+}
+
+impl From<BlockHeader> for ApiBlockHeader {
+    fn from(h: BlockHeader) -> Self {
+        let state_proof_tracking: BTreeMap<u64, ApiStateProofTrackingData> = h
+            .state_proof_tracking
+            .into_iter()
+            .map(|(proof_type, data)| {
+                (
+                    proof_type,
+                    ApiStateProofTrackingData {
+                        next_round: num_as_api_option(data.next_round.0).map(Round),
+                        online_total_weight: num_as_api_option(data.online_total_weight.0)
+                            .map(MicroAlgos),
+                        voters_commitment: vec_as_api_option(data.voters_commitment)
+                            .map(ByteBuf::from),
+                    },
+                )
+            })
+            .collect();
+        ApiBlockHeader {
+            bonus: num_as_api_option(h.bonus.0).map(MicroAlgos),
+            rewards_level: num_as_api_option(h.rewards_state.rewards_level),
+            fees_collected: num_as_api_option(h.fees_collected.0).map(MicroAlgos),
+            fee_sink: address_as_api_option(h.rewards_state.fee_sink),
+            rewards_residue: num_as_api_option(h.rewards_state.rewards_residue),
+            genesis_id: str_as_api_option(h.genesis_id),
+            genesis_hash: hash_as_api_option(h.genesis_hash),
+            next_protocol_vote_before: num_as_api_option(
+                h.upgrade_state.next_protocol_vote_before.0,
+            )
+            .map(Round),
+            next_protocol: h.upgrade_state.next_protocol.and_then(str_as_api_option),
+            next_protocol_switch_on: num_as_api_option(h.upgrade_state.next_protocol_switch_on.0)
+                .map(Round),
+            next_protocol_approvals: num_as_api_option(h.upgrade_state.next_protocol_approvals),
+            absent_participation_accounts: vec_as_api_option(h.absent_participation_accounts),
+            expired_participation_accounts: vec_as_api_option(h.expired_participation_accounts),
+            proposer_payout: num_as_api_option(h.proposer_payout.0).map(MicroAlgos),
+            previous_block_hash: hash_as_api_option(h.previous_block_hash),
+            current_protocol: str_as_api_option(h.upgrade_state.current_protocol),
+            proposer: h.proposer.and_then(address_as_api_option),
+            rewards_rate: num_as_api_option(h.rewards_state.rewards_rate),
+            round: num_as_api_option(h.round.0).map(Round),
+            rewards_recalculation_round: num_as_api_option(
+                h.rewards_state.rewards_recalculation_round.0,
+            )
+            .map(Round),
+            rewards_pool: address_as_api_option(h.rewards_state.rewards_pool),
+            seed: hash_as_api_option(h.seed),
+            state_proof_tracking: if state_proof_tracking.is_empty() {
+                None
+            } else {
+                Some(state_proof_tracking)
+            },
+            transaction_counter: num_as_api_option(h.transaction_counter),
+            timestamp: num_as_api_option(h.timestamp),
+            transactions_root: hash_as_api_option(h.transactions_root),
+            transactions_root_sha256: hash_as_api_option(h.transactions_root_sha256),
+            upgrade_delay: num_as_api_option(h.upgrade_vote.upgrade_delay.0).map(Round),
+            upgrade_propose: h.upgrade_vote.upgrade_propose.and_then(str_as_api_option),
+            upgrade_approve: bool_as_api_option(h.upgrade_vote.upgrade_approve),
         }
     }
 }
 
+/// Restores the genesis id and hash of the transaction from the header, if it has the ones of the block.
+fn signed_transaction_in_block(
+    api_t: ApiSignedTransactionInBlock,
+    header: &BlockHeader,
+) -> Result<SignedTransactionInBlock, TransactionError> {
+    let mut transaction = api_t.transaction;
+    if bool_from_api_option(api_t.has_genesis_hash) {
+        transaction.genesis_hash = header.genesis_hash;
+    }
+    if bool_from_api_option(api_t.has_genesis_id) {
+        transaction.genesis_id = Some(header.genesis_id.clone());
+    }
+    let signed_transaction = ApiSignedTransaction {
+        lsig: api_t.lsig,
+        msig: api_t.msig,
+        auth_address: api_t.auth_address,
+        sig: api_t.sig,
+        transaction,
+        transaction_id: "".to_owned(),
+    }
+    .try_into()?;
+    Ok(SignedTransactionInBlock {
+        signed_transaction,
+        apply_data: api_t.apply_data.try_into()?,
+    })
+}
+
+impl TryFrom<ApiApplyData> for ApplyData {
+    type Error = TransactionError;
+
+    fn try_from(api_ad: ApiApplyData) -> Result<Self, Self::Error> {
+        let eval_delta = api_ad.eval_delta.unwrap_or_default();
+        Ok(ApplyData {
+            closing_amount: MicroAlgos(num_from_api_option(api_ad.closing_amount.map(|a| a.0))),
+            asset_closing_amount: num_from_api_option(api_ad.asset_closing_amount),
+            sender_rewards: MicroAlgos(num_from_api_option(api_ad.sender_rewards.map(|a| a.0))),
+            receiver_rewards: MicroAlgos(num_from_api_option(api_ad.receiver_rewards.map(|a| a.0))),
+            close_rewards: MicroAlgos(num_from_api_option(api_ad.close_rewards.map(|a| a.0))),
+            config_asset: api_ad.config_asset,
+            application_id: api_ad.application_id,
+            global_delta: parse_state_delta(eval_delta.global_delta.unwrap_or_default())?,
+            local_deltas: eval_delta
+                .local_deltas
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(index, delta)| Ok((index, parse_state_delta(delta)?)))
+                .collect::<Result<_, TransactionError>>()?,
+            shared_accounts: eval_delta.shared_accounts.unwrap_or_default(),
+            logs: eval_delta
+                .logs
+                .unwrap_or_default()
+                .into_iter()
+                .map(ByteBuf::into_vec)
+                .collect(),
+            inner_txns: eval_delta
+                .inner_txns
+                .unwrap_or_default()
+                .into_iter()
+                .map(|api_t| {
+                    Ok(TransactionWithApplyData {
+                        transaction: api_t.transaction.try_into()?,
+                        apply_data: api_t.apply_data.try_into()?,
+                    })
+                })
+                .collect::<Result<_, TransactionError>>()?,
+        })
+    }
+}
+
+fn parse_state_delta(
+    api_delta: BTreeMap<ByteBuf, ApiValueDelta>,
+) -> Result<BTreeMap<Vec<u8>, EvalDelta>, TransactionError> {
+    api_delta
+        .into_iter()
+        .map(|(key, value)| {
+            let delta = match value.action {
+                1 => EvalDelta::SetBytes(value.bytes.map(ByteBuf::into_vec).unwrap_or_default()),
+                2 => EvalDelta::SetUint(num_from_api_option(value.uint)),
+                3 => EvalDelta::Delete,
+                action => {
+                    return Err(TransactionError::Deserialization(format!(
+                        "Invalid eval delta action: {}",
+                        action
+                    )))
+                }
+            };
+            Ok((key.into_vec(), delta))
+        })
+        .collect()
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ApiStateSchema {
     #[serde(rename = "nbs", skip_serializing_if = "Option::is_none")]
@@ -636,9 +1185,11 @@ impl From<ApiStateSchema> for StateSchema {
 
 impl ToMsgPack for ApiTransaction {}
 impl ToMsgPack for ApiSignedTransaction {}
+impl ToMsgPack for ApiUnsignedTransaction {}
 impl ToMsgPack for Transaction {}
 impl ToMsgPack for SignedTransaction {}
 impl ToMsgPack for TxGroup {}
+impl ToMsgPack for BlockHeader {}
 
 /// Convenience to serialize Transaction directly to msg pack
 impl Serialize for Transaction {
@@ -646,7 +1197,10 @@ impl Serialize for Transaction {
     where
         S: serde::Serializer,
     {
-        let api_transaction: ApiTransaction = self.to_owned().into();
+        let api_transaction: ApiTransaction = self
+            .to_owned()
+            .try_into()
+            .map_err(serde::ser::Error::custom)?;
         api_transaction.serialize(serializer)
     }
 }
@@ -662,13 +1216,39 @@ impl<'de> Deserialize<'de> for Transaction {
     }
 }
 
+/// Serializes the header canonically, e.g. to compute its hash
+impl Serialize for BlockHeader {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let api_header: ApiBlockHeader = self.to_owned().into();
+        api_header.serialize(serializer)
+    }
+}
+
+/// Convenience to deserialize Block directly from msg pack
+impl<'de> Deserialize<'de> for Block {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        ApiBlock::deserialize(deserializer)?
+            .try_into()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 /// Convenience to serialize SignedTransaction directly to msg pack
 impl Serialize for SignedTransaction {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        let api_transaction: ApiSignedTransaction = self.to_owned().into();
+        let api_transaction: ApiSignedTransaction = self
+            .to_owned()
+            .try_into()
+            .map_err(serde::ser::Error::custom)?;
         api_transaction.serialize(serializer)
     }
 }
@@ -756,6 +1336,42 @@ fn bool_as_api_option(b: bool) -> Option<bool> {
     }
 }
 
+/// The index of the box application: 0 for the called application, otherwise its position in the
+/// foreign apps, starting at 1. The app has to be in the foreign apps, see
+/// [ApplicationCallTransaction::with_boxes].
+fn box_app_index(
+    app_id: u64,
+    called_app_id: Option<u64>,
+    foreign_apps: &[u64],
+) -> Result<u64, TransactionError> {
+    if app_id == 0 || Some(app_id) == called_app_id {
+        return Ok(0);
+    }
+    match foreign_apps.iter().position(|id| *id == app_id) {
+        Some(position) => Ok(position as u64 + 1),
+        None => Err(TransactionError::BoxAppNotInForeignApps { app_id }),
+    }
+}
+
+fn parse_box_reference(
+    api_box: ApiBoxReference,
+    foreign_apps: &Option<Vec<u64>>,
+) -> Result<BoxReference, TransactionError> {
+    let app_id = match num_from_api_option(api_box.index) {
+        0 => 0,
+        index => *foreign_apps
+            .as_ref()
+            .and_then(|apps| apps.get(index as usize - 1))
+            .ok_or_else(|| {
+                TransactionError::Deserialization(format!("Invalid box app index: {}", index))
+            })?,
+    };
+    Ok(BoxReference {
+        app_id,
+        name: api_box.name.unwrap_or_default(),
+    })
+}
+
 /// See [ApiTransaction] doc
 fn vec_as_api_option<T>(v: Vec<T>) -> Option<Vec<T>> {
     if v.is_empty() {
@@ -765,6 +1381,24 @@ fn vec_as_api_option<T>(v: Vec<T>) -> Option<Vec<T>> {
     }
 }
 
+/// See [ApiTransaction] doc
+fn hash_as_api_option(hash: HashDigest) -> Option<HashDigest> {
+    if hash == HashDigest::default() {
+        None
+    } else {
+        Some(hash)
+    }
+}
+
+/// See [ApiTransaction] doc
+fn address_as_api_option(address: Address) -> Option<Address> {
+    if address == Address([0; 32]) {
+        None
+    } else {
+        Some(address)
+    }
+}
+
 /// See [ApiTransaction] doc
 fn str_as_api_option(s: String) -> Option<String> {
     if s.is_empty() {
@@ -859,4 +1493,110 @@ mod tests {
 
         assert_eq!(lsig, lsig_deserialized);
     }
+
+    #[test]
+    fn test_serialize_box_references() {
+        let sender = Address([0; 32]);
+        let txn = Transaction {
+            fee: MicroAlgos(1000),
+            first_valid: Round(1),
+            genesis_hash: HashDigest([1; 32]),
+            last_valid: Round(1001),
+            txn_type: crate::builder::CallApplication::new(sender, 10)
+                .foreign_apps(vec![30])
+                .boxes(vec![
+                    BoxReference {
+                        app_id: 0,
+                        name: b"a".to_vec(),
+                    },
+                    BoxReference {
+                        app_id: 10,
+                        name: b"b".to_vec(),
+                    },
+                    BoxReference {
+                        app_id: 20,
+                        name: b"c".to_vec(),
+                    },
+                ])
+                .build(),
+            genesis_id: None,
+            group: None,
+            lease: None,
+            note: None,
+            rekey_to: None,
+        };
+
+        let api_t: ApiTransaction = txn.clone().try_into().unwrap();
+        assert_eq!(Some(vec![30, 20]), api_t.foreign_apps);
+        let boxes = api_t.boxes.clone().unwrap();
+        assert_eq!(
+            vec![None, None, Some(2)],
+            boxes.iter().map(|b| b.index).collect::<Vec<_>>()
+        );
+
+        let serialized = rmp_serde::to_vec_named(&api_t).unwrap();
+        let deserialized: ApiTransaction = rmp_serde::from_slice(&serialized).unwrap();
+        let txn_deserialized: Transaction = deserialized.try_into().unwrap();
+        assert_eq!(txn, txn_deserialized);
+        match txn_deserialized.txn_type {
+            TransactionType::ApplicationCallTransaction(call) => assert_eq!(
+                vec![0, 0, 20],
+                call.boxes
+                    .unwrap()
+                    .iter()
+                    .map(|b| b.app_id)
+                    .collect::<Vec<_>>()
+            ),
+            _ => panic!("expected an application call"),
+        }
+    }
+
+    #[test]
+    fn test_serialize_box_reference_missing_in_foreign_apps() {
+        let sender = Address([0; 32]);
+        let call = |foreign_apps: Option<Vec<u64>>| Transaction {
+            fee: MicroAlgos(1000),
+            first_valid: Round(1),
+            genesis_hash: HashDigest([1; 32]),
+            last_valid: Round(1001),
+            txn_type: TransactionType::ApplicationCallTransaction(ApplicationCallTransaction {
+                sender,
+                app_id: Some(10),
+                on_complete: ApplicationCallOnComplete::NoOp,
+                accounts: None,
+                approval_program: None,
+                app_arguments: None,
+                clear_state_program: None,
+                foreign_apps,
+                foreign_assets: None,
+                boxes: Some(vec![BoxReference {
+                    app_id: 20,
+                    name: b"c".to_vec(),
+                }]),
+                global_state_schema: None,
+                local_state_schema: None,
+                extra_pages: 0,
+            }),
+            genesis_id: None,
+            group: None,
+            lease: None,
+            note: None,
+            rekey_to: None,
+        };
+
+        let missing = call(None);
+        assert!(matches!(
+            ApiTransaction::try_from(missing.clone()),
+            Err(TransactionError::BoxAppNotInForeignApps { app_id: 20 })
+        ));
+        assert!(missing.to_msg_pack().is_err());
+        assert!(missing.raw_id().is_err());
+
+        let listed = call(Some(vec![20]));
+        let bytes = listed.to_msg_pack().unwrap();
+        let decoded: Transaction = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(listed, decoded);
+        assert_eq!(bytes, decoded.to_msg_pack().unwrap());
+        assert_eq!(listed.raw_id().unwrap(), decoded.raw_id().unwrap());
+    }
 }
@@ -0,0 +1,501 @@
+use crate::error::TransactionError;
+use crate::{SignedTransaction, Transaction};
+use algonaut_core::{Address, MicroAlgos, Round, ToMsgPack};
+use algonaut_crypto::HashDigest;
+use algonaut_encoding::{msgpack_map, msgpack_map_entries};
+use algonaut_model::algod::v2::EvalDelta;
+use sha2::Digest;
+use std::collections::BTreeMap;
+
+/// A block: the header and the transactions it confirms (payset).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub header: BlockHeader,
+    pub payset: Vec<SignedTransactionInBlock>,
+}
+
+/// The header of a block.
+///
+/// Zero values (e.g. an empty previous block hash in the genesis block) mean that the field is not set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub round: Round,
+
+    /// Hash of the previous block header.
+    pub previous_block_hash: HashDigest,
+
+    /// Sortition seed.
+    pub seed: HashDigest,
+
+    /// Root of the merkle tree of the payset transactions, using SHA512_256.
+    pub transactions_root: HashDigest,
+
+    /// Root of the merkle tree of the payset transactions, using SHA256.
+    pub transactions_root_sha256: HashDigest,
+
+    /// Seconds since epoch at which the block was proposed.
+    pub timestamp: i64,
+
+    pub genesis_id: String,
+
+    pub genesis_hash: HashDigest,
+
+    /// The account that proposed the block, if the protocol tracks it.
+    pub proposer: Option<Address>,
+
+    /// Fees paid by the transactions of the block.
+    pub fees_collected: MicroAlgos,
+
+    /// Incentive paid to the proposer in addition to the fees.
+    pub bonus: MicroAlgos,
+
+    /// Amount actually paid to the proposer.
+    pub proposer_payout: MicroAlgos,
+
+    pub rewards_state: RewardsState,
+
+    pub upgrade_state: UpgradeState,
+
+    pub upgrade_vote: UpgradeVote,
+
+    /// Number of transactions committed up to and including this block.
+    pub transaction_counter: u64,
+
+    /// State proof tracking data by state proof type.
+    pub state_proof_tracking: BTreeMap<u64, StateProofTrackingData>,
+
+    /// Participation accounts whose keys expired in this round.
+    pub expired_participation_accounts: Vec<Address>,
+
+    /// Participation accounts that are suspended for being absent.
+    pub absent_participation_accounts: Vec<Address>,
+
+    /// The msgpack encoding of the header sent by the node, if decoded with [Block::from_msg_pack].
+    ///
+    /// Hashed instead of the encoding of the fields above, which would drop the fields this
+    /// version doesn't know.
+    pub encoded: Option<Vec<u8>>,
+}
+
+/// The encoded key of the payset in a block.
+const PAYSET_KEY: &[u8] = &[0xa4, b't', b'x', b'n', b's'];
+
+impl Block {
+    /// Decodes a block from its msgpack encoding, keeping the encoding of the header to hash it.
+    pub fn from_msg_pack(bytes: &[u8]) -> Result<Block, TransactionError> {
+        let mut block: Block = rmp_serde::from_slice(bytes)?;
+        // the header fields are the fields of the block other than the payset
+        let header_entries: Vec<_> = msgpack_map_entries(bytes)
+            .map_err(TransactionError::Deserialization)?
+            .into_iter()
+            .filter(|(key, _)| *key != PAYSET_KEY)
+            .collect();
+        block.header.encoded = Some(msgpack_map(&header_entries));
+        Ok(block)
+    }
+}
+
+impl BlockHeader {
+    /// The msgpack encoding of the header, prefixed with "BH".
+    ///
+    /// The encoding sent by the node if any, the canonical encoding of the fields otherwise.
+    pub fn bytes_to_sign(&self) -> Result<Vec<u8>, TransactionError> {
+        let encoded_header = match &self.encoded {
+            Some(encoded) => encoded.clone(),
+            None => self.to_msg_pack()?,
+        };
+        let mut prefix_encoded_header = b"BH".to_vec();
+        prefix_encoded_header.extend_from_slice(&encoded_header);
+        Ok(prefix_encoded_header)
+    }
+
+    /// The hash of the header, referenced by the next block as previous block hash.
+    pub fn hash(&self) -> Result<HashDigest, TransactionError> {
+        let hashed = sha2::Sha512_256::digest(&self.bytes_to_sign()?);
+        Ok(HashDigest(hashed.into()))
+    }
+}
+
+/// Verifies that the headers, e.g. fetched with consecutive calls to `Algod::block`, form a chain:
+/// the rounds are consecutive, each header references the hash of the previous one and all
+/// belong to the same network (genesis hash).
+pub fn verify_chain(headers: &[BlockHeader]) -> Result<(), TransactionError> {
+    for pair in headers.windows(2) {
+        let (previous, header) = (&pair[0], &pair[1]);
+        let error = |message: String| TransactionError::InvalidBlockChain {
+            round: header.round.0,
+            message,
+        };
+        if header.round.0 != previous.round.0 + 1 {
+            return Err(error(format!(
+                "expected round {} after round {}",
+                previous.round.0 + 1,
+                previous.round.0
+            )));
+        }
+        if header.genesis_hash != previous.genesis_hash {
+            return Err(error(format!(
+                "genesis hash {:?} doesn't match {:?}",
+                header.genesis_hash, previous.genesis_hash
+            )));
+        }
+        let previous_hash = previous.hash()?;
+        if header.previous_block_hash != previous_hash {
+            return Err(error(format!(
+                "previous block hash {:?} doesn't match the hash of round {}: {:?}",
+                header.previous_block_hash, previous.round.0, previous_hash
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The state of the rewards distribution after the block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewardsState {
+    /// Receives the transaction fees.
+    pub fee_sink: Address,
+
+    /// Pays the participation rewards.
+    pub rewards_pool: Address,
+
+    /// Number of micro algos earned by a unit of algos since the genesis.
+    pub rewards_level: u64,
+
+    /// Number of micro algos paid as rewards in the next round.
+    pub rewards_rate: u64,
+
+    /// Micro algos left over after distributing the rewards, carried to the next round.
+    pub rewards_residue: u64,
+
+    /// Round at which the rewards rate is recalculated.
+    pub rewards_recalculation_round: Round,
+}
+
+/// The state of a protocol upgrade after the block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpgradeState {
+    pub current_protocol: String,
+
+    /// The protocol being voted on, if any.
+    pub next_protocol: Option<String>,
+
+    /// Number of blocks that approved the next protocol.
+    pub next_protocol_approvals: u64,
+
+    /// Round until which the next protocol can be voted.
+    pub next_protocol_vote_before: Round,
+
+    /// Round at which the next protocol is adopted.
+    pub next_protocol_switch_on: Round,
+}
+
+/// The protocol upgrade vote of the block proposer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpgradeVote {
+    /// Proposed protocol upgrade, if any.
+    pub upgrade_propose: Option<String>,
+
+    /// Proposed delay of the upgrade in rounds.
+    pub upgrade_delay: Round,
+
+    /// Whether the proposer approves the upgrade being voted on.
+    pub upgrade_approve: bool,
+}
+
+/// Tracks the state proof of a type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateProofTrackingData {
+    /// Commitment to the voters of the next state proof.
+    pub voters_commitment: Vec<u8>,
+
+    /// Total online stake of the voters.
+    pub online_total_weight: MicroAlgos,
+
+    /// Next round for which a state proof is expected.
+    pub next_round: Round,
+}
+
+/// A transaction of the payset, with the effects of its evaluation.
+///
+/// The genesis id and hash, which are omitted in the block encoding, are restored from the header,
+/// so the transaction id matches the one of the transaction that was sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedTransactionInBlock {
+    pub signed_transaction: SignedTransaction,
+    pub apply_data: ApplyData,
+}
+
+/// An inner transaction, issued by an application call, with the effects of its evaluation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionWithApplyData {
+    pub transaction: Transaction,
+    pub apply_data: ApplyData,
+}
+
+/// The effects of evaluating a transaction which aren't part of the transaction itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApplyData {
+    /// Amount sent to the close remainder to account.
+    pub closing_amount: MicroAlgos,
+
+    /// Amount of asset units sent to the asset close to account.
+    pub asset_closing_amount: u64,
+
+    pub sender_rewards: MicroAlgos,
+
+    pub receiver_rewards: MicroAlgos,
+
+    pub close_rewards: MicroAlgos,
+
+    /// Id of the asset created by the transaction.
+    pub config_asset: Option<u64>,
+
+    /// Id of the application created by the transaction.
+    pub application_id: Option<u64>,
+
+    /// Global state changes of the called application, by key.
+    pub global_delta: BTreeMap<Vec<u8>, EvalDelta>,
+
+    /// Local state changes of the called application, by key, by account index
+    /// (0 is the sender, then the accounts of the transaction and the shared accounts).
+    pub local_deltas: BTreeMap<u64, BTreeMap<Vec<u8>, EvalDelta>>,
+
+    /// Accounts referenced by the local deltas which aren't in the accounts of the transaction.
+    pub shared_accounts: Vec<Address>,
+
+    /// Logs emitted by the application call.
+    pub logs: Vec<Vec<u8>>,
+
+    /// Inner transactions issued by the application call.
+    pub inner_txns: Vec<TransactionWithApplyData>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::Account;
+    use crate::api_model::{
+        ApiApplyData, ApiBlock, ApiBlockHeader, ApiEvalDelta, ApiSignedTransactionInBlock,
+        ApiTransaction, ApiValueDelta,
+    };
+    use crate::transaction::tests::payment;
+    use crate::transaction::TransactionSignature;
+    use serde_bytes::ByteBuf;
+    use std::convert::TryInto;
+
+    fn api_header(genesis_hash: HashDigest) -> ApiBlockHeader {
+        ApiBlockHeader {
+            rewards_level: Some(27521),
+            fee_sink: Some(Address([2; 32])),
+            rewards_residue: Some(6_886_250_351),
+            genesis_id: Some("testnet-v1.0".to_owned()),
+            genesis_hash: Some(genesis_hash),
+            previous_block_hash: Some(HashDigest([3; 32])),
+            current_protocol: Some("future".to_owned()),
+            round: Some(Round(100)),
+            rewards_recalculation_round: Some(Round(500_000)),
+            rewards_pool: Some(Address([4; 32])),
+            seed: Some(HashDigest([5; 32])),
+            transaction_counter: Some(1234),
+            timestamp: Some(1_660_000_000),
+            transactions_root: Some(HashDigest([6; 32])),
+            ..ApiBlockHeader::default()
+        }
+    }
+
+    #[test]
+    fn test_decode_block() {
+        let genesis_hash = HashDigest([1; 32]);
+        let account = Account::generate();
+        let mut txn = payment(account.address(), 1);
+        txn.genesis_id = Some("testnet-v1.0".to_owned());
+        let signed = account.sign_transaction(txn).unwrap();
+
+        // the block omits the genesis id and hash of the transactions
+        let mut api_txn: ApiTransaction = signed.transaction.clone().try_into().unwrap();
+        api_txn.genesis_hash = HashDigest([0; 32]);
+        api_txn.genesis_id = None;
+        let api_block = ApiBlock {
+            header: api_header(genesis_hash),
+            payset: Some(vec![ApiSignedTransactionInBlock {
+                apply_data: ApiApplyData {
+                    eval_delta: Some(ApiEvalDelta {
+                        global_delta: Some(
+                            vec![(
+                                ByteBuf::from(b"count".to_vec()),
+                                ApiValueDelta {
+                                    action: 2,
+                                    bytes: None,
+                                    uint: Some(7),
+                                },
+                            )]
+                            .into_iter()
+                            .collect(),
+                        ),
+                        logs: Some(vec![ByteBuf::from(vec![0xff, 0x00])]),
+                        ..ApiEvalDelta::default()
+                    }),
+                    sender_rewards: Some(MicroAlgos(10)),
+                    ..ApiApplyData::default()
+                },
+                has_genesis_hash: Some(true),
+                has_genesis_id: Some(true),
+                lsig: None,
+                msig: None,
+                auth_address: None,
+                sig: match signed.sig {
+                    TransactionSignature::Single(sig) => Some(sig),
+                    _ => unreachable!(),
+                },
+                transaction: api_txn,
+            }]),
+        };
+
+        let block: Block =
+            rmp_serde::from_slice(&rmp_serde::to_vec_named(&api_block).unwrap()).unwrap();
+
+        assert_eq!(Round(100), block.header.round);
+        assert_eq!(HashDigest([3; 32]), block.header.previous_block_hash);
+        assert_eq!(genesis_hash, block.header.genesis_hash);
+        assert_eq!("future", block.header.upgrade_state.current_protocol);
+        assert_eq!(Address([2; 32]), block.header.rewards_state.fee_sink);
+        assert_eq!(27521, block.header.rewards_state.rewards_level);
+        assert_eq!(1_660_000_000, block.header.timestamp);
+        assert_eq!(1234, block.header.transaction_counter);
+        assert_eq!(None, block.header.proposer);
+
+        assert_eq!(1, block.payset.len());
+        let txn_in_block = &block.payset[0];
+        assert_eq!(signed, txn_in_block.signed_transaction);
+        assert_eq!(
+            signed.transaction_id,
+            txn_in_block.signed_transaction.transaction_id
+        );
+        assert_eq!(MicroAlgos(10), txn_in_block.apply_data.sender_rewards);
+        assert_eq!(
+            Some(&EvalDelta::SetUint(7)),
+            txn_in_block.apply_data.global_delta.get(b"count".as_ref())
+        );
+        assert_eq!(vec![vec![0xff, 0x00]], txn_in_block.apply_data.logs);
+        assert!(txn_in_block.apply_data.inner_txns.is_empty());
+    }
+
+    #[test]
+    fn test_header_encoding() {
+        let header: BlockHeader = ApiBlockHeader {
+            round: Some(Round(1)),
+            genesis_id: Some("a".to_owned()),
+            ..ApiBlockHeader::default()
+        }
+        .into();
+        // sorted keys, zero values omitted
+        let mut expected = vec![0x82, 0xa3, b'g', b'e', b'n', 0xa1, b'a'];
+        expected.extend_from_slice(&[0xa3, b'r', b'n', b'd', 0x01]);
+        assert_eq!(expected, header.to_msg_pack().unwrap());
+        assert_eq!(
+            b"BH".to_vec(),
+            header.bytes_to_sign().unwrap()[..2].to_vec()
+        );
+
+        // positive integers use the unsigned markers, like go-algorand (e.g. a uint32 timestamp)
+        let header: BlockHeader = ApiBlockHeader {
+            timestamp: Some(1_660_000_000),
+            ..ApiBlockHeader::default()
+        }
+        .into();
+        assert_eq!(
+            vec![0x81, 0xa2, b't', b's', 0xce, 0x62, 0xf1, 0x97, 0x00],
+            header.to_msg_pack().unwrap()
+        );
+
+        let api_header = api_header(HashDigest([1; 32]));
+        let header: BlockHeader = api_header.clone().into();
+        assert_eq!(
+            rmp_serde::to_vec_named(&api_header).unwrap(),
+            header.to_msg_pack().unwrap()
+        );
+    }
+
+    /// Blocks encoded with sorted keys by another msgpack implementation (python msgpack), the second
+    /// with a payset. The first one has a field ("zz") unknown to the header.
+    const BLOCK_100: &str = "\
+        8fa46561726ecd6b81a466656573c42002020202020202020202020202020202020202020202020202020202\
+        02020202a466726163cf000000019a73d76fa367656eac746573746e65742d76312e30a26768c42001010101\
+        01010101010101010101010101010101010101010101010101010101a470726576c420030303030303030303\
+        0303030303030303030303030303030303030303030303a570726f746fa6667574757265a3726e6464a67277\
+        63616c72ce0007a120a3727764c4200404040404040404040404040404040404040404040404040404040404\
+        040404a473656564c4200505050505050505050505050505050505050505050505050505050505050505a274\
+        63cd04d2a27473ce62f19700a374786ec4200606060606060606060606060606060606060606060606060606\
+        060606060606a27a7a01";
+    const BLOCK_100_HASH: &str = "c60c9a57e7d08cc54ccb613f0b0f279b915c0119c64a0525207404a0b7d3ef1b";
+    const BLOCK_101: &str = "\
+        de0010a46561726ecd6b81a466656573c4200202020202020202020202020202020202020202020202020202\
+        020202020202a466726163cf000000019a73d76fa367656eac746573746e65742d76312e30a26768c4200101\
+        010101010101010101010101010101010101010101010101010101010101a470726576c420c60c9a57e7d08c\
+        c54ccb613f0b0f279b915c0119c64a0525207404a0b7d3ef1ba570726f746fa6667574757265a3726e6465a6\
+        727763616c72ce0007a120a3727764c420040404040404040404040404040404040404040404040404040404\
+        0404040404a473656564c4200505050505050505050505050505050505050505050505050505050505050505\
+        a27463cd04d2a27473ce62f19704a374786ec420060606060606060606060606060606060606060606060606\
+        0606060606060606a474786e7390a27a7a01";
+    const BLOCK_101_HASH: &str = "0a1325f3ec0f473c5429541b86c978a99eb0cd522c145024377b758be7253fa7";
+
+    fn hex(s: &str) -> Vec<u8> {
+        data_encoding::HEXLOWER.decode(s.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_header_hash_vector() {
+        let first = Block::from_msg_pack(&hex(BLOCK_100)).unwrap();
+        let second = Block::from_msg_pack(&hex(BLOCK_101)).unwrap();
+        assert_eq!(Round(100), first.header.round);
+        assert_eq!(1_660_000_000, first.header.timestamp);
+        assert!(second.payset.is_empty());
+
+        assert_eq!(hex(BLOCK_100_HASH), first.header.hash().unwrap().0.to_vec());
+        assert_eq!(
+            hex(BLOCK_101_HASH),
+            second.header.hash().unwrap().0.to_vec()
+        );
+        assert_eq!(
+            first.header.hash().unwrap(),
+            second.header.previous_block_hash
+        );
+        assert!(verify_chain(&[first.header.clone(), second.header]).is_ok());
+
+        // the unknown field is covered by the hash, but can't be re-encoded from the fields
+        let mut decoded = first.header;
+        decoded.encoded = None;
+        assert_ne!(hex(BLOCK_100_HASH), decoded.hash().unwrap().0.to_vec());
+    }
+
+    #[test]
+    fn test_verify_chain() {
+        let first: BlockHeader = api_header(HashDigest([1; 32])).into();
+        let mut second = first.clone();
+        second.round = Round(101);
+        second.previous_block_hash = first.hash().unwrap();
+        let mut third = second.clone();
+        third.round = Round(102);
+        third.previous_block_hash = second.hash().unwrap();
+        assert_ne!(second.hash().unwrap(), third.hash().unwrap());
+
+        assert!(verify_chain(&[first.clone(), second.clone(), third.clone()]).is_ok());
+        assert!(verify_chain(std::slice::from_ref(&first)).is_ok());
+
+        // missing round
+        assert!(verify_chain(&[first.clone(), third.clone()]).is_err());
+
+        let mut tampered = second.clone();
+        tampered.timestamp += 1;
+        assert!(verify_chain(&[first.clone(), tampered.clone()]).is_ok());
+        assert!(verify_chain(&[tampered, third.clone()]).is_err());
+
+        let mut other_network = third;
+        other_network.genesis_hash = HashDigest([9; 32]);
+        match verify_chain(&[first, second, other_network]) {
+            Err(TransactionError::InvalidBlockChain { round, .. }) => assert_eq!(102, round),
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+}
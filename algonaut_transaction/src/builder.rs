@@ -1,10 +1,11 @@
 use crate::{
+    abi::{AbiArgValue, AppCallReferences, Method},
     error::TransactionError,
     transaction::{
         ApplicationCallOnComplete, ApplicationCallTransaction, AssetAcceptTransaction,
         AssetClawbackTransaction, AssetConfigurationTransaction, AssetFreezeTransaction,
-        AssetParams, AssetTransferTransaction, KeyRegistration, Payment, StateSchema, Transaction,
-        TransactionType,
+        AssetParams, AssetTransferTransaction, BoxReference, KeyRegistration, Payment, StateSchema,
+        Transaction, TransactionType,
     },
 };
 use algonaut_core::{
@@ -622,6 +623,7 @@ pub struct CreateApplication {
     clear_state_program: Option<CompiledTeal>,
     foreign_apps: Option<Vec<u64>>,
     foreign_assets: Option<Vec<u64>>,
+    boxes: Option<Vec<BoxReference>>,
     global_state_schema: Option<StateSchema>,
     local_state_schema: Option<StateSchema>,
     extra_pages: u64,
@@ -643,6 +645,7 @@ impl CreateApplication {
             clear_state_program: Some(clear_state_program),
             foreign_apps: None,
             foreign_assets: None,
+            boxes: None,
             global_state_schema: Some(global_state_schema),
             local_state_schema: Some(local_state_schema),
             extra_pages: 0,
@@ -669,26 +672,35 @@ impl CreateApplication {
         self
     }
 
+    pub fn boxes(mut self, boxes: Vec<BoxReference>) -> Self {
+        self.boxes = Some(boxes);
+        self
+    }
+
     pub fn extra_pages(mut self, extra_pages: u64) -> Self {
         self.extra_pages = extra_pages;
         self
     }
 
     pub fn build(self) -> TransactionType {
-        TransactionType::ApplicationCallTransaction(ApplicationCallTransaction {
-            sender: self.sender,
-            app_id: None,
-            on_complete: ApplicationCallOnComplete::NoOp,
-            accounts: self.accounts,
-            approval_program: self.approval_program,
-            app_arguments: self.app_arguments,
-            clear_state_program: self.clear_state_program,
-            foreign_apps: self.foreign_apps,
-            foreign_assets: self.foreign_assets,
-            global_state_schema: self.global_state_schema,
-            local_state_schema: self.local_state_schema,
-            extra_pages: self.extra_pages,
-        })
+        TransactionType::ApplicationCallTransaction(
+            ApplicationCallTransaction {
+                sender: self.sender,
+                app_id: None,
+                on_complete: ApplicationCallOnComplete::NoOp,
+                accounts: self.accounts,
+                approval_program: self.approval_program,
+                app_arguments: self.app_arguments,
+                clear_state_program: self.clear_state_program,
+                foreign_apps: self.foreign_apps,
+                foreign_assets: self.foreign_assets,
+                boxes: None,
+                global_state_schema: self.global_state_schema,
+                local_state_schema: self.local_state_schema,
+                extra_pages: self.extra_pages,
+            }
+            .with_boxes(self.boxes.unwrap_or_default()),
+        )
     }
 }
 
@@ -702,6 +714,7 @@ pub struct UpdateApplication {
     clear_state_program: Option<CompiledTeal>,
     foreign_apps: Option<Vec<u64>>,
     foreign_assets: Option<Vec<u64>>,
+    boxes: Option<Vec<BoxReference>>,
 }
 
 impl UpdateApplication {
@@ -720,6 +733,7 @@ impl UpdateApplication {
             clear_state_program: Some(clear_state_program),
             foreign_apps: None,
             foreign_assets: None,
+            boxes: None,
         }
     }
 
@@ -743,21 +757,30 @@ impl UpdateApplication {
         self
     }
 
+    pub fn boxes(mut self, boxes: Vec<BoxReference>) -> Self {
+        self.boxes = Some(boxes);
+        self
+    }
+
     pub fn build(self) -> TransactionType {
-        TransactionType::ApplicationCallTransaction(ApplicationCallTransaction {
-            sender: self.sender,
-            app_id: Some(self.app_id),
-            on_complete: ApplicationCallOnComplete::UpdateApplication,
-            accounts: self.accounts,
-            approval_program: self.approval_program,
-            app_arguments: self.app_arguments,
-            clear_state_program: self.clear_state_program,
-            foreign_apps: self.foreign_apps,
-            foreign_assets: self.foreign_assets,
-            global_state_schema: None,
-            local_state_schema: None,
-            extra_pages: 0,
-        })
+        TransactionType::ApplicationCallTransaction(
+            ApplicationCallTransaction {
+                sender: self.sender,
+                app_id: Some(self.app_id),
+                on_complete: ApplicationCallOnComplete::UpdateApplication,
+                accounts: self.accounts,
+                approval_program: self.approval_program,
+                app_arguments: self.app_arguments,
+                clear_state_program: self.clear_state_program,
+                foreign_apps: self.foreign_apps,
+                foreign_assets: self.foreign_assets,
+                boxes: None,
+                global_state_schema: None,
+                local_state_schema: None,
+                extra_pages: 0,
+            }
+            .with_boxes(self.boxes.unwrap_or_default()),
+        )
     }
 }
 
@@ -769,6 +792,7 @@ pub struct CallApplication {
     app_arguments: Option<Vec<Vec<u8>>>,
     foreign_apps: Option<Vec<u64>>,
     foreign_assets: Option<Vec<u64>>,
+    boxes: Option<Vec<BoxReference>>,
 }
 
 impl CallApplication {
@@ -780,6 +804,7 @@ impl CallApplication {
             app_arguments: None,
             foreign_apps: None,
             foreign_assets: None,
+            boxes: None,
         }
     }
 
@@ -803,21 +828,52 @@ impl CallApplication {
         self
     }
 
+    pub fn boxes(mut self, boxes: Vec<BoxReference>) -> Self {
+        self.boxes = Some(boxes);
+        self
+    }
+
+    /// Sets the app arguments to call an ABI method, with a value for each non-transaction argument.
+    ///
+    /// Reference arguments are added to accounts, foreign assets and foreign apps, so this has to be called
+    /// after setting those fields, if needed.
+    pub fn abi_method(
+        mut self,
+        method: &Method,
+        args: &[AbiArgValue],
+    ) -> Result<Self, TransactionError> {
+        let mut references = AppCallReferences {
+            accounts: self.accounts.take().unwrap_or_default(),
+            foreign_assets: self.foreign_assets.take().unwrap_or_default(),
+            foreign_apps: self.foreign_apps.take().unwrap_or_default(),
+        };
+        self.app_arguments =
+            Some(method.encode_app_arguments(args, &self.sender, self.app_id, &mut references)?);
+        self.accounts = Some(references.accounts).filter(|v| !v.is_empty());
+        self.foreign_assets = Some(references.foreign_assets).filter(|v| !v.is_empty());
+        self.foreign_apps = Some(references.foreign_apps).filter(|v| !v.is_empty());
+        Ok(self)
+    }
+
     pub fn build(self) -> TransactionType {
-        TransactionType::ApplicationCallTransaction(ApplicationCallTransaction {
-            sender: self.sender,
-            app_id: Some(self.app_id),
-            on_complete: ApplicationCallOnComplete::NoOp,
-            accounts: self.accounts,
-            approval_program: None,
-            app_arguments: self.app_arguments,
-            clear_state_program: None,
-            foreign_apps: self.foreign_apps,
-            foreign_assets: self.foreign_assets,
-            global_state_schema: None,
-            local_state_schema: None,
-            extra_pages: 0,
-        })
+        TransactionType::ApplicationCallTransaction(
+            ApplicationCallTransaction {
+                sender: self.sender,
+                app_id: Some(self.app_id),
+                on_complete: ApplicationCallOnComplete::NoOp,
+                accounts: self.accounts,
+                approval_program: None,
+                app_arguments: self.app_arguments,
+                clear_state_program: None,
+                foreign_apps: self.foreign_apps,
+                foreign_assets: self.foreign_assets,
+                boxes: None,
+                global_state_schema: None,
+                local_state_schema: None,
+                extra_pages: 0,
+            }
+            .with_boxes(self.boxes.unwrap_or_default()),
+        )
     }
 }
 
@@ -829,6 +885,7 @@ pub struct ClearApplication {
     app_arguments: Option<Vec<Vec<u8>>>,
     foreign_apps: Option<Vec<u64>>,
     foreign_assets: Option<Vec<u64>>,
+    boxes: Option<Vec<BoxReference>>,
 }
 
 impl ClearApplication {
@@ -840,6 +897,7 @@ impl ClearApplication {
             app_arguments: None,
             foreign_apps: None,
             foreign_assets: None,
+            boxes: None,
         }
     }
 
@@ -863,21 +921,30 @@ impl ClearApplication {
         self
     }
 
+    pub fn boxes(mut self, boxes: Vec<BoxReference>) -> Self {
+        self.boxes = Some(boxes);
+        self
+    }
+
     pub fn build(self) -> TransactionType {
-        TransactionType::ApplicationCallTransaction(ApplicationCallTransaction {
-            sender: self.sender,
-            app_id: Some(self.app_id),
-            on_complete: ApplicationCallOnComplete::ClearState,
-            accounts: self.accounts,
-            approval_program: None,
-            app_arguments: self.app_arguments,
-            clear_state_program: None,
-            foreign_apps: self.foreign_apps,
-            foreign_assets: self.foreign_assets,
-            global_state_schema: None,
-            local_state_schema: None,
-            extra_pages: 0,
-        })
+        TransactionType::ApplicationCallTransaction(
+            ApplicationCallTransaction {
+                sender: self.sender,
+                app_id: Some(self.app_id),
+                on_complete: ApplicationCallOnComplete::ClearState,
+                accounts: self.accounts,
+                approval_program: None,
+                app_arguments: self.app_arguments,
+                clear_state_program: None,
+                foreign_apps: self.foreign_apps,
+                foreign_assets: self.foreign_assets,
+                boxes: None,
+                global_state_schema: None,
+                local_state_schema: None,
+                extra_pages: 0,
+            }
+            .with_boxes(self.boxes.unwrap_or_default()),
+        )
     }
 }
 
@@ -889,6 +956,7 @@ pub struct CloseApplication {
     app_arguments: Option<Vec<Vec<u8>>>,
     foreign_apps: Option<Vec<u64>>,
     foreign_assets: Option<Vec<u64>>,
+    boxes: Option<Vec<BoxReference>>,
 }
 
 impl CloseApplication {
@@ -900,6 +968,7 @@ impl CloseApplication {
             app_arguments: None,
             foreign_apps: None,
             foreign_assets: None,
+            boxes: None,
         }
     }
 
@@ -923,21 +992,30 @@ impl CloseApplication {
         self
     }
 
+    pub fn boxes(mut self, boxes: Vec<BoxReference>) -> Self {
+        self.boxes = Some(boxes);
+        self
+    }
+
     pub fn build(self) -> TransactionType {
-        TransactionType::ApplicationCallTransaction(ApplicationCallTransaction {
-            sender: self.sender,
-            app_id: Some(self.app_id),
-            on_complete: ApplicationCallOnComplete::CloseOut,
-            accounts: self.accounts,
-            approval_program: None,
-            app_arguments: self.app_arguments,
-            clear_state_program: None,
-            foreign_apps: self.foreign_apps,
-            foreign_assets: self.foreign_assets,
-            global_state_schema: None,
-            local_state_schema: None,
-            extra_pages: 0,
-        })
+        TransactionType::ApplicationCallTransaction(
+            ApplicationCallTransaction {
+                sender: self.sender,
+                app_id: Some(self.app_id),
+                on_complete: ApplicationCallOnComplete::CloseOut,
+                accounts: self.accounts,
+                approval_program: None,
+                app_arguments: self.app_arguments,
+                clear_state_program: None,
+                foreign_apps: self.foreign_apps,
+                foreign_assets: self.foreign_assets,
+                boxes: None,
+                global_state_schema: None,
+                local_state_schema: None,
+                extra_pages: 0,
+            }
+            .with_boxes(self.boxes.unwrap_or_default()),
+        )
     }
 }
 
@@ -949,6 +1027,7 @@ pub struct DeleteApplication {
     app_arguments: Option<Vec<Vec<u8>>>,
     foreign_apps: Option<Vec<u64>>,
     foreign_assets: Option<Vec<u64>>,
+    boxes: Option<Vec<BoxReference>>,
 }
 
 impl DeleteApplication {
@@ -960,6 +1039,7 @@ impl DeleteApplication {
             app_arguments: None,
             foreign_apps: None,
             foreign_assets: None,
+            boxes: None,
         }
     }
 
@@ -983,21 +1063,30 @@ impl DeleteApplication {
         self
     }
 
+    pub fn boxes(mut self, boxes: Vec<BoxReference>) -> Self {
+        self.boxes = Some(boxes);
+        self
+    }
+
     pub fn build(self) -> TransactionType {
-        TransactionType::ApplicationCallTransaction(ApplicationCallTransaction {
-            sender: self.sender,
-            app_id: Some(self.app_id),
-            on_complete: ApplicationCallOnComplete::DeleteApplication,
-            accounts: self.accounts,
-            approval_program: None,
-            app_arguments: self.app_arguments,
-            clear_state_program: None,
-            foreign_apps: self.foreign_apps,
-            foreign_assets: self.foreign_assets,
-            global_state_schema: None,
-            local_state_schema: None,
-            extra_pages: 0,
-        })
+        TransactionType::ApplicationCallTransaction(
+            ApplicationCallTransaction {
+                sender: self.sender,
+                app_id: Some(self.app_id),
+                on_complete: ApplicationCallOnComplete::DeleteApplication,
+                accounts: self.accounts,
+                approval_program: None,
+                app_arguments: self.app_arguments,
+                clear_state_program: None,
+                foreign_apps: self.foreign_apps,
+                foreign_assets: self.foreign_assets,
+                boxes: None,
+                global_state_schema: None,
+                local_state_schema: None,
+                extra_pages: 0,
+            }
+            .with_boxes(self.boxes.unwrap_or_default()),
+        )
     }
 }
 
@@ -1009,6 +1098,7 @@ pub struct OptInApplication {
     app_arguments: Option<Vec<Vec<u8>>>,
     foreign_apps: Option<Vec<u64>>,
     foreign_assets: Option<Vec<u64>>,
+    boxes: Option<Vec<BoxReference>>,
 }
 
 impl OptInApplication {
@@ -1020,6 +1110,7 @@ impl OptInApplication {
             app_arguments: None,
             foreign_apps: None,
             foreign_assets: None,
+            boxes: None,
         }
     }
 
@@ -1043,20 +1134,96 @@ impl OptInApplication {
         self
     }
 
+    pub fn boxes(mut self, boxes: Vec<BoxReference>) -> Self {
+        self.boxes = Some(boxes);
+        self
+    }
+
     pub fn build(self) -> TransactionType {
-        TransactionType::ApplicationCallTransaction(ApplicationCallTransaction {
-            sender: self.sender,
-            app_id: Some(self.app_id),
-            on_complete: ApplicationCallOnComplete::OptIn,
-            accounts: self.accounts,
-            approval_program: None,
-            app_arguments: self.app_arguments,
-            clear_state_program: None,
-            foreign_apps: self.foreign_apps,
-            foreign_assets: self.foreign_assets,
-            global_state_schema: None,
-            local_state_schema: None,
-            extra_pages: 0,
-        })
+        TransactionType::ApplicationCallTransaction(
+            ApplicationCallTransaction {
+                sender: self.sender,
+                app_id: Some(self.app_id),
+                on_complete: ApplicationCallOnComplete::OptIn,
+                accounts: self.accounts,
+                approval_program: None,
+                app_arguments: self.app_arguments,
+                clear_state_program: None,
+                foreign_apps: self.foreign_apps,
+                foreign_assets: self.foreign_assets,
+                boxes: None,
+                global_state_schema: None,
+                local_state_schema: None,
+                extra_pages: 0,
+            }
+            .with_boxes(self.boxes.unwrap_or_default()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi::AbiValue;
+
+    #[test]
+    fn test_abi_method() {
+        let sender = Address([1; 32]);
+        let listed_account = Address([2; 32]);
+        let new_account = Address([3; 32]);
+        let signature = format!(
+            "refs(account,account,account,asset,application,application,application,{})void",
+            vec!["uint64"; 13].join(",")
+        );
+        let method = Method::from_signature(&signature).unwrap();
+
+        let mut args = vec![
+            AbiArgValue::Account(sender),
+            AbiArgValue::Account(listed_account),
+            AbiArgValue::Account(new_account),
+            AbiArgValue::Asset(5),
+            AbiArgValue::Application(10),
+            AbiArgValue::Application(20),
+            AbiArgValue::Application(30),
+        ];
+        args.extend((1..=13).map(|i: u64| AbiArgValue::AbiValue(AbiValue::from(i))));
+
+        let call = match CallApplication::new(sender, 10)
+            .accounts(vec![listed_account])
+            .foreign_apps(vec![20])
+            .abi_method(&method, &args)
+            .unwrap()
+            .build()
+        {
+            TransactionType::ApplicationCallTransaction(call) => call,
+            _ => panic!("expected an application call"),
+        };
+
+        assert_eq!(Some(vec![listed_account, new_account]), call.accounts);
+        assert_eq!(Some(vec![5]), call.foreign_assets);
+        assert_eq!(Some(vec![20, 30]), call.foreign_apps);
+
+        let app_args = call.app_arguments.unwrap();
+        assert_eq!(16, app_args.len());
+        assert_eq!(method.selector().to_vec(), app_args[0]);
+        // the sender and the called app are 0, the other references their index starting at 1
+        assert_eq!(
+            vec![
+                vec![0],
+                vec![1],
+                vec![2],
+                vec![0],
+                vec![0],
+                vec![1],
+                vec![2]
+            ],
+            app_args[1..8].to_vec()
+        );
+        for (i, arg) in app_args[8..15].iter().enumerate() {
+            assert_eq!((i as u64 + 1).to_be_bytes().to_vec(), *arg);
+        }
+        // the 15th and later arguments are packed into a tuple in the last app argument
+        let tuple: Vec<u8> = (8..=13u64).flat_map(|i| i.to_be_bytes().to_vec()).collect();
+        assert_eq!(tuple, app_args[15]);
     }
 }
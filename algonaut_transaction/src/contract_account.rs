@@ -1,5 +1,5 @@
 use crate::error::TransactionError;
-use crate::transaction::{SignedTransaction, Transaction, TransactionSignature};
+use crate::transaction::{auth_address, SignedTransaction, Transaction, TransactionSignature};
 use algonaut_core::{Address, CompiledTeal, LogicSignature, SignedLogic};
 use serde::{Deserialize, Serialize};
 
@@ -36,6 +36,7 @@ impl ContractAccount {
                 args,
                 sig: LogicSignature::ContractAccount,
             }),
+            auth_address: auth_address(transaction, self.address),
         })
     }
 }
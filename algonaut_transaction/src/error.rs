@@ -22,8 +22,48 @@ pub enum TransactionError {
     MaxTransactionGroupSizeError { size: usize },
     #[error("serde encode error {0}")]
     RmpSerdeError(#[from] rmp_serde::encode::Error),
+    #[error("serde decode error {0}")]
+    RmpSerdeDecodeError(#[from] rmp_serde::decode::Error),
     #[error("crypto error {0}")]
     MnemonicError(#[from] algonaut_crypto::error::CryptoError),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
     #[error("Deserialization error: {0}")]
     Deserialization(String),
+    #[error("ABI error: {0}")]
+    Abi(String),
+    #[error("Signing error: {0}")]
+    Signing(String),
+    #[error("Transaction index out of bounds: {}.", index)]
+    TransactionIndexOutOfBounds { index: usize },
+    #[error("Invalid block chain at round {}: {}", round, message)]
+    InvalidBlockChain { round: u64, message: String },
+    #[error("Box application {} is not in the foreign apps.", app_id)]
+    BoxAppNotInForeignApps { app_id: u64 },
+}
+
+impl TransactionError {
+    /// A stable, snake case code identifying the kind of error, e.g. for logs and metrics.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidSenderInMultisig => "invalid_sender_in_multisig",
+            Self::InvalidSecretKeyInMultisig => "invalid_secret_key_in_multisig",
+            Self::InsufficientTransactions => "insufficient_transactions",
+            Self::InvalidNumberOfSubsignatures => "invalid_number_of_subsignatures",
+            Self::InvalidPublicKeyInMultisig => "invalid_public_key_in_multisig",
+            Self::MismatchingSignatures => "mismatching_signatures",
+            Self::EmptyTransactionListError => "empty_transaction_list",
+            Self::MaxTransactionGroupSizeError { .. } => "max_transaction_group_size",
+            Self::RmpSerdeError(_) => "encoding",
+            Self::RmpSerdeDecodeError(_) => "decoding",
+            Self::MnemonicError(e) => e.code(),
+            Self::Io(_) => "io",
+            Self::Deserialization(_) => "deserialization",
+            Self::Abi(_) => "abi",
+            Self::Signing(_) => "signing",
+            Self::TransactionIndexOutOfBounds { .. } => "transaction_index_out_of_bounds",
+            Self::InvalidBlockChain { .. } => "invalid_block_chain",
+            Self::BoxAppNotInForeignApps { .. } => "box_app_not_in_foreign_apps",
+        }
+    }
 }
@@ -0,0 +1,111 @@
+//! Transaction files compatible with `goal clerk`.
+//!
+//! Unsigned transactions (`.txn`) are encoded like goal does it, as signed transactions without
+//! signature (`{"txn": ...}`). Signed transactions (`.stxn`) and groups (`.tx`) are the
+//! concatenated encoded signed transactions, which can also be broadcast as they are.
+use crate::api_model::ApiUnsignedTransaction;
+use crate::error::TransactionError;
+use crate::transaction::{SignedTransaction, Transaction};
+use algonaut_core::ToMsgPack;
+use serde::Deserialize;
+use std::convert::{TryFrom, TryInto};
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+/// Encodes unsigned transactions like `goal clerk send --out` does.
+pub fn encode_transactions(txns: &[Transaction]) -> Result<Vec<u8>, TransactionError> {
+    let mut bytes = vec![];
+    for txn in txns {
+        bytes.extend(ApiUnsignedTransaction::try_from(txn.clone())?.to_msg_pack()?);
+    }
+    Ok(bytes)
+}
+
+/// Decodes unsigned transactions encoded like `goal clerk send --out` does.
+pub fn decode_transactions(bytes: &[u8]) -> Result<Vec<Transaction>, TransactionError> {
+    let mut cursor = Cursor::new(bytes);
+    let mut txns = vec![];
+    while (cursor.position() as usize) < bytes.len() {
+        let mut deserializer = rmp_serde::Deserializer::new(&mut cursor);
+        txns.push(ApiUnsignedTransaction::deserialize(&mut deserializer)?.try_into()?);
+    }
+    Ok(txns)
+}
+
+/// Encodes signed transactions like `goal clerk sign` does.
+pub fn encode_signed_transactions(txns: &[SignedTransaction]) -> Result<Vec<u8>, TransactionError> {
+    let mut bytes = vec![];
+    for txn in txns {
+        bytes.extend(txn.to_msg_pack()?);
+    }
+    Ok(bytes)
+}
+
+/// Decodes signed transactions encoded like `goal clerk sign` does.
+pub fn decode_signed_transactions(
+    bytes: &[u8],
+) -> Result<Vec<SignedTransaction>, TransactionError> {
+    SignedTransaction::from_msg_pack_group(bytes)
+}
+
+/// Writes unsigned transactions to a `.txn` file.
+pub fn write_transactions<P: AsRef<Path>>(
+    path: P,
+    txns: &[Transaction],
+) -> Result<(), TransactionError> {
+    Ok(fs::write(path, encode_transactions(txns)?)?)
+}
+
+/// Reads unsigned transactions from a `.txn` file.
+pub fn read_transactions<P: AsRef<Path>>(path: P) -> Result<Vec<Transaction>, TransactionError> {
+    decode_transactions(&fs::read(path)?)
+}
+
+/// Writes signed transactions to a `.stxn` or `.tx` file.
+pub fn write_signed_transactions<P: AsRef<Path>>(
+    path: P,
+    txns: &[SignedTransaction],
+) -> Result<(), TransactionError> {
+    Ok(fs::write(path, encode_signed_transactions(txns)?)?)
+}
+
+/// Reads signed transactions from a `.stxn` or `.tx` file.
+pub fn read_signed_transactions<P: AsRef<Path>>(
+    path: P,
+) -> Result<Vec<SignedTransaction>, TransactionError> {
+    decode_signed_transactions(&fs::read(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::Account;
+    use crate::transaction::tests::payment;
+
+    #[test]
+    fn test_unsigned_transactions_are_wrapped() {
+        let txn = payment(Account::generate().address(), 1);
+        let bytes = encode_transactions(std::slice::from_ref(&txn)).unwrap();
+        // fixmap with 1 entry, "txn" key
+        assert_eq!([0x81, 0xa3, b't', b'x', b'n'], bytes[..5]);
+        assert_eq!(txn.to_msg_pack().unwrap(), bytes[5..]);
+    }
+
+    #[test]
+    fn test_transactions_file_roundtrip() {
+        let account = Account::generate();
+        let txns = vec![payment(account.address(), 1), payment(account.address(), 2)];
+        let path = std::env::temp_dir().join(format!("algonaut-{}.txn", account.address()));
+        write_transactions(&path, &txns).unwrap();
+        assert_eq!(txns, read_transactions(&path).unwrap());
+
+        let signed: Vec<SignedTransaction> = txns
+            .into_iter()
+            .map(|t| account.sign_transaction(t).unwrap())
+            .collect();
+        write_signed_transactions(&path, &signed).unwrap();
+        assert_eq!(signed, read_signed_transactions(&path).unwrap());
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,619 @@
+use crate::error::TransactionError;
+use crate::transaction::{
+    ApplicationCallOnComplete, ApplicationCallTransaction, AssetAcceptTransaction,
+    AssetClawbackTransaction, AssetConfigurationTransaction, AssetFreezeTransaction, AssetParams,
+    AssetTransferTransaction, BoxReference, KeyRegistration, Payment, StateSchema,
+    TransactionSignature,
+};
+use crate::{SignedTransaction, Transaction, TransactionType};
+use algonaut_core::{
+    Address, CompiledTeal, LogicSignature, MicroAlgos, MultisigSignature, MultisigSubsig, Round,
+    SignedLogic, VotePk, VrfPk,
+};
+use algonaut_crypto::{Ed25519PublicKey, HashDigest, Signature};
+use algonaut_model::indexer::v2 as indexer;
+use data_encoding::BASE64;
+use std::convert::{TryFrom, TryInto};
+
+/// Converts a transaction returned by the indexer, e.g. to verify its signature.
+///
+/// The id is computed from the converted transaction, so comparing it with the id reported by the
+/// indexer checks that the transaction was converted faithfully.
+/// Inner transactions can't be converted, as they aren't signed.
+impl TryFrom<indexer::Transaction> for SignedTransaction {
+    type Error = TransactionError;
+
+    fn try_from(t: indexer::Transaction) -> Result<Self, Self::Error> {
+        let sig = t
+            .signature
+            .clone()
+            .ok_or_else(|| TransactionError::Deserialization("signature missing".to_owned()))?
+            .try_into()?;
+        let auth_address = t.auth_addr.as_deref().map(parse_address).transpose()?;
+        let transaction: Transaction = t.try_into()?;
+        Ok(SignedTransaction {
+            transaction_id: transaction.id()?,
+            transaction,
+            sig,
+            auth_address,
+        })
+    }
+}
+
+/// Converts a transaction returned by the indexer, without its signature.
+impl TryFrom<indexer::Transaction> for Transaction {
+    type Error = TransactionError;
+
+    fn try_from(t: indexer::Transaction) -> Result<Self, Self::Error> {
+        let sender = parse_address(&t.sender)?;
+        let txn_type = match t.tx_type {
+            indexer::TransactionType::Payment => {
+                let payment = field(t.payment_transaction, "payment-transaction")?;
+                TransactionType::Payment(Payment {
+                    sender,
+                    receiver: parse_address(&payment.receiver)?,
+                    amount: payment.amount,
+                    close_remainder_to: payment
+                        .close_remainder_to
+                        .as_deref()
+                        .map(parse_address)
+                        .transpose()?,
+                })
+            }
+            indexer::TransactionType::KeyRegistration => {
+                let keyreg = field(t.keyreg_transaction, "keyreg-transaction")?;
+                TransactionType::KeyRegistration(KeyRegistration {
+                    sender,
+                    vote_pk: keyreg
+                        .vote_participation_key
+                        .as_deref()
+                        .map(|k| parse_bytes32(k).map(VotePk))
+                        .transpose()?,
+                    selection_pk: keyreg
+                        .selection_participation_key
+                        .as_deref()
+                        .map(|k| parse_bytes32(k).map(VrfPk))
+                        .transpose()?,
+                    vote_first: keyreg.vote_first_valid.map(Round),
+                    vote_last: keyreg.vote_last_valid.map(Round),
+                    vote_key_dilution: Some(keyreg.vote_key_dilution.unwrap_or(0)),
+                    nonparticipating: keyreg.non_participation,
+                })
+            }
+            indexer::TransactionType::AssetConfigurationTransaction => {
+                let config = field(t.asset_config_transaction, "asset-config-transaction")?;
+                TransactionType::AssetConfigurationTransaction(AssetConfigurationTransaction {
+                    sender,
+                    params: config.params.map(asset_params).transpose()?.flatten(),
+                    // the creation is represented as None in the domain
+                    config_asset: config.asset_id.filter(|id| *id != 0),
+                })
+            }
+            indexer::TransactionType::AssetTransferTransaction => {
+                let transfer = field(t.asset_transfer_transaction, "asset-transfer-transaction")?;
+                asset_transfer(sender, transfer)?
+            }
+            indexer::TransactionType::AssetFreezeTransaction => {
+                let freeze = field(t.asset_freeze_transaction, "asset-freeze-transaction")?;
+                TransactionType::AssetFreezeTransaction(AssetFreezeTransaction {
+                    sender,
+                    freeze_account: parse_address(&freeze.address)?,
+                    asset_id: freeze.asset_id,
+                    frozen: freeze.new_freeze_status,
+                })
+            }
+            indexer::TransactionType::ApplicationTransaction => {
+                let call = field(t.application_transaction, "application-transaction")?;
+                application_call(sender, call)?
+            }
+        };
+        Ok(Transaction {
+            fee: MicroAlgos(t.fee),
+            first_valid: Round(t.first_valid),
+            genesis_hash: t.genesis_hash,
+            last_valid: t.last_valid,
+            txn_type,
+            genesis_id: t.genesis_id.filter(|id| !id.is_empty()),
+            group: t
+                .group
+                .as_deref()
+                .map(|g| parse_bytes32(g).map(HashDigest))
+                .transpose()?,
+            lease: t.lease,
+            note: t
+                .note
+                .as_deref()
+                .map(parse_base64)
+                .transpose()?
+                .filter(|n| !n.is_empty()),
+            rekey_to: t.rekey_to.as_deref().map(parse_address).transpose()?,
+        })
+    }
+}
+
+impl TryFrom<indexer::TransactionSignature> for TransactionSignature {
+    type Error = TransactionError;
+
+    fn try_from(sig: indexer::TransactionSignature) -> Result<Self, Self::Error> {
+        match (sig.sig, sig.multisig, sig.logicsig) {
+            (Some(sig), None, None) => Ok(TransactionSignature::Single(parse_signature(&sig)?)),
+            (None, Some(msig), None) => Ok(TransactionSignature::Multi(multisig(msig)?)),
+            (None, None, Some(lsig)) => Ok(TransactionSignature::Logic(logicsig(lsig)?)),
+            _ => Err(TransactionError::Deserialization(
+                "Invalid sig combination".to_owned(),
+            )),
+        }
+    }
+}
+
+fn multisig(
+    msig: indexer::TransactionSignatureMultisig,
+) -> Result<MultisigSignature, TransactionError> {
+    let subsigs = msig
+        .subsignature
+        .into_iter()
+        .map(|subsig| {
+            let key = subsig.public_key.as_deref().ok_or_else(|| {
+                TransactionError::Deserialization("public-key missing".to_owned())
+            })?;
+            Ok(MultisigSubsig {
+                key: Ed25519PublicKey(parse_bytes32(key)?),
+                sig: subsig
+                    .signature
+                    .as_deref()
+                    .map(parse_signature)
+                    .transpose()?,
+            })
+        })
+        .collect::<Result<Vec<_>, TransactionError>>()?;
+    Ok(MultisigSignature {
+        version: parse_u8(msig.version.unwrap_or(0))?,
+        threshold: parse_u8(msig.threshold.unwrap_or(0))?,
+        subsigs,
+    })
+}
+
+fn logicsig(lsig: indexer::TransactionSignatureLogicsig) -> Result<SignedLogic, TransactionError> {
+    let sig = match (lsig.signature, lsig.multisig_signature) {
+        (None, None) => LogicSignature::ContractAccount,
+        (Some(sig), None) => LogicSignature::DelegatedSig(parse_signature(&sig)?),
+        (None, Some(msig)) => LogicSignature::DelegatedMultiSig(multisig(msig)?),
+        (Some(_), Some(_)) => {
+            return Err(TransactionError::Deserialization(
+                "Invalid logic sig combination".to_owned(),
+            ))
+        }
+    };
+    Ok(SignedLogic {
+        logic: CompiledTeal(parse_base64(&lsig.logic)?),
+        args: lsig
+            .args
+            .iter()
+            .map(|arg| parse_base64(arg))
+            .collect::<Result<_, _>>()?,
+        sig,
+    })
+}
+
+/// Converts the asset parameters, which are None if they're all zero values (asset destruction).
+fn asset_params(params: indexer::AssetParams) -> Result<Option<AssetParams>, TransactionError> {
+    let name = params.name.filter(|n| !n.is_empty());
+    let unit_name = params.unit_name.filter(|n| !n.is_empty());
+    let url = params.url.filter(|u| !u.is_empty());
+    let reserve = params
+        .reserve
+        .as_deref()
+        .filter(|r| !r.is_empty())
+        .map(parse_address)
+        .transpose()?;
+    let default_frozen = params.default_frozen.unwrap_or(false);
+    let decimals = params.decimals;
+    if params.total == 0
+        && decimals == 0
+        && !default_frozen
+        && name.is_none()
+        && unit_name.is_none()
+        && url.is_none()
+        && params.metadata_hash.is_empty()
+        && params.clawback.is_none()
+        && params.freeze.is_none()
+        && params.manager.is_none()
+        && reserve.is_none()
+    {
+        return Ok(None);
+    }
+    Ok(Some(AssetParams {
+        asset_name: name,
+        decimals: Some(decimals.try_into().map_err(|_| {
+            TransactionError::Deserialization(format!("Invalid decimals: {}", decimals))
+        })?),
+        default_frozen: Some(default_frozen),
+        total: Some(params.total),
+        unit_name,
+        meta_data_hash: Some(params.metadata_hash).filter(|h| !h.is_empty()),
+        url,
+        clawback: params.clawback,
+        freeze: params.freeze,
+        manager: params.manager,
+        reserve,
+    }))
+}
+
+fn asset_transfer(
+    sender: Address,
+    transfer: indexer::TransactionAssetTransfer,
+) -> Result<TransactionType, TransactionError> {
+    let receiver = parse_address(&transfer.receiver)?;
+    let close_to = transfer
+        .close_to
+        .as_deref()
+        .map(parse_address)
+        .transpose()?;
+    Ok(match transfer.sender.as_deref() {
+        Some(asset_sender) => TransactionType::AssetClawbackTransaction(AssetClawbackTransaction {
+            sender,
+            xfer: transfer.asset_id,
+            asset_amount: transfer.amount,
+            asset_sender: parse_address(asset_sender)?,
+            asset_receiver: receiver,
+            asset_close_to: close_to,
+        }),
+        None if receiver == sender && transfer.amount == 0 && close_to.is_none() => {
+            TransactionType::AssetAcceptTransaction(AssetAcceptTransaction {
+                sender,
+                xfer: transfer.asset_id,
+            })
+        }
+        None => TransactionType::AssetTransferTransaction(AssetTransferTransaction {
+            sender,
+            xfer: transfer.asset_id,
+            amount: transfer.amount,
+            receiver,
+            close_to,
+        }),
+    })
+}
+
+fn application_call(
+    sender: Address,
+    call: indexer::TransactionApplication,
+) -> Result<TransactionType, TransactionError> {
+    let on_complete = match call.on_completion {
+        indexer::OnCompletion::Noop => ApplicationCallOnComplete::NoOp,
+        indexer::OnCompletion::Optin => ApplicationCallOnComplete::OptIn,
+        indexer::OnCompletion::Closeout => ApplicationCallOnComplete::CloseOut,
+        indexer::OnCompletion::Clear => ApplicationCallOnComplete::ClearState,
+        indexer::OnCompletion::Update => ApplicationCallOnComplete::UpdateApplication,
+        indexer::OnCompletion::Delete => ApplicationCallOnComplete::DeleteApplication,
+    };
+    let app_id = Some(call.application_id).filter(|id| *id != 0);
+    let schema = |schema: Option<indexer::StateSchema>| match schema {
+        Some(s) => Some(StateSchema {
+            number_ints: s.num_uint,
+            number_byteslices: s.num_byte_slice,
+        }),
+        // a zero schema isn't encoded, but an application creation always has one
+        None if app_id.is_none() => Some(StateSchema {
+            number_ints: 0,
+            number_byteslices: 0,
+        }),
+        None => None,
+    };
+    let program = |program: Option<String>| {
+        program
+            .as_deref()
+            .map(parse_base64)
+            .transpose()
+            .map(|p| p.filter(|p| !p.is_empty()).map(CompiledTeal))
+    };
+    Ok(TransactionType::ApplicationCallTransaction(
+        ApplicationCallTransaction {
+            sender,
+            app_id,
+            on_complete,
+            accounts: non_empty(call.accounts),
+            approval_program: program(call.approval_program)?,
+            app_arguments: non_empty(
+                call.application_args
+                    .iter()
+                    .map(|arg| parse_base64(arg))
+                    .collect::<Result<_, _>>()?,
+            ),
+            clear_state_program: program(call.clear_state_program)?,
+            foreign_apps: non_empty(call.foreign_apps),
+            foreign_assets: non_empty(call.foreign_assets),
+            boxes: non_empty(
+                call.box_references
+                    .into_iter()
+                    .map(|b| BoxReference {
+                        app_id: b.app,
+                        name: b.name,
+                    })
+                    .collect(),
+            ),
+            global_state_schema: schema(call.global_state_schema),
+            local_state_schema: schema(call.local_state_schema),
+            extra_pages: call.extra_program_pages,
+        },
+    ))
+}
+
+fn field<T>(value: Option<T>, name: &str) -> Result<T, TransactionError> {
+    value.ok_or_else(|| TransactionError::Deserialization(format!("{} missing", name)))
+}
+
+fn non_empty<T>(v: Vec<T>) -> Option<Vec<T>> {
+    if v.is_empty() {
+        None
+    } else {
+        Some(v)
+    }
+}
+
+fn parse_address(address: &str) -> Result<Address, TransactionError> {
+    address.parse().map_err(|e| {
+        TransactionError::Deserialization(format!("Invalid address {}: {}", address, e))
+    })
+}
+
+fn parse_base64(s: &str) -> Result<Vec<u8>, TransactionError> {
+    BASE64
+        .decode(s.as_bytes())
+        .map_err(|e| TransactionError::Deserialization(format!("Invalid base64 {}: {}", s, e)))
+}
+
+fn parse_bytes32(s: &str) -> Result<[u8; 32], TransactionError> {
+    parse_base64(s)?
+        .try_into()
+        .map_err(|_| TransactionError::Deserialization(format!("Invalid 32 bytes: {}", s)))
+}
+
+fn parse_signature(s: &str) -> Result<Signature, TransactionError> {
+    let bytes: [u8; 64] = parse_base64(s)?
+        .try_into()
+        .map_err(|_| TransactionError::Deserialization(format!("Invalid signature: {}", s)))?;
+    Ok(Signature(bytes))
+}
+
+fn parse_u8(n: u64) -> Result<u8, TransactionError> {
+    n.try_into()
+        .map_err(|_| TransactionError::Deserialization(format!("Invalid multisig value: {}", n)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{CallApplication, CreateApplication};
+    use crate::contract_account::ContractAccount;
+    use crate::{account::Account, Pay, TxnBuilder};
+    use algonaut_core::{MultisigAddress, SuggestedTransactionParams};
+    use serde_json::json;
+
+    fn params() -> SuggestedTransactionParams {
+        SuggestedTransactionParams {
+            genesis_id: "testnet-v1.0".to_owned(),
+            genesis_hash: HashDigest([1; 32]),
+            consensus_version: "".to_owned(),
+            fee_per_byte: MicroAlgos(0),
+            min_fee: MicroAlgos(1000),
+            first_valid: Round(1),
+            last_valid: Round(1001),
+        }
+    }
+
+    fn indexer_transaction(
+        signed: &SignedTransaction,
+        tx_type: &str,
+        fields: serde_json::Value,
+    ) -> indexer::Transaction {
+        let t = &signed.transaction;
+        let mut json = json!({
+            "id": signed.transaction_id,
+            "sender": t.sender().to_string(),
+            "fee": t.fee.0,
+            "first-valid": t.first_valid.0,
+            "last-valid": t.last_valid.0,
+            "genesis-hash": t.genesis_hash.to_string(),
+            "genesis-id": t.genesis_id,
+            "note": t.note.as_ref().map(|n| BASE64.encode(n)),
+            "tx-type": tx_type,
+        });
+        let signature = match &signed.sig {
+            TransactionSignature::Single(sig) => json!({ "sig": BASE64.encode(&sig.0) }),
+            TransactionSignature::Multi(msig) => json!({ "multisig": multisig_json(msig) }),
+            TransactionSignature::Logic(lsig) => {
+                let mut logicsig = json!({
+                    "logic": BASE64.encode(&lsig.logic.0),
+                    "args": lsig.args.iter().map(|a| BASE64.encode(a)).collect::<Vec<_>>(),
+                });
+                match &lsig.sig {
+                    LogicSignature::ContractAccount => {}
+                    LogicSignature::DelegatedSig(sig) => {
+                        logicsig["signature"] = json!(BASE64.encode(&sig.0))
+                    }
+                    LogicSignature::DelegatedMultiSig(msig) => {
+                        logicsig["multisig-signature"] = multisig_json(msig)
+                    }
+                }
+                json!({ "logicsig": logicsig })
+            }
+        };
+        json["signature"] = signature;
+        for (key, value) in fields.as_object().unwrap() {
+            json[key] = value.clone();
+        }
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn multisig_json(msig: &MultisigSignature) -> serde_json::Value {
+        json!({
+            "version": msig.version,
+            "threshold": msig.threshold,
+            "subsignature": msig.subsigs.iter().map(|subsig| json!({
+                "public-key": BASE64.encode(&subsig.key.0),
+                "signature": subsig.sig.map(|sig| BASE64.encode(&sig.0)),
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    #[test]
+    fn test_convert_payment() {
+        let sender = Account::generate();
+        let receiver = Account::generate().address();
+        let txn = TxnBuilder::with(
+            &params(),
+            Pay::new(sender.address(), receiver, MicroAlgos(123)).build(),
+        )
+        .note(vec![1, 2, 3])
+        .build()
+        .unwrap();
+        let signed = sender.sign_transaction(txn).unwrap();
+        let mut indexer_txn = indexer_transaction(
+            &signed,
+            "pay",
+            json!({
+                "payment-transaction": { "amount": 123, "receiver": receiver.to_string() }
+            }),
+        );
+
+        let converted = SignedTransaction::try_from(indexer_txn.clone()).unwrap();
+        assert_eq!(signed, converted);
+        assert_eq!(indexer_txn.id, converted.transaction_id);
+        assert!(converted.verify());
+
+        indexer_txn.payment_transaction.as_mut().unwrap().amount = MicroAlgos(124);
+        let converted = SignedTransaction::try_from(indexer_txn.clone()).unwrap();
+        assert_ne!(indexer_txn.id, converted.transaction_id);
+        assert!(!converted.verify());
+
+        indexer_txn.signature = None;
+        assert!(SignedTransaction::try_from(indexer_txn).is_err());
+    }
+
+    #[test]
+    fn test_convert_logic_signed_application_call() {
+        let contract = ContractAccount::new(CompiledTeal(vec![6, 129, 1]));
+        let txn = TxnBuilder::with(
+            &params(),
+            CallApplication::new(*contract.address(), 5)
+                .app_arguments(vec![vec![1], vec![2, 3]])
+                .foreign_assets(vec![10])
+                .build(),
+        )
+        .build()
+        .unwrap();
+        let signed = contract.sign(&txn, vec![vec![4]]).unwrap();
+        let indexer_txn = indexer_transaction(
+            &signed,
+            "appl",
+            json!({
+                "application-transaction": {
+                    "application-id": 5,
+                    "application-args": ["AQ==", "AgM="],
+                    "foreign-assets": [10],
+                    "on-completion": "noop",
+                }
+            }),
+        );
+
+        let converted = SignedTransaction::try_from(indexer_txn.clone()).unwrap();
+        assert_eq!(signed, converted);
+        assert_eq!(indexer_txn.id, converted.transaction_id);
+        assert!(converted.verify());
+    }
+
+    #[test]
+    fn test_convert_multisig_payment() {
+        let (a, b, c) = (
+            Account::generate(),
+            Account::generate(),
+            Account::generate(),
+        );
+        let msig_address =
+            MultisigAddress::new(1, 2, &[a.address(), b.address(), c.address()]).unwrap();
+        let receiver = Account::generate().address();
+        let txn = TxnBuilder::with(
+            &params(),
+            Pay::new(msig_address.address(), receiver, MicroAlgos(123)).build(),
+        )
+        .build()
+        .unwrap();
+        let msig = a.init_transaction_msig(&txn, &msig_address).unwrap();
+        let msig = c.append_to_transaction_msig(&txn, msig).unwrap();
+        let signed = SignedTransaction {
+            transaction_id: txn.id().unwrap(),
+            transaction: txn,
+            sig: TransactionSignature::Multi(msig),
+            auth_address: None,
+        };
+        let indexer_txn = indexer_transaction(
+            &signed,
+            "pay",
+            json!({
+                "payment-transaction": { "amount": 123, "receiver": receiver.to_string() }
+            }),
+        );
+
+        let converted = SignedTransaction::try_from(indexer_txn.clone()).unwrap();
+        assert_eq!(signed, converted);
+        assert_eq!(indexer_txn.id, converted.transaction_id);
+        assert!(converted.verify());
+    }
+
+    #[test]
+    fn test_convert_delegated_logic_signed_application_creation() {
+        let sender = Account::generate();
+        let logic = CompiledTeal(vec![6, 129, 1]);
+        let mut txn = TxnBuilder::with(
+            &params(),
+            CreateApplication::new(
+                sender.address(),
+                CompiledTeal(vec![6, 129, 1]),
+                CompiledTeal(vec![6, 129, 1]),
+                StateSchema {
+                    number_ints: 1,
+                    number_byteslices: 2,
+                },
+                StateSchema {
+                    number_ints: 0,
+                    number_byteslices: 0,
+                },
+            )
+            .build(),
+        )
+        .build()
+        .unwrap();
+        // the creator can opt in while creating the application
+        if let TransactionType::ApplicationCallTransaction(call) = &mut txn.txn_type {
+            call.on_complete = ApplicationCallOnComplete::OptIn;
+        }
+        let signed = SignedTransaction {
+            transaction_id: txn.id().unwrap(),
+            transaction: txn,
+            sig: TransactionSignature::Logic(SignedLogic {
+                sig: LogicSignature::DelegatedSig(sender.generate_program_sig(&logic)),
+                logic,
+                args: vec![vec![4]],
+            }),
+            auth_address: None,
+        };
+        let indexer_txn = indexer_transaction(
+            &signed,
+            "appl",
+            json!({
+                "application-transaction": {
+                    "application-id": 0,
+                    "approval-program": "BoEB",
+                    "clear-state-program": "BoEB",
+                    "global-state-schema": { "num-uint": 1, "num-byte-slice": 2 },
+                    "on-completion": "optin",
+                }
+            }),
+        );
+
+        let converted = SignedTransaction::try_from(indexer_txn.clone()).unwrap();
+        assert_eq!(signed, converted);
+        assert_eq!(indexer_txn.id, converted.transaction_id);
+        assert!(converted.verify());
+    }
+}
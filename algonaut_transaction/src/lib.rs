@@ -1,9 +1,14 @@
+pub mod abi;
 pub mod account;
 mod api_model;
 pub mod auction;
+pub mod block;
 pub mod builder;
 pub mod contract_account;
 pub mod error;
+pub mod file;
+mod indexer;
+pub mod signer;
 pub mod transaction;
 pub mod tx_group;
 pub mod url;
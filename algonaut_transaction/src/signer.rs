@@ -0,0 +1,271 @@
+use crate::account::Account;
+use crate::contract_account::ContractAccount;
+use crate::error::TransactionError;
+use crate::transaction::{auth_address, SignedTransaction, Transaction, TransactionSignature};
+use algonaut_core::{
+    LogicSignature, MultisigAddress, MultisigSignature, MultisigSubsig, SignedLogic,
+};
+use async_trait::async_trait;
+
+/// Signs transactions independently of the kind of key or service behind it.
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait]
+pub trait TransactionSigner: Send + Sync {
+    /// Signs the transactions of `txns` at `indexes`, returning them in the order of `indexes`.
+    ///
+    /// The whole group is passed, as some signers need the context of the other transactions.
+    async fn sign_transactions(
+        &self,
+        txns: &[Transaction],
+        indexes: &[usize],
+    ) -> Result<Vec<SignedTransaction>, TransactionError>;
+}
+
+/// Signs transactions independently of the kind of key or service behind it.
+// Signers backed by a service (e.g. KMD) are not thread safe with the WASM target,
+// so neither the signers nor their futures are required to be Send there.
+#[cfg(target_arch = "wasm32")]
+#[async_trait(?Send)]
+pub trait TransactionSigner {
+    /// Signs the transactions of `txns` at `indexes`, returning them in the order of `indexes`.
+    ///
+    /// The whole group is passed, as some signers need the context of the other transactions.
+    async fn sign_transactions(
+        &self,
+        txns: &[Transaction],
+        indexes: &[usize],
+    ) -> Result<Vec<SignedTransaction>, TransactionError>;
+}
+
+/// Gets the transactions at `indexes`, failing if any is out of bounds.
+pub(crate) fn transactions_at<'a>(
+    txns: &'a [Transaction],
+    indexes: &[usize],
+) -> Result<Vec<&'a Transaction>, TransactionError> {
+    indexes
+        .iter()
+        .map(|i| {
+            txns.get(*i)
+                .ok_or(TransactionError::TransactionIndexOutOfBounds { index: *i })
+        })
+        .collect()
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl TransactionSigner for Account {
+    async fn sign_transactions(
+        &self,
+        txns: &[Transaction],
+        indexes: &[usize],
+    ) -> Result<Vec<SignedTransaction>, TransactionError> {
+        transactions_at(txns, indexes)?
+            .into_iter()
+            .map(|t| self.sign_transaction(t.clone()))
+            .collect()
+    }
+}
+
+/// Signs with the accounts of a multisig address.
+/// The accounts have to provide enough signatures to reach the threshold.
+/// The sender can be the multisig address or an account rekeyed to it.
+#[derive(Debug)]
+pub struct MultisigSigner {
+    address: MultisigAddress,
+    accounts: Vec<Account>,
+}
+
+impl MultisigSigner {
+    /// Returns an error if there are no accounts or an account is not part of the multisig address.
+    pub fn new(
+        address: MultisigAddress,
+        accounts: Vec<Account>,
+    ) -> Result<MultisigSigner, TransactionError> {
+        if accounts.is_empty() || !accounts.iter().all(|a| address.contains(&a.address())) {
+            return Err(TransactionError::InvalidSecretKeyInMultisig);
+        }
+        Ok(MultisigSigner { address, accounts })
+    }
+
+    pub fn address(&self) -> &MultisigAddress {
+        &self.address
+    }
+
+    fn sign_transaction(&self, txn: &Transaction) -> Result<SignedTransaction, TransactionError> {
+        // Start with the unsigned subsigs instead of `init_transaction_msig`,
+        // which expects the sender to be the multisig address.
+        let mut msig = MultisigSignature {
+            version: self.address.version,
+            threshold: self.address.threshold,
+            subsigs: self
+                .address
+                .public_keys
+                .iter()
+                .map(|key| MultisigSubsig {
+                    key: *key,
+                    sig: None,
+                })
+                .collect(),
+        };
+        for account in &self.accounts {
+            msig = account.append_to_transaction_msig(txn, msig)?;
+        }
+        Ok(SignedTransaction {
+            transaction: txn.clone(),
+            transaction_id: txn.id()?,
+            sig: TransactionSignature::Multi(msig),
+            auth_address: auth_address(txn, self.address.address()),
+        })
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl TransactionSigner for MultisigSigner {
+    async fn sign_transactions(
+        &self,
+        txns: &[Transaction],
+        indexes: &[usize],
+    ) -> Result<Vec<SignedTransaction>, TransactionError> {
+        transactions_at(txns, indexes)?
+            .into_iter()
+            .map(|t| self.sign_transaction(t))
+            .collect()
+    }
+}
+
+/// Signs as contract account, without program arguments.
+/// To pass arguments, use a [SignedLogic] with [LogicSignature::ContractAccount](algonaut_core::LogicSignature::ContractAccount).
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl TransactionSigner for ContractAccount {
+    async fn sign_transactions(
+        &self,
+        txns: &[Transaction],
+        indexes: &[usize],
+    ) -> Result<Vec<SignedTransaction>, TransactionError> {
+        transactions_at(txns, indexes)?
+            .into_iter()
+            .map(|t| self.sign(t, vec![]))
+            .collect()
+    }
+}
+
+/// Signs with a logic signature: contract account or delegated (single or multi) signature.
+/// The auth address is set if the program or multisig address isn't the sender. With a single
+/// delegated signature the signing key is unknown, so it's expected to be the sender's.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl TransactionSigner for SignedLogic {
+    async fn sign_transactions(
+        &self,
+        txns: &[Transaction],
+        indexes: &[usize],
+    ) -> Result<Vec<SignedTransaction>, TransactionError> {
+        transactions_at(txns, indexes)?
+            .into_iter()
+            .map(|t| {
+                let signer = match &self.sig {
+                    LogicSignature::ContractAccount => Some(self.as_address()),
+                    LogicSignature::DelegatedMultiSig(msig) => Some(msig.address()),
+                    LogicSignature::DelegatedSig(_) => None,
+                };
+                Ok(SignedTransaction {
+                    transaction: t.clone(),
+                    transaction_id: t.id()?,
+                    sig: TransactionSignature::Logic(self.clone()),
+                    auth_address: signer.and_then(|signer| auth_address(t, signer)),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::tests::payment;
+    use algonaut_core::{Address, CompiledTeal, LogicSignature};
+
+    async fn sign_all<S: TransactionSigner>(
+        signer: &S,
+        txns: &[Transaction],
+    ) -> Vec<SignedTransaction> {
+        let indexes: Vec<usize> = (0..txns.len()).collect();
+        signer.sign_transactions(txns, &indexes).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_account_signer() {
+        let account = Account::generate();
+        let txns = vec![payment(account.address(), 1), payment(account.address(), 1)];
+        let signed = account.sign_transactions(&txns, &[1]).await.unwrap();
+        assert_eq!(1, signed.len());
+        assert_eq!(
+            account.sign_transaction(txns[1].clone()).unwrap(),
+            signed[0]
+        );
+        assert!(account.sign_transactions(&txns, &[2]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_multisig_signer() {
+        let accounts = vec![
+            Account::generate(),
+            Account::generate(),
+            Account::generate(),
+        ];
+        let addresses: Vec<Address> = accounts.iter().map(|a| a.address()).collect();
+        let msig_address = MultisigAddress::new(1, 2, &addresses).unwrap();
+        let txns = vec![payment(msig_address.address(), 1)];
+
+        let signer =
+            MultisigSigner::new(msig_address.clone(), accounts.into_iter().take(2).collect())
+                .unwrap();
+        let signed = sign_all(&signer, &txns).await;
+        match &signed[0].sig {
+            TransactionSignature::Multi(msig) => {
+                assert_eq!(2, msig.subsigs.iter().filter(|s| s.sig.is_some()).count());
+                assert!(msig.verify(&txns[0].bytes_to_sign().unwrap()))
+            }
+            sig => panic!("unexpected signature: {:?}", sig),
+        }
+
+        assert_eq!(None, signed[0].auth_address);
+        assert!(signed[0].verify());
+
+        // sender rekeyed to the multisig address
+        let rekeyed_txns = vec![payment(Account::generate().address(), 1)];
+        let signed = sign_all(&signer, &rekeyed_txns).await;
+        assert_eq!(Some(msig_address.address()), signed[0].auth_address);
+        assert!(signed[0].verify());
+
+        assert!(MultisigSigner::new(msig_address.clone(), vec![]).is_err());
+        assert!(MultisigSigner::new(msig_address, vec![Account::generate()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_logic_signers() {
+        let program = CompiledTeal(vec![1, 32, 1, 1, 34]);
+        let contract_account = ContractAccount::new(program.clone());
+        let txns = vec![payment(*contract_account.address(), 1)];
+
+        let signed = sign_all(&contract_account, &txns).await;
+        assert_eq!(txns[0].id().unwrap(), signed[0].transaction_id);
+        assert_eq!(None, signed[0].auth_address);
+        assert!(signed[0].verify());
+
+        let rekeyed_txns = vec![payment(Account::generate().address(), 1)];
+        let signed = sign_all(&contract_account, &rekeyed_txns).await;
+        assert_eq!(Some(*contract_account.address()), signed[0].auth_address);
+        assert!(signed[0].verify());
+
+        let signed_logic = SignedLogic {
+            logic: program,
+            args: vec![vec![1]],
+            sig: LogicSignature::ContractAccount,
+        };
+        let signed = sign_all(&signed_logic, &txns).await;
+        assert_eq!(TransactionSignature::Logic(signed_logic), signed[0].sig);
+    }
+}
@@ -1,15 +1,17 @@
 use crate::account::Account;
 use crate::error::TransactionError;
 use algonaut_core::CompiledTeal;
-use algonaut_core::SignedLogic;
 use algonaut_core::SuggestedTransactionParams;
 use algonaut_core::ToMsgPack;
 use algonaut_core::{Address, MultisigSignature};
+use algonaut_core::{LogicSignature, SignedLogic};
 use algonaut_core::{MicroAlgos, Round, VotePk, VrfPk};
 use algonaut_crypto::HashDigest;
 use algonaut_crypto::Signature;
 use data_encoding::BASE32_NOPAD;
+use serde::Deserialize;
 use sha2::Digest;
+use std::io::Cursor;
 
 /// Enum containing the types of transactions and their specific fields
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -77,6 +79,11 @@ pub struct Transaction {
 }
 
 impl Transaction {
+    /// Decodes a msgpack encoded (unsigned) transaction.
+    pub fn from_msg_pack(bytes: &[u8]) -> Result<Transaction, TransactionError> {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+
     pub fn bytes_to_sign(&self) -> Result<Vec<u8>, TransactionError> {
         let encoded_tx = self.to_owned().to_msg_pack()?;
         let mut prefix_encoded_tx = b"TX".to_vec();
@@ -89,6 +96,12 @@ impl Transaction {
         Ok(HashDigest(hashed.into()))
     }
 
+    /// The id hashed with SHA-256, used in the SHA-256 transaction commitments of blocks.
+    pub fn raw_id_sha256(&self) -> Result<HashDigest, TransactionError> {
+        let hashed = sha2::Sha256::digest(&self.bytes_to_sign()?);
+        Ok(HashDigest(hashed.into()))
+    }
+
     pub fn id(&self) -> Result<String, TransactionError> {
         Ok(BASE32_NOPAD.encode(&self.raw_id()?.0))
     }
@@ -354,6 +367,10 @@ pub struct ApplicationCallTransaction {
     /// clear-state-program. The access is read-only.
     pub foreign_assets: Option<Vec<u64>>,
 
+    /// Lists the boxes that may be accessed by this application's approval-program and
+    /// clear-state-program. See [ApplicationCallTransaction::with_boxes].
+    pub boxes: Option<Vec<BoxReference>>,
+
     /// Holds the maximum number of global state values defined within a StateSchema object.
     pub global_state_schema: Option<StateSchema>,
 
@@ -364,6 +381,40 @@ pub struct ApplicationCallTransaction {
     pub extra_pages: u64,
 }
 
+impl ApplicationCallTransaction {
+    /// Sets the boxes that may be accessed by the application.
+    ///
+    /// Boxes are encoded with the index of their application in the foreign apps, so references to the
+    /// called application are set to 0 and the other applications are added to the foreign apps if missing.
+    pub fn with_boxes(mut self, boxes: Vec<BoxReference>) -> Self {
+        let boxes: Vec<BoxReference> = boxes
+            .into_iter()
+            .map(|b| match self.app_id {
+                Some(app_id) if app_id == b.app_id => BoxReference { app_id: 0, ..b },
+                _ => b,
+            })
+            .collect();
+        for b in boxes.iter().filter(|b| b.app_id != 0) {
+            let foreign_apps = self.foreign_apps.get_or_insert_with(Vec::new);
+            if !foreign_apps.contains(&b.app_id) {
+                foreign_apps.push(b.app_id);
+            }
+        }
+        self.boxes = Some(boxes).filter(|b| !b.is_empty());
+        self
+    }
+}
+
+/// A box of an application, accessible in an application call.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BoxReference {
+    /// The application owning the box, 0 for the called application.
+    pub app_id: u64,
+
+    /// The name of the box.
+    pub name: Vec<u8>,
+}
+
 /// An application transaction must indicate the action to be taken following the execution of its approvalProgram or clearStateProgram. The variants below describe the available actions.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ApplicationCallOnComplete {
@@ -402,6 +453,71 @@ pub struct SignedTransaction {
     pub transaction: Transaction,
     pub transaction_id: String,
     pub sig: TransactionSignature,
+    /// The address authorizing the transaction, if the sender was rekeyed to it.
+    pub auth_address: Option<Address>,
+}
+
+impl SignedTransaction {
+    /// Decodes a msgpack encoded signed transaction, e.g. a `.stxn` file written by goal
+    /// or the signed transaction returned by KMD.
+    ///
+    /// The transaction id is not part of the encoding, it's recomputed from the transaction.
+    pub fn from_msg_pack(bytes: &[u8]) -> Result<SignedTransaction, TransactionError> {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+
+    /// The address that has to sign the transaction: the auth address if set, otherwise the sender.
+    pub fn authorizer(&self) -> Address {
+        self.auth_address
+            .unwrap_or_else(|| self.transaction.sender())
+    }
+
+    /// Verifies the signature against the authorizer address.
+    pub fn verify(&self) -> bool {
+        let authorizer = self.authorizer();
+        match &self.sig {
+            TransactionSignature::Single(sig) => self
+                .transaction
+                .bytes_to_sign()
+                .map(|bytes| authorizer.as_public_key().verify(&bytes, sig))
+                .unwrap_or(false),
+            TransactionSignature::Multi(msig) => {
+                msig.address() == authorizer
+                    && self
+                        .transaction
+                        .bytes_to_sign()
+                        .map(|bytes| msig.verify(&bytes))
+                        .unwrap_or(false)
+            }
+            TransactionSignature::Logic(lsig) => {
+                let msig_matches = match &lsig.sig {
+                    LogicSignature::DelegatedMultiSig(msig) => msig.address() == authorizer,
+                    _ => true,
+                };
+                msig_matches && lsig.verify(authorizer)
+            }
+        }
+    }
+
+    /// Decodes concatenated msgpack encoded signed transactions, e.g. a signed group written by goal.
+    pub fn from_msg_pack_group(bytes: &[u8]) -> Result<Vec<SignedTransaction>, TransactionError> {
+        let mut cursor = Cursor::new(bytes);
+        let mut txns = vec![];
+        while (cursor.position() as usize) < bytes.len() {
+            let mut deserializer = rmp_serde::Deserializer::new(&mut cursor);
+            txns.push(SignedTransaction::deserialize(&mut deserializer)?);
+        }
+        Ok(txns)
+    }
+}
+
+/// The auth address to set when `signer` signs the transaction: none if it's the sender.
+pub(crate) fn auth_address(transaction: &Transaction, signer: Address) -> Option<Address> {
+    if transaction.sender() == signer {
+        None
+    } else {
+        Some(signer)
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -410,3 +526,96 @@ pub enum TransactionSignature {
     Multi(MultisigSignature),
     Logic(SignedLogic),
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::builder::TxnFee;
+    use crate::{Pay, TxnBuilder};
+
+    /// A payment of the sender to itself, shared by the tests of the crate.
+    pub(crate) fn payment(sender: Address, amount: u64) -> Transaction {
+        TxnBuilder::new(
+            TxnFee::Fixed(MicroAlgos(1000)),
+            Round(1),
+            Round(1001),
+            HashDigest([1; 32]),
+            Pay::new(sender, sender, MicroAlgos(amount)).build(),
+        )
+        .build()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_transaction_from_msg_pack() {
+        let txn = payment(Account::generate().address(), 1);
+        let bytes = txn.clone().to_msg_pack().unwrap();
+        assert_eq!(txn, Transaction::from_msg_pack(&bytes).unwrap());
+    }
+
+    #[test]
+    fn test_signed_transaction_from_msg_pack() {
+        let account = Account::generate();
+        let signed = account
+            .sign_transaction(payment(account.address(), 1))
+            .unwrap();
+        let decoded = SignedTransaction::from_msg_pack(&signed.to_msg_pack().unwrap()).unwrap();
+        assert_eq!(signed, decoded);
+        assert_eq!(signed.transaction.id().unwrap(), decoded.transaction_id);
+
+        assert!(SignedTransaction::from_msg_pack(&[0x81, 0xa3]).is_err());
+    }
+
+    #[test]
+    fn test_sign_rekeyed_transaction() {
+        let sender = Account::generate();
+        let auth = Account::generate();
+
+        let signed = sender
+            .sign_transaction(payment(sender.address(), 1))
+            .unwrap();
+        assert_eq!(None, signed.auth_address);
+        assert!(signed.verify());
+
+        let signed = auth.sign_transaction(payment(sender.address(), 1)).unwrap();
+        assert_eq!(Some(auth.address()), signed.auth_address);
+        assert_eq!(auth.address(), signed.authorizer());
+        assert!(signed.verify());
+
+        let decoded = SignedTransaction::from_msg_pack(&signed.to_msg_pack().unwrap()).unwrap();
+        assert_eq!(signed, decoded);
+
+        // the signature doesn't match the sender
+        let not_rekeyed = SignedTransaction {
+            auth_address: None,
+            ..signed
+        };
+        assert!(!not_rekeyed.verify());
+    }
+
+    #[test]
+    fn test_signed_transaction_group_from_msg_pack() {
+        let account = Account::generate();
+        let signed: Vec<SignedTransaction> = (1..=3)
+            .map(|amount| {
+                account
+                    .sign_transaction(payment(account.address(), amount))
+                    .unwrap()
+            })
+            .collect();
+        let mut bytes = vec![];
+        for t in &signed {
+            bytes.extend(t.to_msg_pack().unwrap());
+        }
+        assert_eq!(
+            signed,
+            SignedTransaction::from_msg_pack_group(&bytes).unwrap()
+        );
+        assert!(SignedTransaction::from_msg_pack_group(&[])
+            .unwrap()
+            .is_empty());
+
+        bytes.push(0x81);
+        assert!(SignedTransaction::from_msg_pack_group(&bytes).is_err());
+    }
+}
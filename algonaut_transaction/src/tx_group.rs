@@ -12,7 +12,7 @@ pub struct TxGroup {
 }
 
 impl TxGroup {
-    const MAX_TX_GROUP_SIZE: usize = 16;
+    pub const MAX_TX_GROUP_SIZE: usize = 16;
 
     pub fn new(tx_group_hashes: Vec<HashDigest>) -> TxGroup {
         TxGroup { tx_group_hashes }
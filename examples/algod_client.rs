@@ -1,5 +1,6 @@
 use algonaut::algod::v2::Algod;
 use algonaut::core::Round;
+use algonaut::transaction::block::verify_chain;
 use dotenv::dotenv;
 use std::env;
 use std::error::Error;
@@ -25,5 +26,10 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let last_block = algod.block(Round(node_status.last_round)).await?;
     println!("{:#?}", last_block);
 
+    // check that the last block follows the previous one
+    let previous_block = algod.block(Round(node_status.last_round - 1)).await?;
+    println!("last block hash: {:?}", last_block.header.hash()?);
+    verify_chain(&[previous_block.header, last_block.header])?;
+
     Ok(())
 }
@@ -1,6 +1,4 @@
 use algonaut::algod::v2::Algod;
-use algonaut::error::ServiceError;
-use algonaut::model::algod::v2::PendingTransaction;
 use algonaut::transaction::account::Account;
 use algonaut::transaction::transaction::StateSchema;
 use algonaut::transaction::CreateApplication;
@@ -8,7 +6,6 @@ use algonaut::transaction::TxnBuilder;
 use dotenv::dotenv;
 use std::env;
 use std::error::Error;
-use std::time::{Duration, Instant};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
@@ -66,30 +63,10 @@ int 1
 
     let send_response = algod.broadcast_signed_transaction(&signed_t).await?;
 
-    let pending_t = wait_for_pending_transaction(&algod, &send_response.tx_id).await?;
-    println!(
-        "Application id: {:?}",
-        pending_t.map(|t| t.application_index)
-    );
+    let pending_t = algod
+        .wait_for_confirmation(&send_response.tx_id, 10)
+        .await?;
+    println!("Application id: {:?}", pending_t.application_index);
 
     Ok(())
 }
-
-/// Utility function to wait on a transaction to be confirmed
-async fn wait_for_pending_transaction(
-    algod: &Algod,
-    txid: &str,
-) -> Result<Option<PendingTransaction>, ServiceError> {
-    let timeout = Duration::from_secs(10);
-    let start = Instant::now();
-    loop {
-        let pending_transaction = algod.pending_transaction_with_id(txid).await?;
-        // If the transaction has been confirmed or we time out, exit.
-        if pending_transaction.confirmed_round.is_some() {
-            return Ok(Some(pending_transaction));
-        } else if start.elapsed() >= timeout {
-            return Ok(None);
-        }
-        std::thread::sleep(Duration::from_millis(250))
-    }
-}
@@ -16,9 +16,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
     println!("found {} accounts", accounts.len());
 
     // query accounts with custom query parameters.
-    let mut accounts_query = QueryAccount::default();
     // why 2? see: https://github.com/algorand/indexer/issues/516
-    accounts_query.limit = Some(2);
+    let accounts_query = QueryAccount::new().limit(2);
 
     let accounts = indexer.accounts(&accounts_query).await?.accounts;
     println!("found {} accounts", accounts.len());
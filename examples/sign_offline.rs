@@ -1,12 +1,11 @@
 use algonaut::algod::v2::Algod;
-use algonaut::core::{MicroAlgos, ToMsgPack};
+use algonaut::core::MicroAlgos;
 use algonaut::transaction::account::Account;
+use algonaut::transaction::file;
 use algonaut::transaction::{Pay, TxnBuilder};
 use dotenv::dotenv;
 use std::env;
 use std::error::Error;
-use std::fs::File;
-use std::io::Write;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
@@ -30,13 +29,20 @@ async fn main() -> Result<(), Box<dyn Error>> {
     )
     .build()?;
 
-    // sign the transaction
-    let signed_transaction = account.sign_transaction(t)?;
-    let bytes = signed_transaction.to_msg_pack()?;
+    // save the unsigned transaction, like `goal clerk send --out` does,
+    // to move it to the offline machine
+    let unsigned_filename = "./unsigned.txn";
+    file::write_transactions(unsigned_filename, &[t])?;
+    println!("Saved unsigned transaction to file: {}", unsigned_filename);
+
+    // on the offline machine: read and sign the transaction, like `goal clerk sign` does
+    let mut signed_transactions = vec![];
+    for t in file::read_transactions(unsigned_filename)? {
+        signed_transactions.push(account.sign_transaction(t)?);
+    }
 
     let filename = "./signed.tx";
-    let mut f = File::create(filename)?;
-    f.write_all(&bytes)?;
+    file::write_signed_transactions(filename, &signed_transactions)?;
 
     println!("Saved signed transaction to file: {}", filename);
 
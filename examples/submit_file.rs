@@ -1,22 +1,25 @@
 use algonaut::algod::v2::Algod;
+use algonaut::transaction::file;
 use dotenv::dotenv;
 use std::env;
 use std::error::Error;
-use std::fs::File;
-use std::io::Read;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     // load variables in .env
     dotenv().ok();
 
-    let mut f = File::open("./signed.tx")?;
-    let mut raw_transaction = Vec::new();
-    let _ = f.read_to_end(&mut raw_transaction)?;
+    // signed transactions or group, e.g. written by `goal clerk sign`
+    let signed_transactions = file::read_signed_transactions("./signed.tx")?;
+    for t in &signed_transactions {
+        println!("Read transaction: {}", t.transaction_id);
+    }
 
     let algod = Algod::new(&env::var("ALGOD_URL")?, &env::var("ALGOD_TOKEN")?)?;
 
-    let send_response = algod.broadcast_raw_transaction(&raw_transaction).await?;
+    let send_response = algod
+        .broadcast_signed_transactions(&signed_transactions)
+        .await?;
     println!("Transaction ID: {}", send_response.tx_id);
 
     Ok(())
@@ -0,0 +1,429 @@
+use super::Algod;
+use crate::error::ServiceError;
+use algonaut_client::retry::RetryPolicy;
+use algonaut_core::{Address, Round};
+use algonaut_transaction::block::{ApplyData, Block, SignedTransactionInBlock};
+use algonaut_transaction::{Transaction, TransactionType};
+use futures::stream::{self, Stream, StreamExt};
+use futures_timer::Delay;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[cfg(not(target_arch = "wasm32"))]
+type InnerStream<'a> = stream::BoxStream<'a, Result<Block, ServiceError>>;
+#[cfg(target_arch = "wasm32")]
+type InnerStream<'a> = stream::LocalBoxStream<'a, Result<Block, ServiceError>>;
+
+/// Follows the chain, yielding the blocks in order, starting at a round.
+///
+/// Waits for new rounds with [Algod::status_after_round], so the stream doesn't end.
+/// An error (e.g. the node is unreachable) is yielded as item and doesn't end the stream either:
+/// polling again retries the same round, after the backoff of the default [RetryPolicy] (growing
+/// with the consecutive errors), so the caller decides whether to go on.
+pub struct BlockStream<'a> {
+    inner: InnerStream<'a>,
+}
+
+impl<'a> BlockStream<'a> {
+    /// Yields all the blocks, starting at `start`.
+    pub fn new(algod: &'a Algod, start: Round) -> BlockStream<'a> {
+        Self::start(algod, start, None)
+    }
+
+    /// Yields only the blocks with transactions matching the filter, starting at `start`.
+    pub fn with_filter(algod: &'a Algod, start: Round, filter: BlockFilter) -> BlockStream<'a> {
+        Self::start(algod, start, Some(filter))
+    }
+
+    fn start(algod: &'a Algod, start: Round, filter: Option<BlockFilter>) -> BlockStream<'a> {
+        let state = FollowState {
+            algod,
+            next_round: start.0,
+            last_round: None,
+            filter,
+            backoff: RetryPolicy::default(),
+            failures: 0,
+        };
+        let inner = stream::unfold(state, |mut state| async move {
+            let next = state.next().await;
+            Some((next, state))
+        });
+        #[cfg(not(target_arch = "wasm32"))]
+        let inner = inner.boxed();
+        #[cfg(target_arch = "wasm32")]
+        let inner = inner.boxed_local();
+        BlockStream { inner }
+    }
+}
+
+impl Stream for BlockStream<'_> {
+    type Item = Result<Block, ServiceError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+impl std::fmt::Debug for BlockStream<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockStream").finish()
+    }
+}
+
+struct FollowState<'a> {
+    algod: &'a Algod,
+    next_round: u64,
+    /// Last round known to be available, unknown after an error.
+    last_round: Option<u64>,
+    filter: Option<BlockFilter>,
+    /// The delays before retrying after errors.
+    backoff: RetryPolicy,
+    /// Consecutive errors.
+    failures: u32,
+}
+
+impl FollowState<'_> {
+    async fn next(&mut self) -> Result<Block, ServiceError> {
+        if self.failures > 0 {
+            Delay::new(self.backoff.delay(self.failures)).await;
+        }
+        match self.follow().await {
+            Ok(block) => {
+                self.failures = 0;
+                Ok(block)
+            }
+            Err(e) => {
+                self.failures = self.failures.saturating_add(1);
+                self.last_round = None;
+                Err(e)
+            }
+        }
+    }
+
+    async fn follow(&mut self) -> Result<Block, ServiceError> {
+        loop {
+            let available = matches!(self.last_round, Some(last) if self.next_round <= last);
+            if !available {
+                // returns when the round is available, or after a timeout with the current round
+                let status = self
+                    .algod
+                    .status_after_round(Round(self.next_round.saturating_sub(1)))
+                    .await?;
+                self.last_round = Some(status.last_round);
+                continue;
+            }
+            let block = self.algod.block(Round(self.next_round)).await?;
+            self.next_round += 1;
+            match &self.filter {
+                Some(filter) if !filter.matches_block(&block) => continue,
+                _ => return Ok(block),
+            }
+        }
+    }
+}
+
+/// Selects the blocks with transactions involving any of the addresses, applications or assets.
+///
+/// Inner transactions and the applications and assets created by a transaction are considered too.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockFilter {
+    pub addresses: Vec<Address>,
+    pub app_ids: Vec<u64>,
+    pub asset_ids: Vec<u64>,
+}
+
+impl BlockFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn address(mut self, address: Address) -> Self {
+        self.addresses.push(address);
+        self
+    }
+
+    pub fn app_id(mut self, app_id: u64) -> Self {
+        self.app_ids.push(app_id);
+        self
+    }
+
+    pub fn asset_id(mut self, asset_id: u64) -> Self {
+        self.asset_ids.push(asset_id);
+        self
+    }
+
+    /// Whether any transaction of the block matches.
+    pub fn matches_block(&self, block: &Block) -> bool {
+        block.payset.iter().any(|txn| self.matches(txn))
+    }
+
+    /// Whether the transaction (or one of its inner transactions) matches.
+    pub fn matches(&self, txn: &SignedTransactionInBlock) -> bool {
+        let signed = &txn.signed_transaction;
+        signed
+            .auth_address
+            .iter()
+            .any(|a| self.addresses.contains(a))
+            || self.matches_transaction(&signed.transaction, &txn.apply_data)
+    }
+
+    fn matches_transaction(&self, txn: &Transaction, apply_data: &ApplyData) -> bool {
+        let (addresses, app_ids, asset_ids) = involved(txn);
+        addresses
+            .iter()
+            .chain(txn.rekey_to.iter())
+            .any(|a| self.addresses.contains(a))
+            || app_ids
+                .iter()
+                .chain(apply_data.application_id.iter())
+                .any(|id| self.app_ids.contains(id))
+            || asset_ids
+                .iter()
+                .chain(apply_data.config_asset.iter())
+                .any(|id| self.asset_ids.contains(id))
+            || apply_data
+                .inner_txns
+                .iter()
+                .any(|inner| self.matches_transaction(&inner.transaction, &inner.apply_data))
+    }
+}
+
+/// The addresses, application ids and asset ids referenced by the transaction.
+fn involved(txn: &Transaction) -> (Vec<Address>, Vec<u64>, Vec<u64>) {
+    let mut addresses = vec![txn.sender()];
+    let mut app_ids = vec![];
+    let mut asset_ids = vec![];
+    match &txn.txn_type {
+        TransactionType::Payment(p) => {
+            addresses.push(p.receiver);
+            addresses.extend(p.close_remainder_to);
+        }
+        TransactionType::KeyRegistration(_) => {}
+        TransactionType::AssetConfigurationTransaction(c) => {
+            asset_ids.extend(c.config_asset);
+        }
+        TransactionType::AssetTransferTransaction(t) => {
+            addresses.push(t.receiver);
+            addresses.extend(t.close_to);
+            asset_ids.push(t.xfer);
+        }
+        TransactionType::AssetAcceptTransaction(a) => {
+            asset_ids.push(a.xfer);
+        }
+        TransactionType::AssetClawbackTransaction(c) => {
+            addresses.push(c.asset_sender);
+            addresses.push(c.asset_receiver);
+            addresses.extend(c.asset_close_to);
+            asset_ids.push(c.xfer);
+        }
+        TransactionType::AssetFreezeTransaction(f) => {
+            addresses.push(f.freeze_account);
+            asset_ids.push(f.asset_id);
+        }
+        TransactionType::ApplicationCallTransaction(c) => {
+            addresses.extend(c.accounts.iter().flatten());
+            app_ids.extend(c.app_id);
+            app_ids.extend(c.foreign_apps.iter().flatten());
+            asset_ids.extend(c.foreign_assets.iter().flatten());
+        }
+    }
+    (addresses, app_ids, asset_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algonaut_client::error::ClientError;
+    use algonaut_client::transport::{HttpRequest, HttpResponse, HttpTransport};
+    use algonaut_core::{MicroAlgos, SuggestedTransactionParams};
+    use algonaut_crypto::HashDigest;
+    use algonaut_transaction::block::TransactionWithApplyData;
+    use algonaut_transaction::builder::CallApplication;
+    use algonaut_transaction::{account::Account, Pay, TransferAsset, TxnBuilder};
+    use async_trait::async_trait;
+    use serde::Serialize;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    fn params() -> SuggestedTransactionParams {
+        SuggestedTransactionParams {
+            genesis_id: "testnet-v1.0".to_owned(),
+            genesis_hash: HashDigest([1; 32]),
+            consensus_version: "".to_owned(),
+            fee_per_byte: MicroAlgos(0),
+            min_fee: MicroAlgos(1000),
+            first_valid: Round(1),
+            last_valid: Round(1001),
+        }
+    }
+
+    fn apply_data(inner_txns: Vec<TransactionWithApplyData>) -> ApplyData {
+        ApplyData {
+            closing_amount: MicroAlgos(0),
+            asset_closing_amount: 0,
+            sender_rewards: MicroAlgos(0),
+            receiver_rewards: MicroAlgos(0),
+            close_rewards: MicroAlgos(0),
+            config_asset: None,
+            application_id: None,
+            global_delta: Default::default(),
+            local_deltas: Default::default(),
+            shared_accounts: vec![],
+            logs: vec![],
+            inner_txns,
+        }
+    }
+
+    fn in_block(
+        account: &Account,
+        txn: Transaction,
+        inner_txns: Vec<TransactionWithApplyData>,
+    ) -> SignedTransactionInBlock {
+        SignedTransactionInBlock {
+            signed_transaction: account.sign_transaction(txn).unwrap(),
+            apply_data: apply_data(inner_txns),
+        }
+    }
+
+    #[test]
+    fn test_filter_payment() {
+        let sender = Account::generate();
+        let receiver = Account::generate().address();
+        let txn = TxnBuilder::with(
+            &params(),
+            Pay::new(sender.address(), receiver, MicroAlgos(1)).build(),
+        )
+        .build()
+        .unwrap();
+        let txn = in_block(&sender, txn, vec![]);
+
+        assert!(BlockFilter::new().address(sender.address()).matches(&txn));
+        assert!(BlockFilter::new().address(receiver).matches(&txn));
+        assert!(!BlockFilter::new()
+            .address(Account::generate().address())
+            .app_id(1)
+            .asset_id(1)
+            .matches(&txn));
+        assert!(!BlockFilter::new().matches(&txn));
+    }
+
+    #[test]
+    fn test_filter_inner_transactions() {
+        let sender = Account::generate();
+        let app_address = Account::generate().address();
+        let receiver = Account::generate().address();
+        let call = TxnBuilder::with(
+            &params(),
+            CallApplication::new(sender.address(), 5)
+                .foreign_assets(vec![10])
+                .build(),
+        )
+        .build()
+        .unwrap();
+        let transfer = TxnBuilder::with(
+            &params(),
+            TransferAsset::new(app_address, 11, 1, receiver).build(),
+        )
+        .build()
+        .unwrap();
+        let txn = in_block(
+            &sender,
+            call,
+            vec![TransactionWithApplyData {
+                transaction: transfer,
+                apply_data: apply_data(vec![]),
+            }],
+        );
+
+        assert!(BlockFilter::new().app_id(5).matches(&txn));
+        assert!(BlockFilter::new().asset_id(10).matches(&txn));
+        assert!(BlockFilter::new().asset_id(11).matches(&txn));
+        assert!(BlockFilter::new().address(receiver).matches(&txn));
+        assert!(!BlockFilter::new().app_id(6).asset_id(12).matches(&txn));
+    }
+
+    #[derive(Serialize)]
+    struct Header {
+        rnd: u64,
+        gen: &'static str,
+    }
+
+    /// A block response, with the certificate first.
+    #[derive(Serialize)]
+    struct BlockResponse {
+        cert: Header,
+        block: Header,
+    }
+
+    /// A node at round 101, failing the first request of block 100.
+    #[derive(Debug, Default)]
+    struct Node {
+        requests: Mutex<Vec<(String, Instant)>>,
+    }
+
+    #[async_trait]
+    impl HttpTransport for Node {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse, ClientError> {
+            let path = request.url["http://localhost:4001/".len()..].to_owned();
+            let mut requests = self.requests.lock().unwrap();
+            let failed_before = requests.iter().any(|(p, _)| *p == path);
+            requests.push((path.clone(), Instant::now()));
+            let (status, body) = if path.starts_with("v2/status/wait-for-block-after/") {
+                let status = r#"{"catchup-time":0,"last-round":101,"last-version":"future",
+                    "next-version":"future","next-version-round":102,"next-version-supported":true,
+                    "stopped-at-unsupported-round":false,"time-since-last-round":0}"#;
+                (200, status.as_bytes().to_vec())
+            } else if path == "v2/blocks/100?format=msgpack" && !failed_before {
+                (503, br#"{"message":"unavailable"}"#.to_vec())
+            } else {
+                let round = path["v2/blocks/".len()..path.len() - "?format=msgpack".len()]
+                    .parse()
+                    .unwrap();
+                let header = || Header {
+                    rnd: round,
+                    gen: "testnet-v1.0",
+                };
+                let response = BlockResponse {
+                    cert: header(),
+                    block: header(),
+                };
+                (200, rmp_serde::to_vec_named(&response).unwrap())
+            };
+            Ok(HttpResponse {
+                status,
+                headers: vec![],
+                body,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_follow_after_error() {
+        let node = Arc::new(Node::default());
+        let algod = Algod::with_transport("http://localhost:4001", vec![], node.clone()).unwrap();
+        let mut blocks = BlockStream::new(&algod, Round(100));
+
+        assert!(blocks.next().await.unwrap().is_err());
+        let block = blocks.next().await.unwrap().unwrap();
+        assert_eq!(Round(100), block.header.round);
+        assert_eq!("testnet-v1.0", block.header.genesis_id);
+        assert_eq!(
+            Round(101),
+            blocks.next().await.unwrap().unwrap().header.round
+        );
+
+        let requests = node.requests.lock().unwrap();
+        assert_eq!(
+            vec![
+                "v2/status/wait-for-block-after/99",
+                "v2/blocks/100?format=msgpack",
+                "v2/status/wait-for-block-after/99",
+                "v2/blocks/100?format=msgpack",
+                "v2/blocks/101?format=msgpack",
+            ],
+            requests.iter().map(|(p, _)| p.as_str()).collect::<Vec<_>>()
+        );
+        // at least half of the initial backoff, with the jitter
+        assert!(requests[2].1 - requests[1].1 >= Duration::from_millis(50));
+    }
+}
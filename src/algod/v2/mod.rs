@@ -1,14 +1,34 @@
-use algonaut_client::{algod::v2::Client, token::ApiToken, Headers};
+use algonaut_client::{
+    algod::v2::Client,
+    retry::RetryPolicy,
+    token::ApiToken,
+    transport::{HttpTransport, ReqwestTransport},
+    Headers,
+};
 use algonaut_core::{Address, CompiledTeal, Round, SuggestedTransactionParams, ToMsgPack};
-use algonaut_encoding::decode_base64;
+use algonaut_crypto::merkle::{self, HashType};
+use algonaut_encoding::{decode_base64, msgpack_map_entries};
 use algonaut_model::algod::v2::{
-    Account, Application, Asset, Block, Catchup, DryrunRequest, DryrunResponse, GenesisBlock,
-    KeyRegistration, NodeStatus, PendingTransaction, PendingTransactions, Supply,
-    TransactionParams, TransactionResponse, Version,
+    Account, AccountStateDelta, Application, Asset, BoxValue, Boxes, Catchup, DryrunRequest,
+    DryrunResponse, EvalDeltaKeyValue, GenesisBlock, KeyRegistration, NodeStatus, SimulateOptions,
+    SimulateRequest, SimulateRequestTransactionGroup, SimulateResponse, Supply, TransactionParams,
+    TransactionProof, TransactionResponse, Version,
+};
+use algonaut_transaction::{
+    block::{Block, BlockHeader},
+    SignedTransaction, Transaction,
 };
-use algonaut_transaction::SignedTransaction;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_bytes::ByteBuf;
+use std::sync::Arc;
 
 use crate::error::ServiceError;
+use crate::Error;
+
+mod block_stream;
+pub use algonaut_client::algod::v2::ResponseFormat;
+pub use algonaut_client::algod::v2::TransactionRejection;
+pub use block_stream::{BlockFilter, BlockStream};
 
 #[derive(Debug)]
 pub struct Algod {
@@ -36,6 +56,67 @@ impl Algod {
         })
     }
 
+    /// Build a v2 client for Algorand protocol daemon.
+    /// Use this initializer to send the requests with a custom transport, e.g. to record and
+    /// replay them in tests or to route them through a proxy.
+    ///
+    /// Returns an error if the url or headers have an invalid format.
+    pub fn with_transport(
+        url: &str,
+        headers: Headers,
+        transport: Arc<dyn HttpTransport>,
+    ) -> Result<Algod, ServiceError> {
+        Ok(Algod {
+            client: Client::with_transport(url, headers, transport)?,
+        })
+    }
+
+    /// Build a v2 client for several Algorand protocol daemons, e.g. own nodes and a third party
+    /// service, with the url and headers of each one.
+    ///
+    /// The requests are sent to the first healthy node, in the given order, which isn't catching
+    /// up nor behind the others. After a connection error, a timeout or a 5xx response, they're
+    /// sent to the next one, except requests which aren't idempotent (e.g. broadcasting a
+    /// transaction): the node may have handled them, so they're sent again only after a
+    /// connection error. The timeout of the [retry policy](Self::with_retry_policy) applies to
+    /// each node.
+    ///
+    /// Returns an error if there are no endpoints, or an url or headers have an invalid format.
+    pub fn with_endpoints(endpoints: Vec<(&str, Headers)>) -> Result<Algod, ServiceError> {
+        Ok(Algod {
+            client: Client::with_endpoints(endpoints, Arc::new(ReqwestTransport::new()))?,
+        })
+    }
+
+    /// Retries the failed requests as allowed by the policy: by default they aren't retried.
+    ///
+    /// Only connection errors are retried for requests which aren't idempotent, e.g. broadcasting
+    /// a transaction.
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Algod {
+        Algod {
+            client: self.client.with_retry_policy(retry_policy),
+        }
+    }
+
+    /// Requests the account and application information in the format: json by default.
+    ///
+    /// Msgpack is byte exact (e.g. no base64 round trip of the TEAL values) and faster to decode,
+    /// but the msgpack account has neither the round, the pending rewards nor the creators of the
+    /// held assets: see [raw::AccountData::into_account](algonaut_model::algod::v2::raw::AccountData::into_account).
+    pub fn with_format(self, format: ResponseFormat) -> Algod {
+        Algod {
+            client: self.client.with_format(format),
+        }
+    }
+
+    /// Orders the endpoints by the health, catchup state and last round of their nodes.
+    ///
+    /// Done before the first request and after a node fails: call it periodically to detect
+    /// nodes falling behind. Does nothing if the client was built with a single endpoint.
+    pub async fn check_endpoints(&self) {
+        self.client.check_endpoints().await
+    }
+
     /// Returns the entire genesis file in json.
     pub async fn genesis(&self) -> Result<GenesisBlock, ServiceError> {
         Ok(self.client.genesis().await?)
@@ -69,10 +150,11 @@ impl Algod {
         address: &Address,
         max: u64,
     ) -> Result<PendingTransactions, ServiceError> {
-        Ok(self
+        let bytes = self
             .client
             .pending_transactions_for(&address.to_string(), max)
-            .await?)
+            .await?;
+        Ok(rmp_serde::from_slice(&bytes)?)
     }
 
     /// Get application information.
@@ -83,6 +165,26 @@ impl Algod {
         Ok(self.client.application_information(id).await?)
     }
 
+    /// Get the box names of an application.
+    ///
+    /// Returns at most `max` names, if set.
+    pub async fn application_boxes(
+        &self,
+        id: u64,
+        max: Option<u64>,
+    ) -> Result<Boxes, ServiceError> {
+        Ok(self.client.application_boxes(id, max).await?)
+    }
+
+    /// Get an application box by name.
+    pub async fn application_box_by_name(
+        &self,
+        id: u64,
+        name: &[u8],
+    ) -> Result<BoxValue, ServiceError> {
+        Ok(self.client.application_box_by_name(id, name).await?)
+    }
+
     /// Get asset information.
     ///
     /// Given a asset id, it returns asset information including creator, name,
@@ -91,9 +193,30 @@ impl Algod {
         Ok(self.client.asset_information(id).await?)
     }
 
-    /// Get the block for the given round.
+    /// Get the block for the given round, with its transactions.
     pub async fn block(&self, round: Round) -> Result<Block, ServiceError> {
-        Ok(self.client.block(round).await?)
+        let bytes = self.client.block(round).await?;
+        // the certificate isn't decoded
+        let (_, block) = msgpack_map_entries(&bytes)?
+            .into_iter()
+            .find(|(key, _)| *key == BLOCK_KEY)
+            .ok_or_else(|| ServiceError::Internal("Missing block in response".to_owned()))?;
+        Ok(Block::from_msg_pack(block)?)
+    }
+
+    /// Gets a proof that the transaction is in the block of `round`.
+    ///
+    /// The proof can be verified against the header of the block with [verify_transaction_proof].
+    pub async fn transaction_proof(
+        &self,
+        round: Round,
+        txid: &str,
+        hash_type: HashType,
+    ) -> Result<TransactionProof, ServiceError> {
+        Ok(self
+            .client
+            .transaction_proof(round, txid, hash_type)
+            .await?)
     }
 
     /// Starts a catchpoint catchup.
@@ -171,6 +294,8 @@ impl Algod {
     }
 
     /// Broadcasts a transaction to the network.
+    ///
+    /// If the node rejects the transaction, [ServiceError::rejection] tells why.
     pub async fn broadcast_signed_transaction(
         &self,
         txn: &SignedTransaction,
@@ -207,6 +332,50 @@ impl Algod {
         Ok(self.client.broadcast_raw_transaction(rawtxn).await?)
     }
 
+    /// Simulates the execution of a transaction group, without broadcasting it.
+    ///
+    /// The results contain e.g. the logs and budget consumed per transaction and, if the group
+    /// failed, the failure message and the path to the failing transaction.
+    pub async fn simulate(
+        &self,
+        txns: &[SignedTransaction],
+        options: SimulateOptions,
+    ) -> Result<SimulateResponse<PendingTransaction>, ServiceError> {
+        self.simulate_group(txns.iter().collect(), options).await
+    }
+
+    /// Simulates the execution of an unsigned transaction group, as if it had correct signatures.
+    ///
+    /// Sets [allow_empty_signatures](SimulateOptions::allow_empty_signatures).
+    pub async fn simulate_unsigned(
+        &self,
+        txns: &[Transaction],
+        options: SimulateOptions,
+    ) -> Result<SimulateResponse<PendingTransaction<Transaction>>, ServiceError> {
+        let txns = txns.iter().map(|txn| UnsignedTransaction { txn }).collect();
+        let options = SimulateOptions {
+            allow_empty_signatures: Some(true),
+            ..options
+        };
+        self.simulate_group(txns, options).await
+    }
+
+    async fn simulate_group<T: Serialize, R: PendingTransactionTxn>(
+        &self,
+        txns: Vec<T>,
+        options: SimulateOptions,
+    ) -> Result<SimulateResponse<PendingTransaction<R>>, ServiceError> {
+        let request = SimulateRequest {
+            txn_groups: vec![SimulateRequestTransactionGroup { txns }],
+            options,
+        };
+        let bytes = self
+            .client
+            .simulate(&rmp_serde::to_vec_named(&request)?)
+            .await?;
+        Ok(rmp_serde::from_slice(&bytes)?)
+    }
+
     /// Get parameters for constructing a new transaction.
     pub async fn transaction_params(&self) -> Result<TransactionParams, ServiceError> {
         Ok(self.client.transaction_params().await?)
@@ -236,7 +405,8 @@ impl Algod {
         &self,
         max: u64,
     ) -> Result<PendingTransactions, ServiceError> {
-        Ok(self.client.pending_transactions(max).await?)
+        let bytes = self.client.pending_transactions(max).await?;
+        Ok(rmp_serde::from_slice(&bytes)?)
     }
 
     /// Get a specific pending transaction.
@@ -253,7 +423,42 @@ impl Algod {
         &self,
         txid: &str,
     ) -> Result<PendingTransaction, ServiceError> {
-        Ok(self.client.pending_transaction_with_id(txid).await?)
+        let bytes = self.client.pending_transaction_with_id(txid).await?;
+        Ok(rmp_serde::from_slice(&bytes)?)
+    }
+
+    /// Waits for the transaction to be confirmed, for at most `max_rounds` rounds after the current one.
+    ///
+    /// Returns [ServiceError::TransactionRejected] if the node removed the transaction from the pool,
+    /// and [ServiceError::ConfirmationTimeout] if it's not confirmed after `max_rounds`.
+    pub async fn wait_for_confirmation(
+        &self,
+        txid: &str,
+        max_rounds: u64,
+    ) -> Result<PendingTransaction, ServiceError> {
+        let start_round = self.status().await?.last_round + 1;
+        let mut round = start_round;
+        loop {
+            let pending = self.pending_transaction_with_id(txid).await?;
+            if pending.confirmed_round.is_some() {
+                return Ok(pending);
+            }
+            if !pending.pool_error.is_empty() {
+                return Err(ServiceError::TransactionRejected {
+                    txid: txid.to_owned(),
+                    message: pending.pool_error,
+                });
+            }
+            // checked after waiting for the last round too, which may have confirmed it
+            if round >= start_round + max_rounds {
+                return Err(ServiceError::ConfirmationTimeout {
+                    txid: txid.to_owned(),
+                    rounds: max_rounds,
+                });
+            }
+            self.status_after_round(Round(round)).await?;
+            round += 1;
+        }
     }
 
     /// Retrieves the current version
@@ -262,9 +467,240 @@ impl Algod {
     }
 }
 
+/// Verifies that the transaction is in the block of the header, with a proof fetched with
+/// [Algod::transaction_proof].
+///
+/// The proof is checked against the transactions root matching its hash type, with the id of the
+/// transaction hashed with the same type.
+pub fn verify_transaction_proof(
+    proof: &TransactionProof,
+    header: &BlockHeader,
+    txn: &SignedTransaction,
+) -> Result<(), Error> {
+    let (root, txid) = match proof.hash_type {
+        HashType::Sha512_256 => (header.transactions_root, txn.transaction.raw_id()?),
+        HashType::Sha256 => (
+            header.transactions_root_sha256,
+            txn.transaction.raw_id_sha256()?,
+        ),
+    };
+    Ok(merkle::verify_transaction_proof(
+        &root,
+        &txid,
+        &proof.stib_hash,
+        proof.index,
+        proof.tree_depth,
+        &proof.proof,
+        proof.hash_type,
+    )?)
+}
+
+/// A potentially truncated list of transactions currently in the node's transaction pool.
+/// You can compute whether or not the list is truncated if the number of elements in the
+/// top-transactions array is fewer than total-transactions.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PendingTransactions {
+    /// The signed transactions, sorted by priority in decreasing order.
+    #[serde(rename = "top-transactions", default)]
+    pub top_transactions: Vec<SignedTransaction>,
+
+    /// Total number of transactions in the pool.
+    #[serde(rename = "total-transactions", default)]
+    pub total_transactions: u64,
+}
+
+/// A transaction in the pool or recently confirmed, with the results of its evaluation.
+///
+/// If the transaction was kicked out of the pool, the reason is in `pool_error`.
+///
+/// The transaction is signed, except in the results of [Algod::simulate_unsigned].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(bound(deserialize = "T: PendingTransactionTxn"))]
+pub struct PendingTransaction<T = SignedTransaction> {
+    /// The application index if the transaction was found and it created an application.
+    #[serde(rename = "application-index")]
+    pub application_index: Option<u64>,
+
+    /// The asset index if the transaction was found and it created an asset.
+    #[serde(rename = "asset-index")]
+    pub asset_index: Option<u64>,
+
+    /// The number of the asset's unit that were transferred to the close-to address.
+    #[serde(rename = "asset-closing-amount")]
+    pub asset_closing_amount: Option<u64>,
+
+    /// Rewards in microalgos applied to the close remainder to account.
+    #[serde(rename = "close-rewards")]
+    pub close_rewards: Option<u64>,
+
+    /// Closing amount for the transaction.
+    #[serde(rename = "closing-amount")]
+    pub closing_amount: Option<u64>,
+
+    /// The round where this transaction was confirmed, if present.
+    #[serde(rename = "confirmed-round")]
+    pub confirmed_round: Option<u64>,
+
+    /// Global state key/value changes for the application being executed by this transaction.
+    #[serde(default, rename = "global-state-delta")]
+    pub global_state_delta: Vec<EvalDeltaKeyValue>,
+
+    /// Local state key/value changes for the application being executed by this transaction.
+    #[serde(default, rename = "local-state-delta")]
+    pub local_state_delta: Vec<AccountStateDelta>,
+
+    /// Inner transactions produced by the application execution.
+    #[serde(default, rename = "inner-txns")]
+    pub inner_txns: Vec<InnerTransaction>,
+
+    /// Logs emitted by the application call, if any.
+    #[serde(default, deserialize_with = "deserialize_logs")]
+    pub logs: Vec<Vec<u8>>,
+
+    /// Indicates that the transaction was kicked out of this node's transaction pool
+    /// (and specifies why that happened). An empty string indicates the transaction
+    /// wasn't kicked out of this node's txpool due to an error.
+    #[serde(default, rename = "pool-error")]
+    pub pool_error: String,
+
+    /// Rewards in microalgos applied to the receiver account.
+    #[serde(rename = "receiver-rewards")]
+    pub receiver_rewards: Option<u64>,
+
+    /// Rewards in microalgos applied to the sender account.
+    #[serde(rename = "sender-rewards")]
+    pub sender_rewards: Option<u64>,
+
+    /// The signed transaction.
+    #[serde(deserialize_with = "deserialize_pending_txn")]
+    pub txn: T,
+}
+
+/// The transaction of a [PendingTransaction]: a [SignedTransaction], or a [Transaction] for the
+/// results of unsigned simulations.
+pub trait PendingTransactionTxn: Sized + private::Sealed {
+    #[doc(hidden)]
+    fn deserialize_txn<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+}
+
+impl PendingTransactionTxn for SignedTransaction {
+    fn deserialize_txn<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        SignedTransaction::deserialize(deserializer)
+    }
+}
+
+impl PendingTransactionTxn for Transaction {
+    fn deserialize_txn<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_unsigned_transaction(deserializer)
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for algonaut_transaction::SignedTransaction {}
+    impl Sealed for algonaut_transaction::Transaction {}
+}
+
+fn deserialize_pending_txn<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: PendingTransactionTxn,
+{
+    T::deserialize_txn(deserializer)
+}
+
+/// A transaction issued by an application call, with the results of its evaluation.
+///
+/// Inner transactions aren't signed and don't include the genesis hash.
+#[derive(Debug, Clone, Deserialize)]
+pub struct InnerTransaction {
+    /// The application index if the transaction created an application.
+    #[serde(rename = "application-index")]
+    pub application_index: Option<u64>,
+
+    /// The asset index if the transaction created an asset.
+    #[serde(rename = "asset-index")]
+    pub asset_index: Option<u64>,
+
+    /// The number of the asset's unit that were transferred to the close-to address.
+    #[serde(rename = "asset-closing-amount")]
+    pub asset_closing_amount: Option<u64>,
+
+    /// Closing amount for the transaction.
+    #[serde(rename = "closing-amount")]
+    pub closing_amount: Option<u64>,
+
+    /// Global state key/value changes for the application being executed by this transaction.
+    #[serde(default, rename = "global-state-delta")]
+    pub global_state_delta: Vec<EvalDeltaKeyValue>,
+
+    /// Local state key/value changes for the application being executed by this transaction.
+    #[serde(default, rename = "local-state-delta")]
+    pub local_state_delta: Vec<AccountStateDelta>,
+
+    /// Inner transactions produced by the application execution.
+    #[serde(default, rename = "inner-txns")]
+    pub inner_txns: Vec<InnerTransaction>,
+
+    /// Logs emitted by the application call, if any.
+    #[serde(default, deserialize_with = "deserialize_logs")]
+    pub logs: Vec<Vec<u8>>,
+
+    /// The transaction.
+    #[serde(deserialize_with = "deserialize_unsigned_transaction")]
+    pub txn: Transaction,
+}
+
+/// The encoded key of the block in the block endpoint response.
+const BLOCK_KEY: &[u8] = &[0xa5, b'b', b'l', b'o', b'c', b'k'];
+
+/// A transaction encoded like a signed transaction without signature.
+#[derive(Serialize, Deserialize)]
+struct UnsignedTransaction<T> {
+    txn: T,
+}
+
+fn deserialize_unsigned_transaction<'de, D>(deserializer: D) -> Result<Transaction, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(UnsignedTransaction::<Transaction>::deserialize(deserializer)?.txn)
+}
+
+/// Logs are encoded as msgpack binaries.
+fn deserialize_logs<'de, D>(deserializer: D) -> Result<Vec<Vec<u8>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let logs = Vec::<ByteBuf>::deserialize(deserializer)?;
+    Ok(logs.into_iter().map(ByteBuf::into_vec).collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use algonaut_client::error::ClientError;
+    use algonaut_client::transport::{HttpRequest, HttpResponse, HttpTransport};
+    use algonaut_core::MicroAlgos;
+    use algonaut_crypto::HashDigest;
+    use algonaut_transaction::{account::Account, builder::TxnFee, Pay, TxnBuilder};
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+
+    /// A payment of a new account to itself, signed.
+    fn signed_payment() -> SignedTransaction {
+        let account = Account::generate();
+        let txn = TxnBuilder::new(
+            TxnFee::Fixed(MicroAlgos(1000)),
+            Round(1),
+            Round(1001),
+            HashDigest([1; 32]),
+            Pay::new(account.address(), account.address(), MicroAlgos(1)).build(),
+        )
+        .build()
+        .unwrap();
+        account.sign_transaction(txn).unwrap()
+    }
 
     #[test]
     fn test_valid_client_builder() {
@@ -302,13 +738,373 @@ mod tests {
             "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
         );
         assert!(res.is_err());
-        assert!(res.err().unwrap() == ServiceError::BadToken);
+        assert!(matches!(res.err().unwrap(), ServiceError::BadToken));
+    }
+
+    #[test]
+    fn test_simulate_request_encoding() {
+        #[derive(Deserialize)]
+        struct DecodedRequest {
+            #[serde(rename = "txn-groups")]
+            txn_groups: Vec<DecodedGroup>,
+            #[serde(rename = "extra-opcode-budget")]
+            extra_opcode_budget: u64,
+            #[serde(rename = "allow-more-logging")]
+            allow_more_logging: Option<bool>,
+        }
+        #[derive(Deserialize)]
+        struct DecodedGroup {
+            txns: Vec<SignedTransaction>,
+        }
+
+        let signed = signed_payment();
+
+        let request = SimulateRequest {
+            txn_groups: vec![SimulateRequestTransactionGroup {
+                txns: vec![&signed],
+            }],
+            options: SimulateOptions {
+                extra_opcode_budget: Some(700),
+                ..SimulateOptions::default()
+            },
+        };
+        let decoded: DecodedRequest =
+            rmp_serde::from_slice(&rmp_serde::to_vec_named(&request).unwrap()).unwrap();
+        assert_eq!(vec![signed], decoded.txn_groups[0].txns);
+        assert_eq!(700, decoded.extra_opcode_budget);
+        assert_eq!(None, decoded.allow_more_logging);
     }
 
     #[test]
     fn test_client_builder_with_empty_token() {
         let res = Algod::new("http://example.com", "");
         assert!(res.is_err());
-        assert!(res.err().unwrap() == ServiceError::BadToken);
+        assert!(matches!(res.err().unwrap(), ServiceError::BadToken));
+    }
+
+    #[test]
+    fn test_decode_pending_transactions() {
+        #[derive(Serialize)]
+        struct EncodedPendingTransactions<'a> {
+            #[serde(rename = "top-transactions")]
+            top_transactions: Vec<&'a SignedTransaction>,
+            #[serde(rename = "total-transactions")]
+            total_transactions: u64,
+        }
+
+        let signed = signed_payment();
+
+        let bytes = rmp_serde::to_vec_named(&EncodedPendingTransactions {
+            top_transactions: vec![&signed],
+            total_transactions: 3,
+        })
+        .unwrap();
+        let pending: PendingTransactions = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(vec![signed], pending.top_transactions);
+        assert_eq!(3, pending.total_transactions);
+
+        // the node omits empty fields
+        let empty: PendingTransactions = rmp_serde::from_slice(
+            &rmp_serde::to_vec_named(&std::collections::HashMap::<String, u64>::new()).unwrap(),
+        )
+        .unwrap();
+        assert!(empty.top_transactions.is_empty());
+        assert_eq!(0, empty.total_transactions);
+    }
+
+    #[test]
+    fn test_decode_simulate_response() {
+        #[derive(Serialize)]
+        struct EncodedTxnResult<T> {
+            logs: Vec<ByteBuf>,
+            txn: T,
+        }
+        #[derive(Serialize)]
+        struct EncodedResult<T> {
+            #[serde(rename = "txn-result")]
+            txn_result: EncodedTxnResult<T>,
+            #[serde(rename = "app-budget-consumed")]
+            app_budget_consumed: u64,
+        }
+        #[derive(Serialize)]
+        struct EncodedGroup<T> {
+            #[serde(rename = "txn-results")]
+            txn_results: Vec<EncodedResult<T>>,
+            #[serde(rename = "failure-message")]
+            failure_message: &'static str,
+        }
+        #[derive(Serialize)]
+        struct EncodedResponse<T> {
+            version: u64,
+            #[serde(rename = "last-round")]
+            last_round: u64,
+            #[serde(rename = "txn-groups")]
+            txn_groups: Vec<EncodedGroup<T>>,
+        }
+        fn encode<T: Serialize>(txn: T) -> Vec<u8> {
+            rmp_serde::to_vec_named(&EncodedResponse {
+                version: 2,
+                last_round: 20,
+                txn_groups: vec![EncodedGroup {
+                    txn_results: vec![EncodedResult {
+                        txn_result: EncodedTxnResult {
+                            logs: vec![ByteBuf::from(vec![1, 2])],
+                            txn,
+                        },
+                        app_budget_consumed: 7,
+                    }],
+                    failure_message: "logic eval error",
+                }],
+            })
+            .unwrap()
+        }
+
+        let signed = signed_payment();
+        let txn = signed.transaction.clone();
+
+        let response: SimulateResponse<PendingTransaction> =
+            rmp_serde::from_slice(&encode(&signed)).unwrap();
+        assert_eq!(Round(20), response.last_round);
+        let group = &response.txn_groups[0];
+        assert_eq!(Some("logic eval error"), group.failure_message.as_deref());
+        assert_eq!(Some(7), group.txn_results[0].app_budget_consumed);
+        let result = &group.txn_results[0].txn_result;
+        assert_eq!(signed, result.txn);
+        assert_eq!(vec![vec![1, 2]], result.logs);
+
+        // the transactions of unsigned simulations have no signature
+        let response: SimulateResponse<PendingTransaction<Transaction>> =
+            rmp_serde::from_slice(&encode(UnsignedTransaction { txn: &txn })).unwrap();
+        assert_eq!(txn, response.txn_groups[0].txn_results[0].txn_result.txn);
+    }
+
+    #[test]
+    fn test_decode_pending_transaction() {
+        use algonaut_model::algod::v2::EvalDelta;
+
+        #[derive(Serialize)]
+        struct EncodedEvalDelta {
+            action: u64,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            bytes: Option<&'static str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            uint: Option<u64>,
+        }
+        #[derive(Serialize)]
+        struct EncodedKeyValue {
+            key: &'static str,
+            value: EncodedEvalDelta,
+        }
+        #[derive(Serialize)]
+        struct EncodedInnerTransaction<'a> {
+            logs: Vec<ByteBuf>,
+            txn: UnsignedTransaction<&'a Transaction>,
+        }
+        #[derive(Serialize)]
+        struct EncodedPendingTransaction<'a> {
+            #[serde(rename = "confirmed-round")]
+            confirmed_round: u64,
+            #[serde(rename = "global-state-delta")]
+            global_state_delta: Vec<EncodedKeyValue>,
+            #[serde(rename = "inner-txns")]
+            inner_txns: Vec<EncodedInnerTransaction<'a>>,
+            logs: Vec<ByteBuf>,
+            #[serde(rename = "pool-error")]
+            pool_error: &'static str,
+            txn: &'a SignedTransaction,
+        }
+
+        let signed = signed_payment();
+        let txn = signed.transaction.clone();
+
+        let bytes = rmp_serde::to_vec_named(&EncodedPendingTransaction {
+            confirmed_round: 10,
+            global_state_delta: vec![
+                EncodedKeyValue {
+                    key: "Y291bnQ=",
+                    value: EncodedEvalDelta {
+                        action: 2,
+                        bytes: None,
+                        uint: Some(5),
+                    },
+                },
+                EncodedKeyValue {
+                    key: "bmFtZQ==",
+                    value: EncodedEvalDelta {
+                        action: 1,
+                        bytes: Some("YWxnbw=="),
+                        uint: None,
+                    },
+                },
+            ],
+            inner_txns: vec![EncodedInnerTransaction {
+                logs: vec![ByteBuf::from(b"inner".to_vec())],
+                txn: UnsignedTransaction { txn: &txn },
+            }],
+            logs: vec![ByteBuf::from(b"outer".to_vec())],
+            pool_error: "",
+            txn: &signed,
+        })
+        .unwrap();
+        let pending: PendingTransaction = rmp_serde::from_slice(&bytes).unwrap();
+
+        assert_eq!(Some(10), pending.confirmed_round);
+        assert_eq!(signed, pending.txn);
+        assert_eq!(vec![b"outer".to_vec()], pending.logs);
+        assert_eq!(b"count".to_vec(), pending.global_state_delta[0].key);
+        assert_eq!(EvalDelta::SetUint(5), pending.global_state_delta[0].value);
+        assert_eq!(
+            EvalDelta::SetBytes(b"algo".to_vec()),
+            pending.global_state_delta[1].value
+        );
+        assert_eq!(1, pending.inner_txns.len());
+        assert_eq!(txn, pending.inner_txns[0].txn);
+        assert_eq!(vec![b"inner".to_vec()], pending.inner_txns[0].logs);
+        assert!(pending.inner_txns[0].inner_txns.is_empty());
+    }
+
+    /// A node at round 100, confirming the transaction in round 103.
+    #[derive(Debug)]
+    struct Node {
+        signed: SignedTransaction,
+        requests: Mutex<Vec<String>>,
+        last_round: Mutex<u64>,
+    }
+
+    #[derive(Serialize)]
+    struct EncodedPendingTransaction<'a> {
+        #[serde(rename = "confirmed-round", skip_serializing_if = "Option::is_none")]
+        confirmed_round: Option<u64>,
+        #[serde(rename = "pool-error")]
+        pool_error: &'static str,
+        txn: &'a SignedTransaction,
+    }
+
+    #[async_trait]
+    impl HttpTransport for Node {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse, ClientError> {
+            let path = request.url["http://localhost:4001/".len()..].to_owned();
+            self.requests.lock().unwrap().push(path.clone());
+            let mut last_round = self.last_round.lock().unwrap();
+            let body = if path.starts_with("v2/transactions/pending/") {
+                rmp_serde::to_vec_named(&EncodedPendingTransaction {
+                    confirmed_round: Some(*last_round).filter(|r| *r >= 103),
+                    pool_error: "",
+                    txn: &self.signed,
+                })
+                .unwrap()
+            } else {
+                if let Some(round) = path.strip_prefix("v2/status/wait-for-block-after/") {
+                    *last_round = round.parse::<u64>().unwrap() + 1;
+                }
+                format!(
+                    r#"{{"catchup-time":0,"last-round":{},"last-version":"future",
+                    "next-version":"future","next-version-round":{},"next-version-supported":true,
+                    "stopped-at-unsupported-round":false,"time-since-last-round":0}}"#,
+                    *last_round,
+                    *last_round + 1
+                )
+                .into_bytes()
+            };
+            Ok(HttpResponse {
+                status: 200,
+                headers: vec![],
+                body,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_confirmation_in_last_round() {
+        let signed = signed_payment();
+        let txid = signed.transaction_id.clone();
+        let node = Arc::new(Node {
+            signed: signed.clone(),
+            requests: Mutex::new(vec![]),
+            last_round: Mutex::new(100),
+        });
+        let algod = Algod::with_transport("http://localhost:4001", vec![], node.clone()).unwrap();
+
+        let pending = algod.wait_for_confirmation(&txid, 2).await.unwrap();
+        assert_eq!(Some(103), pending.confirmed_round);
+        assert_eq!(signed, pending.txn);
+
+        let pending_path = format!("v2/transactions/pending/{}?format=msgpack", txid);
+        assert_eq!(
+            vec![
+                "v2/status",
+                &pending_path,
+                "v2/status/wait-for-block-after/101",
+                &pending_path,
+                "v2/status/wait-for-block-after/102",
+                &pending_path,
+            ],
+            *node.requests.lock().unwrap()
+        );
+
+        // not confirmed if the node is already past the rounds to wait for
+        *node.last_round.lock().unwrap() = 90;
+        assert!(matches!(
+            algod.wait_for_confirmation(&txid, 1).await,
+            Err(ServiceError::ConfirmationTimeout { rounds: 1, .. })
+        ));
+    }
+
+    #[derive(Serialize)]
+    struct EncodedHeader {
+        rnd: u64,
+        txn: ByteBuf,
+        txn256: ByteBuf,
+    }
+
+    #[test]
+    fn test_verify_transaction_proof() {
+        let signed = signed_payment();
+        let stib_hash = HashDigest([9; 32]);
+        // the second of three transactions: the leaves are ordered by the bit reversed index
+        let tree = |hash_type: HashType, txid: HashDigest| {
+            let leaf = |txid: &HashDigest, stib_hash: &HashDigest| {
+                hash_type.hash(b"TL", &[&txid.0, &stib_hash.0])
+            };
+            let first = leaf(&HashDigest([1; 32]), &HashDigest([2; 32]));
+            let third = leaf(&HashDigest([3; 32]), &HashDigest([4; 32]));
+            let empty = hash_type.hash(b"", &[]);
+            let left = hash_type.hash(b"MA", &[&first.0, &third.0]);
+            let right = hash_type.hash(b"MA", &[&leaf(&txid, &stib_hash).0, &empty.0]);
+            let proof = TransactionProof {
+                index: 1,
+                proof: [empty.0, left.0].concat(),
+                stib_hash,
+                tree_depth: 2,
+                hash_type,
+            };
+            (hash_type.hash(b"MA", &[&left.0, &right.0]), proof)
+        };
+        let (root, proof) = tree(HashType::Sha512_256, signed.transaction.raw_id().unwrap());
+        let (root_sha256, proof_sha256) = tree(
+            HashType::Sha256,
+            signed.transaction.raw_id_sha256().unwrap(),
+        );
+        let header = Block::from_msg_pack(
+            &rmp_serde::to_vec_named(&EncodedHeader {
+                rnd: 5,
+                txn: ByteBuf::from(root.0.to_vec()),
+                txn256: ByteBuf::from(root_sha256.0.to_vec()),
+            })
+            .unwrap(),
+        )
+        .unwrap()
+        .header;
+
+        assert!(verify_transaction_proof(&proof, &header, &signed).is_ok());
+        assert!(verify_transaction_proof(&proof_sha256, &header, &signed).is_ok());
+
+        // the proofs don't verify with the other hash type or another transaction
+        let mismatched = TransactionProof {
+            hash_type: HashType::Sha256,
+            ..proof.clone()
+        };
+        assert!(verify_transaction_proof(&mismatched, &header, &signed).is_err());
+        assert!(verify_transaction_proof(&proof_sha256, &header, &signed_payment()).is_err());
     }
 }
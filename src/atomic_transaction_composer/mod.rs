@@ -0,0 +1,576 @@
+//! Build, sign and submit transaction groups, including ABI method calls.
+use std::sync::Arc;
+
+use algonaut_core::{Address, CompiledTeal, SuggestedTransactionParams};
+use algonaut_crypto::HashDigest;
+use algonaut_transaction::{
+    abi::{AbiArgType, AbiArgValue, AbiReturnType, AbiValue, AppCallReferences, Method},
+    builder::TxnFee,
+    error::TransactionError,
+    signer::TransactionSigner,
+    transaction::{
+        ApplicationCallOnComplete, ApplicationCallTransaction, BoxReference, StateSchema,
+    },
+    tx_group::TxGroup,
+    SignedTransaction, Transaction, TransactionType, TxnBuilder,
+};
+use futures::future::join_all;
+use thiserror::Error;
+
+use crate::{
+    algod::v2::{Algod, PendingTransaction},
+    error::ServiceError,
+};
+
+/// Prefix of the log line containing the return value of an ABI method call.
+const ABI_RETURN_PREFIX: [u8; 4] = [0x15, 0x1f, 0x7c, 0x75];
+
+#[derive(Debug, Error)]
+pub enum AtomicTransactionComposerError {
+    #[error("Transaction error: {0}")]
+    Transaction(#[from] TransactionError),
+    #[error("Service error: {0}")]
+    Service(#[from] ServiceError),
+    #[error("Invalid composer status: {status:?}, expected: {expected:?}.")]
+    InvalidStatus {
+        status: AtomicTransactionComposerStatus,
+        expected: AtomicTransactionComposerStatus,
+    },
+    #[error("The transaction already has a group id.")]
+    GroupIdAlreadySet,
+    #[error("Invalid method call: {0}")]
+    InvalidMethodCall(String),
+}
+
+impl AtomicTransactionComposerError {
+    /// A stable, snake case code identifying the kind of error, e.g. for logs and metrics.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Transaction(e) => e.code(),
+            Self::Service(e) => e.code(),
+            Self::InvalidStatus { .. } => "invalid_composer_status",
+            Self::GroupIdAlreadySet => "group_id_already_set",
+            Self::InvalidMethodCall(_) => "invalid_method_call",
+        }
+    }
+}
+
+/// The status of an [AtomicTransactionComposer]. A composer only moves forward through these states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AtomicTransactionComposerStatus {
+    /// Transactions can be added.
+    Building,
+    /// The group id has been assigned, transactions can't be added anymore.
+    Built,
+    /// All transactions have been signed.
+    Signed,
+    /// The group has been sent to the network.
+    Submitted,
+    /// The group has been confirmed.
+    Committed,
+}
+
+/// A transaction and the signer that will sign it.
+#[derive(Clone)]
+pub struct TransactionWithSigner {
+    pub txn: Transaction,
+    pub signer: Arc<dyn TransactionSigner>,
+}
+
+impl TransactionWithSigner {
+    pub fn new(txn: Transaction, signer: Arc<dyn TransactionSigner>) -> Self {
+        TransactionWithSigner { txn, signer }
+    }
+}
+
+/// Parameters of an ABI method call.
+#[derive(Clone)]
+pub struct AddMethodCallParams {
+    pub app_id: u64,
+    pub method: Method,
+    /// Values of the non-transaction arguments, in order.
+    pub method_args: Vec<AbiArgValue>,
+    /// Transactions passed as arguments, in order. They're added to the group before the method call.
+    pub txn_args: Vec<TransactionWithSigner>,
+    pub fee: TxnFee,
+    pub sender: Address,
+    pub suggested_params: SuggestedTransactionParams,
+    pub on_complete: ApplicationCallOnComplete,
+    pub approval_program: Option<CompiledTeal>,
+    pub clear_program: Option<CompiledTeal>,
+    pub global_schema: Option<StateSchema>,
+    pub local_schema: Option<StateSchema>,
+    pub extra_pages: u64,
+    /// Boxes the method can access. Their apps are added to the foreign apps if needed.
+    pub boxes: Vec<BoxReference>,
+    pub note: Option<Vec<u8>>,
+    pub lease: Option<HashDigest>,
+    pub rekey_to: Option<Address>,
+    pub signer: Arc<dyn TransactionSigner>,
+}
+
+impl AddMethodCallParams {
+    /// Convenience for a NoOp call to an existing application, with the fee estimated from the suggested params.
+    pub fn new(
+        app_id: u64,
+        method: Method,
+        method_args: Vec<AbiArgValue>,
+        sender: Address,
+        suggested_params: SuggestedTransactionParams,
+        signer: Arc<dyn TransactionSigner>,
+    ) -> Self {
+        AddMethodCallParams {
+            app_id,
+            method,
+            method_args,
+            txn_args: vec![],
+            fee: TxnFee::Estimated {
+                fee_per_byte: suggested_params.fee_per_byte,
+                min_fee: suggested_params.min_fee,
+            },
+            sender,
+            suggested_params,
+            on_complete: ApplicationCallOnComplete::NoOp,
+            approval_program: None,
+            clear_program: None,
+            global_schema: None,
+            local_schema: None,
+            extra_pages: 0,
+            boxes: vec![],
+            note: None,
+            lease: None,
+            rekey_to: None,
+            signer,
+        }
+    }
+}
+
+/// The return value of an ABI method call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbiMethodReturnValue {
+    Some(AbiValue),
+    Void,
+}
+
+/// The result of an ABI method call in an executed group.
+#[derive(Debug, Clone)]
+pub struct AbiMethodResult {
+    pub tx_id: String,
+    pub tx_info: PendingTransaction,
+    /// The decoded return value, or the reason why it couldn't be decoded.
+    pub return_value: Result<AbiMethodReturnValue, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExecuteResult {
+    pub confirmed_round: Option<u64>,
+    pub tx_ids: Vec<String>,
+    pub method_results: Vec<AbiMethodResult>,
+}
+
+/// Builds a transaction group from transactions and ABI method calls, signs it and submits it to the network.
+pub struct AtomicTransactionComposer {
+    status: AtomicTransactionComposerStatus,
+    txns: Vec<TransactionWithSigner>,
+    /// Methods of the method calls, by transaction index.
+    method_map: Vec<(usize, Method)>,
+    signed_txns: Vec<SignedTransaction>,
+}
+
+impl Default for AtomicTransactionComposer {
+    fn default() -> Self {
+        AtomicTransactionComposer {
+            status: AtomicTransactionComposerStatus::Building,
+            txns: vec![],
+            method_map: vec![],
+            signed_txns: vec![],
+        }
+    }
+}
+
+impl AtomicTransactionComposer {
+    pub fn status(&self) -> AtomicTransactionComposerStatus {
+        self.status
+    }
+
+    /// Number of transactions in the group.
+    pub fn count(&self) -> usize {
+        self.txns.len()
+    }
+
+    /// Adds a transaction to the group. The transaction must not have a group id.
+    pub fn add_transaction(
+        &mut self,
+        txn: TransactionWithSigner,
+    ) -> Result<(), AtomicTransactionComposerError> {
+        self.validate_can_add(1)?;
+        if txn.txn.group.is_some() {
+            return Err(AtomicTransactionComposerError::GroupIdAlreadySet);
+        }
+        self.txns.push(txn);
+        Ok(())
+    }
+
+    /// Adds an ABI method call to the group, preceded by its transaction arguments.
+    pub fn add_method_call(
+        &mut self,
+        params: AddMethodCallParams,
+    ) -> Result<(), AtomicTransactionComposerError> {
+        self.validate_can_add(params.txn_args.len() + 1)?;
+
+        let txn_arg_types: Vec<_> = params
+            .method
+            .args
+            .iter()
+            .filter_map(|a| match &a.arg_type {
+                AbiArgType::Tx(t) => Some(*t),
+                _ => None,
+            })
+            .collect();
+        if txn_arg_types.len() != params.txn_args.len() {
+            return Err(AtomicTransactionComposerError::InvalidMethodCall(format!(
+                "method {} expects {} transaction arguments, got {}",
+                params.method.signature(),
+                txn_arg_types.len(),
+                params.txn_args.len()
+            )));
+        }
+        for (arg_type, txn_arg) in txn_arg_types.iter().zip(params.txn_args.iter()) {
+            if !arg_type.accepts(&txn_arg.txn.txn_type) {
+                return Err(AtomicTransactionComposerError::InvalidMethodCall(format!(
+                    "expected transaction argument of type {}",
+                    arg_type
+                )));
+            }
+            if txn_arg.txn.group.is_some() {
+                return Err(AtomicTransactionComposerError::GroupIdAlreadySet);
+            }
+        }
+
+        let mut references = AppCallReferences::default();
+        let app_arguments = params.method.encode_app_arguments(
+            &params.method_args,
+            &params.sender,
+            params.app_id,
+            &mut references,
+        )?;
+
+        let app_call = TransactionType::ApplicationCallTransaction(
+            ApplicationCallTransaction {
+                sender: params.sender,
+                app_id: Some(params.app_id).filter(|id| *id != 0),
+                on_complete: params.on_complete,
+                accounts: Some(references.accounts).filter(|v| !v.is_empty()),
+                approval_program: params.approval_program,
+                app_arguments: Some(app_arguments),
+                clear_state_program: params.clear_program,
+                foreign_apps: Some(references.foreign_apps).filter(|v| !v.is_empty()),
+                foreign_assets: Some(references.foreign_assets).filter(|v| !v.is_empty()),
+                boxes: None,
+                global_state_schema: params.global_schema,
+                local_state_schema: params.local_schema,
+                extra_pages: params.extra_pages,
+            }
+            .with_boxes(params.boxes),
+        );
+        let mut builder = TxnBuilder::with_fee(&params.suggested_params, params.fee, app_call);
+        if let Some(note) = params.note {
+            builder = builder.note(note);
+        }
+        if let Some(lease) = params.lease {
+            builder = builder.lease(lease);
+        }
+        if let Some(rekey_to) = params.rekey_to {
+            builder = builder.rekey_to(rekey_to);
+        }
+        let txn = builder.build()?;
+
+        self.txns.extend(params.txn_args);
+        self.method_map.push((self.txns.len(), params.method));
+        self.txns
+            .push(TransactionWithSigner::new(txn, params.signer.clone()));
+        Ok(())
+    }
+
+    /// Finalizes the group, assigning the group id if there's more than one transaction.
+    pub fn build_group(
+        &mut self,
+    ) -> Result<Vec<TransactionWithSigner>, AtomicTransactionComposerError> {
+        if self.status >= AtomicTransactionComposerStatus::Built {
+            return Ok(self.txns.clone());
+        }
+        if self.txns.is_empty() {
+            return Err(TransactionError::EmptyTransactionListError.into());
+        }
+        if self.txns.len() > 1 {
+            let mut txns: Vec<&mut Transaction> =
+                self.txns.iter_mut().map(|t| &mut t.txn).collect();
+            TxGroup::assign_group_id(&mut txns)?;
+        }
+        self.status = AtomicTransactionComposerStatus::Built;
+        Ok(self.txns.clone())
+    }
+
+    /// Builds the group if needed and signs it, calling each distinct signer once, in parallel.
+    pub async fn gather_signatures(
+        &mut self,
+    ) -> Result<Vec<SignedTransaction>, AtomicTransactionComposerError> {
+        if self.status >= AtomicTransactionComposerStatus::Signed {
+            return Ok(self.signed_txns.clone());
+        }
+        let txns_with_signers = self.build_group()?;
+        let txns: Vec<Transaction> = txns_with_signers.iter().map(|t| t.txn.clone()).collect();
+
+        let mut signers: Vec<(Arc<dyn TransactionSigner>, Vec<usize>)> = vec![];
+        for (i, t) in txns_with_signers.iter().enumerate() {
+            match signers.iter_mut().find(|(s, _)| Arc::ptr_eq(s, &t.signer)) {
+                Some((_, indexes)) => indexes.push(i),
+                None => signers.push((t.signer.clone(), vec![i])),
+            }
+        }
+
+        let results = join_all(
+            signers
+                .iter()
+                .map(|(signer, indexes)| signer.sign_transactions(&txns, indexes)),
+        )
+        .await;
+
+        let mut signed_txns: Vec<Option<SignedTransaction>> = vec![None; txns.len()];
+        for ((_, indexes), result) in signers.iter().zip(results) {
+            for (i, signed) in indexes.iter().zip(result?) {
+                signed_txns[*i] = Some(signed);
+            }
+        }
+        let signed_txns = signed_txns
+            .into_iter()
+            .enumerate()
+            .map(|(i, s)| s.ok_or(TransactionError::TransactionIndexOutOfBounds { index: i }))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.signed_txns = signed_txns.clone();
+        self.status = AtomicTransactionComposerStatus::Signed;
+        Ok(signed_txns)
+    }
+
+    /// Signs the group if needed and submits it to the network, returning the transaction ids.
+    ///
+    /// The group can only be submitted once.
+    pub async fn submit(
+        &mut self,
+        algod: &Algod,
+    ) -> Result<Vec<String>, AtomicTransactionComposerError> {
+        if self.status > AtomicTransactionComposerStatus::Signed {
+            return Err(AtomicTransactionComposerError::InvalidStatus {
+                status: self.status,
+                expected: AtomicTransactionComposerStatus::Signed,
+            });
+        }
+        let signed_txns = self.gather_signatures().await?;
+        algod.broadcast_signed_transactions(&signed_txns).await?;
+        self.status = AtomicTransactionComposerStatus::Submitted;
+        Ok(self.tx_ids())
+    }
+
+    /// Submits the group and waits up to `wait_rounds` rounds for it to be confirmed.
+    ///
+    /// Returns the transaction ids and the return values of the ABI method calls.
+    pub async fn execute(
+        &mut self,
+        algod: &Algod,
+        wait_rounds: u64,
+    ) -> Result<ExecuteResult, AtomicTransactionComposerError> {
+        if self.status == AtomicTransactionComposerStatus::Committed {
+            return Err(AtomicTransactionComposerError::InvalidStatus {
+                status: self.status,
+                expected: AtomicTransactionComposerStatus::Submitted,
+            });
+        }
+        if self.status < AtomicTransactionComposerStatus::Submitted {
+            self.submit(algod).await?;
+        }
+
+        let tx_ids = self.tx_ids();
+        let confirmed = algod.wait_for_confirmation(&tx_ids[0], wait_rounds).await?;
+        self.status = AtomicTransactionComposerStatus::Committed;
+
+        let mut method_results = vec![];
+        for (index, method) in &self.method_map {
+            let tx_id = tx_ids[*index].clone();
+            let tx_info = if *index == 0 {
+                confirmed.clone()
+            } else {
+                algod.pending_transaction_with_id(&tx_id).await?
+            };
+            let return_value = decode_return_value(method, &tx_info);
+            method_results.push(AbiMethodResult {
+                tx_id,
+                tx_info,
+                return_value,
+            });
+        }
+
+        Ok(ExecuteResult {
+            confirmed_round: confirmed.confirmed_round,
+            tx_ids,
+            method_results,
+        })
+    }
+
+    fn tx_ids(&self) -> Vec<String> {
+        self.signed_txns
+            .iter()
+            .map(|t| t.transaction_id.clone())
+            .collect()
+    }
+
+    fn validate_can_add(&self, count: usize) -> Result<(), AtomicTransactionComposerError> {
+        if self.status != AtomicTransactionComposerStatus::Building {
+            return Err(AtomicTransactionComposerError::InvalidStatus {
+                status: self.status,
+                expected: AtomicTransactionComposerStatus::Building,
+            });
+        }
+        if self.txns.len() + count > TxGroup::MAX_TX_GROUP_SIZE {
+            return Err(TransactionError::MaxTransactionGroupSizeError {
+                size: TxGroup::MAX_TX_GROUP_SIZE,
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Decodes the return value of a method call from the last log of the transaction.
+fn decode_return_value(
+    method: &Method,
+    tx_info: &PendingTransaction,
+) -> Result<AbiMethodReturnValue, String> {
+    let return_type = match &method.returns.return_type {
+        AbiReturnType::Void => return Ok(AbiMethodReturnValue::Void),
+        AbiReturnType::Some(t) => t,
+    };
+    let last_log = tx_info
+        .logs
+        .last()
+        .ok_or_else(|| "app call transaction did not log a return value".to_owned())?;
+    if !last_log.starts_with(&ABI_RETURN_PREFIX) {
+        return Err("app call transaction did not log a return value".to_owned());
+    }
+    AbiValue::decode(return_type, &last_log[ABI_RETURN_PREFIX.len()..])
+        .map(AbiMethodReturnValue::Some)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algonaut_core::{MicroAlgos, Round};
+    use algonaut_transaction::{account::Account, Pay};
+
+    fn params() -> SuggestedTransactionParams {
+        SuggestedTransactionParams {
+            genesis_id: "testnet-v1.0".to_owned(),
+            genesis_hash: HashDigest([1; 32]),
+            consensus_version: "".to_owned(),
+            fee_per_byte: MicroAlgos(0),
+            min_fee: MicroAlgos(1000),
+            first_valid: Round(1),
+            last_valid: Round(1001),
+        }
+    }
+
+    fn payment(account: &Account) -> Transaction {
+        TxnBuilder::with(
+            &params(),
+            Pay::new(account.address(), account.address(), MicroAlgos(1)).build(),
+        )
+        .build()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_max_group_size() {
+        let account = Arc::new(Account::generate());
+        let mut atc = AtomicTransactionComposer::default();
+        for _ in 0..TxGroup::MAX_TX_GROUP_SIZE {
+            atc.add_transaction(TransactionWithSigner::new(
+                payment(&account),
+                account.clone(),
+            ))
+            .unwrap();
+        }
+        let res = atc.add_transaction(TransactionWithSigner::new(
+            payment(&account),
+            account.clone(),
+        ));
+        assert!(matches!(
+            res,
+            Err(AtomicTransactionComposerError::Transaction(
+                TransactionError::MaxTransactionGroupSizeError { .. }
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_build_and_sign_group_with_method_call() {
+        let account = Arc::new(Account::generate());
+        let other = Arc::new(Account::generate());
+        let mut atc = AtomicTransactionComposer::default();
+        atc.add_transaction(TransactionWithSigner::new(payment(&other), other.clone()))
+            .unwrap();
+
+        let method = Method::from_signature("deposit(pay,uint64)string").unwrap();
+        let mut params = AddMethodCallParams::new(
+            123,
+            method,
+            vec![AbiValue::from(5u64).into()],
+            account.address(),
+            params(),
+            account.clone(),
+        );
+        params.txn_args = vec![TransactionWithSigner::new(
+            payment(&account),
+            account.clone(),
+        )];
+        atc.add_method_call(params).unwrap();
+        assert_eq!(3, atc.count());
+
+        let signed = atc.gather_signatures().await.unwrap();
+        assert_eq!(AtomicTransactionComposerStatus::Signed, atc.status());
+        assert_eq!(3, signed.len());
+        let group = signed[0].transaction.group;
+        assert!(group.is_some());
+        assert!(signed.iter().all(|s| s.transaction.group == group));
+        assert_eq!(other.address(), signed[0].transaction.sender());
+        assert_eq!(account.address(), signed[2].transaction.sender());
+
+        assert!(matches!(
+            atc.add_transaction(TransactionWithSigner::new(payment(&other), other.clone())),
+            Err(AtomicTransactionComposerError::InvalidStatus { .. })
+        ));
+    }
+
+    #[test]
+    fn test_method_call_with_wrong_transaction_argument() {
+        let account = Arc::new(Account::generate());
+        let mut atc = AtomicTransactionComposer::default();
+        let mut params = AddMethodCallParams::new(
+            123,
+            Method::from_signature("optIn(axfer)void").unwrap(),
+            vec![],
+            account.address(),
+            params(),
+            account.clone(),
+        );
+        params.txn_args = vec![TransactionWithSigner::new(
+            payment(&account),
+            account.clone(),
+        )];
+        assert!(matches!(
+            atc.add_method_call(params),
+            Err(AtomicTransactionComposerError::InvalidMethodCall(_))
+        ));
+        assert_eq!(0, atc.count());
+    }
+}
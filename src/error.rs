@@ -1,7 +1,69 @@
+use crate::algod::v2::TransactionRejection;
+use crate::atomic_transaction_composer::AtomicTransactionComposerError;
+use algonaut_client::error::ClientError;
+use algonaut_client::retry::RetryPolicy;
+use algonaut_core::error::CoreError;
+use algonaut_crypto::error::CryptoError;
+use algonaut_transaction::error::TransactionError;
 use std::fmt::Debug;
 use thiserror::Error;
 
-#[derive(Error, Debug, PartialEq, Eq)]
+/// Any error of algonaut, with the error of the crate it originates from as source.
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Service error: {0}")]
+    Service(#[from] ServiceError),
+    #[error("Transaction error: {0}")]
+    Transaction(#[from] TransactionError),
+    #[error("Core error: {0}")]
+    Core(#[from] CoreError),
+    #[error("Crypto error: {0}")]
+    Crypto(#[from] CryptoError),
+    #[error("Atomic transaction composer error: {0}")]
+    AtomicTransactionComposer(#[from] AtomicTransactionComposerError),
+}
+
+impl Error {
+    /// A stable, snake case code identifying the kind of error, e.g. for logs and metrics.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Service(e) => e.code(),
+            Self::Transaction(e) => e.code(),
+            Self::Core(e) => e.code(),
+            Self::Crypto(e) => e.code(),
+            Self::AtomicTransactionComposer(e) => e.code(),
+        }
+    }
+
+    /// Returns if retrying the request may succeed, see [ServiceError::is_retryable].
+    pub fn is_retryable(&self, idempotent: bool) -> bool {
+        matches!(self.as_service_error(), Some(e) if e.is_retryable(idempotent))
+    }
+
+    /// Returns if the requested resource doesn't exist.
+    pub fn is_not_found(&self) -> bool {
+        matches!(self.as_service_error(), Some(e) if e.is_not_found())
+    }
+
+    /// Gets the service error, possibly of an atomic transaction composer error, or none otherwise.
+    fn as_service_error(&self) -> Option<&ServiceError> {
+        match self {
+            Self::Service(e)
+            | Self::AtomicTransactionComposer(AtomicTransactionComposerError::Service(e)) => {
+                Some(e)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<ClientError> for Error {
+    fn from(error: ClientError) -> Self {
+        Error::Service(error.into())
+    }
+}
+
+#[derive(Error, Debug)]
 pub enum ServiceError {
     /// URL parse error.
     #[error("Url parsing error.")]
@@ -20,13 +82,62 @@ pub enum ServiceError {
     UnitializedToken,
     /// HTTP calls errors
     #[error("http error: {0}")]
-    Request(RequestError),
+    Request(#[source] RequestError),
+    /// The transaction was removed from the pool by the node, with the pool error as message.
+    #[error("Transaction {} was rejected: {}", txid, message)]
+    TransactionRejected { txid: String, message: String },
+    /// The transaction was not confirmed within the given number of rounds.
+    #[error("Transaction {} not confirmed after {} rounds.", txid, rounds)]
+    ConfirmationTimeout { txid: String, rounds: u64 },
+    /// Encoding or decoding errors of transactions and blocks.
+    #[error("Transaction error: {0}")]
+    Transaction(#[from] TransactionError),
     /// Internal errors (please open an [issue](https://github.com/manuelmauro/algonaut/issues)!)
     #[error("Internal error: {0}")]
     Internal(String),
 }
 
 impl ServiceError {
+    /// A stable, snake case code identifying the kind of error, e.g. for logs and metrics.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::BadUrl(_) => "bad_url",
+            Self::BadToken => "bad_token",
+            Self::BadHeader(_) => "bad_header",
+            Self::UnitializedUrl => "uninitialized_url",
+            Self::UnitializedToken => "uninitialized_token",
+            Self::Request(e) => match &e.details {
+                RequestErrorDetails::Http { status: 404, .. } => "not_found",
+                RequestErrorDetails::Http { .. } if self.rejection().is_some() => {
+                    "transaction_rejected"
+                }
+                RequestErrorDetails::Http { .. } => "http",
+                RequestErrorDetails::Timeout => "timeout",
+                RequestErrorDetails::Connection { .. } => "connection",
+                RequestErrorDetails::Client { .. } => "client",
+            },
+            Self::TransactionRejected { .. } => "transaction_rejected",
+            Self::ConfirmationTimeout { .. } => "confirmation_timeout",
+            Self::Transaction(e) => e.code(),
+            Self::Internal(_) => "internal",
+        }
+    }
+
+    /// Returns if retrying the request may succeed, as the default [RetryPolicy] classifies it:
+    /// after a connection error and, if the request is idempotent (unlike broadcasting a
+    /// transaction), after a timeout, rate limiting or the unavailability of the server.
+    pub fn is_retryable(&self, idempotent: bool) -> bool {
+        match self.as_request_error() {
+            Some(e) => RetryPolicy::default().is_retryable(&(&e.details).into(), idempotent),
+            None => false,
+        }
+    }
+
+    /// Returns if the requested resource doesn't exist (a 404 response).
+    pub fn is_not_found(&self) -> bool {
+        self.is_404()
+    }
+
     /// Returns if the error is a `RequestError` that failed with a status code of 404.
     pub fn is_404(&self) -> bool {
         if let Some(e) = self.as_request_error() {
@@ -36,6 +147,23 @@ impl ServiceError {
         }
     }
 
+    /// Returns why the node rejected a transaction, if the error is a rejection of a broadcast
+    /// (or a removal from the pool) with a known message.
+    pub fn rejection(&self) -> Option<TransactionRejection> {
+        match self {
+            Self::TransactionRejected { message, .. } => TransactionRejection::parse(message),
+            Self::Request(RequestError {
+                details:
+                    RequestErrorDetails::Http {
+                        status: 400,
+                        message,
+                    },
+                ..
+            }) => TransactionRejection::parse(message),
+            _ => None,
+        }
+    }
+
     /// Gets the details of a request error, or none otherwise.
     fn as_request_error(&self) -> Option<&RequestError> {
         match self {
@@ -71,6 +199,9 @@ pub enum RequestErrorDetails {
     /// Timeout
     #[error("Timeout connecting to the server.")]
     Timeout,
+    /// The request couldn't be sent, e.g. the server is unreachable or closed the connection
+    #[error("Connection error: {}", description)]
+    Connection { description: String },
     /// Client generated errors (while e.g. building request or decoding response)
     #[error("Client error: {}", description)]
     Client { description: String },
@@ -112,6 +243,9 @@ impl From<algonaut_client::error::RequestErrorDetails> for RequestErrorDetails {
                 RequestErrorDetails::Http { status, message }
             }
             algonaut_client::error::RequestErrorDetails::Timeout => RequestErrorDetails::Timeout {},
+            algonaut_client::error::RequestErrorDetails::Connection { description } => {
+                RequestErrorDetails::Connection { description }
+            }
             algonaut_client::error::RequestErrorDetails::Client { description } => {
                 RequestErrorDetails::Client { description }
             }
@@ -119,9 +253,39 @@ impl From<algonaut_client::error::RequestErrorDetails> for RequestErrorDetails {
     }
 }
 
+impl From<&RequestErrorDetails> for algonaut_client::error::RequestErrorDetails {
+    fn from(details: &RequestErrorDetails) -> Self {
+        match details {
+            RequestErrorDetails::Http { status, message } => {
+                algonaut_client::error::RequestErrorDetails::Http {
+                    status: *status,
+                    message: message.clone(),
+                }
+            }
+            RequestErrorDetails::Timeout => algonaut_client::error::RequestErrorDetails::Timeout,
+            RequestErrorDetails::Connection { description } => {
+                algonaut_client::error::RequestErrorDetails::Connection {
+                    description: description.clone(),
+                }
+            }
+            RequestErrorDetails::Client { description } => {
+                algonaut_client::error::RequestErrorDetails::Client {
+                    description: description.clone(),
+                }
+            }
+        }
+    }
+}
+
 impl From<rmp_serde::encode::Error> for ServiceError {
     fn from(error: rmp_serde::encode::Error) -> Self {
-        ServiceError::Internal(error.to_string())
+        ServiceError::Transaction(error.into())
+    }
+}
+
+impl From<rmp_serde::decode::Error> for ServiceError {
+    fn from(error: rmp_serde::decode::Error) -> Self {
+        ServiceError::Transaction(error.into())
     }
 }
 
@@ -131,6 +295,104 @@ impl From<String> for ServiceError {
     }
 }
 
+#[test]
+fn check_rejection() {
+    let ledger_error = ServiceError::Request(RequestError::new(
+        None,
+        RequestErrorDetails::Http {
+            status: 400,
+            message: "TransactionPool.Remember: transaction already in ledger: ABC".to_owned(),
+        },
+    ));
+    let pool_error = ServiceError::TransactionRejected {
+        txid: "ABC".to_owned(),
+        message: "txn dead: round 20 outside of 5--10".to_owned(),
+    };
+
+    assert_eq!(
+        Some(TransactionRejection::AlreadyInLedger {
+            txid: "ABC".to_owned()
+        }),
+        ledger_error.rejection()
+    );
+    assert!(matches!(
+        pool_error.rejection(),
+        Some(TransactionRejection::Dead { .. })
+    ));
+    assert_eq!(None, ServiceError::UnitializedToken.rejection());
+}
+
+#[test]
+fn check_codes() {
+    use std::error::Error as _;
+
+    let error = |details| Error::Service(ServiceError::Request(RequestError::new(None, details)));
+    let unavailable = error(RequestErrorDetails::Http {
+        status: 503,
+        message: "unavailable".to_owned(),
+    });
+    let not_found = error(RequestErrorDetails::Http {
+        status: 404,
+        message: "not found".to_owned(),
+    });
+    let rejected = error(RequestErrorDetails::Http {
+        status: 400,
+        message: "txn dead: round 20 outside of 5--10".to_owned(),
+    });
+    let timeout: Error = ClientError::Request(algonaut_client::error::RequestError::new(
+        None,
+        algonaut_client::error::RequestErrorDetails::Timeout,
+    ))
+    .into();
+    let connection = error(RequestErrorDetails::Connection {
+        description: "connection reset".to_owned(),
+    });
+    let encoding: Error = ServiceError::from(rmp_serde::decode::Error::LengthMismatch(1)).into();
+
+    assert_eq!("http", unavailable.code());
+    assert!(unavailable.is_retryable(true));
+    assert!(!unavailable.is_retryable(false));
+    assert!(!unavailable.is_not_found());
+    assert_eq!("not_found", not_found.code());
+    assert!(!not_found.is_retryable(true));
+    assert!(not_found.is_not_found());
+    assert_eq!("transaction_rejected", rejected.code());
+    assert!(!rejected.is_retryable(true));
+    assert_eq!("timeout", timeout.code());
+    assert!(timeout.is_retryable(true));
+    assert!(!timeout.is_retryable(false));
+    assert_eq!("connection", connection.code());
+    assert!(connection.is_retryable(false));
+    assert_eq!("decoding", encoding.code());
+    assert!(!encoding.is_retryable(true));
+    assert_eq!(
+        "invalid_checksum",
+        Error::from(CryptoError::InvalidChecksum).code()
+    );
+    assert_eq!(
+        "transaction_index_out_of_bounds",
+        Error::from(TransactionError::TransactionIndexOutOfBounds { index: 2 }).code()
+    );
+    assert_eq!(
+        "bad_token",
+        Error::from(AtomicTransactionComposerError::Service(
+            ServiceError::BadToken
+        ))
+        .code()
+    );
+    assert_eq!(
+        "group_id_already_set",
+        Error::from(AtomicTransactionComposerError::GroupIdAlreadySet).code()
+    );
+
+    // the sources are chained up to the error of the crate
+    let source = encoding.source().unwrap().source().unwrap();
+    assert!(matches!(
+        source.downcast_ref::<TransactionError>(),
+        Some(TransactionError::RmpSerdeDecodeError(_))
+    ));
+}
+
 #[test]
 fn check_404() {
     let not_found_error = ServiceError::Request(RequestError::new(
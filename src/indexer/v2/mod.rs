@@ -1,15 +1,22 @@
-use algonaut_client::{indexer::v2::Client, Headers};
+use algonaut_client::{indexer::v2::Client, retry::RetryPolicy, transport::HttpTransport, Headers};
 use algonaut_core::{Address, Round};
 use algonaut_model::indexer::v2::{
-    AccountInfoResponse, AccountResponse, AccountTransactionResponse, ApplicationInfoResponse,
-    ApplicationResponse, AssetResponse, AssetTransactionResponse, AssetsInfoResponse,
-    BalancesResponse, Block, QueryAccount, QueryAccountInfo, QueryAccountTransaction,
-    QueryApplicationInfo, QueryApplications, QueryAssetTransaction, QueryAssets, QueryAssetsInfo,
-    QueryBalances, QueryTransaction, TransactionInfoResponse, TransactionResponse,
+    Account, AccountInfoResponse, AccountResponse, AccountTransactionResponse, Application,
+    ApplicationBoxesResponse, ApplicationInfoResponse, ApplicationResponse, Asset, AssetResponse,
+    AssetTransactionResponse, AssetsInfoResponse, BalancesResponse, Block, BoxDescriptor, BoxValue,
+    MiniAssetHolding, QueryAccount, QueryAccountInfo, QueryAccountTransaction,
+    QueryApplicationBoxes, QueryApplicationInfo, QueryApplications, QueryAssetTransaction,
+    QueryAssets, QueryAssetsInfo, QueryBalances, QueryTransaction, Transaction,
+    TransactionInfoResponse, TransactionResponse,
 };
+use std::sync::Arc;
 
 use crate::error::ServiceError;
 
+mod pagination;
+use pagination::paginate;
+pub use pagination::PageStream;
+
 #[derive(Debug)]
 pub struct Indexer {
     pub(super) client: Client,
@@ -33,6 +40,31 @@ impl Indexer {
         })
     }
 
+    /// Build a v2 client for Algorand's indexer.
+    /// Use this initializer to send the requests with a custom transport, e.g. to record and
+    /// replay them in tests or to route them through a proxy.
+    ///
+    /// Returns an error if the url or headers have an invalid format.
+    pub fn with_transport(
+        url: &str,
+        headers: Headers,
+        transport: Arc<dyn HttpTransport>,
+    ) -> Result<Indexer, ServiceError> {
+        Ok(Indexer {
+            client: Client::with_transport(url, headers, transport)?,
+        })
+    }
+
+    /// Retries the failed requests as allowed by the policy: by default they aren't retried.
+    ///
+    /// Only connection errors are retried for requests which aren't idempotent, e.g. broadcasting
+    /// a transaction.
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Indexer {
+        Indexer {
+            client: self.client.with_retry_policy(retry_policy),
+        }
+    }
+
     /// Returns Ok if healthy
     pub async fn health(&self) -> Result<(), ServiceError> {
         Ok(self.client.health().await?)
@@ -43,6 +75,19 @@ impl Indexer {
         Ok(self.client.accounts(query).await?)
     }
 
+    /// Search for accounts, following the pages of results.
+    ///
+    /// Yields at most `max_items` accounts, if set.
+    pub fn accounts_stream(
+        &self,
+        query: &QueryAccount,
+        max_items: Option<usize>,
+    ) -> PageStream<'_, Account> {
+        paginate(query.clone(), max_items, move |query| async move {
+            self.accounts(&query).await
+        })
+    }
+
     /// Lookup account information.
     pub async fn account_info(
         &self,
@@ -61,6 +106,21 @@ impl Indexer {
         Ok(self.client.account_transactions(address, query).await?)
     }
 
+    /// Lookup account transactions, following the pages of results.
+    ///
+    /// Yields at most `max_items` transactions, if set.
+    pub fn account_transactions_stream(
+        &self,
+        address: &Address,
+        query: &QueryAccountTransaction,
+        max_items: Option<usize>,
+    ) -> PageStream<'_, Transaction> {
+        let address = *address;
+        paginate(query.clone(), max_items, move |query| async move {
+            self.account_transactions(&address, &query).await
+        })
+    }
+
     /// Search for applications
     pub async fn applications(
         &self,
@@ -69,6 +129,19 @@ impl Indexer {
         Ok(self.client.applications(query).await?)
     }
 
+    /// Search for applications, following the pages of results.
+    ///
+    /// Yields at most `max_items` applications, if set.
+    pub fn applications_stream(
+        &self,
+        query: &QueryApplications,
+        max_items: Option<usize>,
+    ) -> PageStream<'_, Application> {
+        paginate(query.clone(), max_items, move |query| async move {
+            self.applications(&query).await
+        })
+    }
+
     /// Lookup application.
     pub async fn application_info(
         &self,
@@ -78,11 +151,56 @@ impl Indexer {
         Ok(self.client.application_info(id, query).await?)
     }
 
+    /// Get the box names of an application.
+    pub async fn application_boxes(
+        &self,
+        id: u64,
+        query: &QueryApplicationBoxes,
+    ) -> Result<ApplicationBoxesResponse, ServiceError> {
+        Ok(self.client.application_boxes(id, query).await?)
+    }
+
+    /// Get the box names of an application, following the pages of results.
+    ///
+    /// Yields at most `max_items` box names, if set.
+    pub fn application_boxes_stream(
+        &self,
+        id: u64,
+        query: &QueryApplicationBoxes,
+        max_items: Option<usize>,
+    ) -> PageStream<'_, BoxDescriptor> {
+        paginate(query.clone(), max_items, move |query| async move {
+            self.application_boxes(id, &query).await
+        })
+    }
+
+    /// Get an application box by name.
+    pub async fn application_box_by_name(
+        &self,
+        id: u64,
+        name: &[u8],
+    ) -> Result<BoxValue, ServiceError> {
+        Ok(self.client.application_box_by_name(id, name).await?)
+    }
+
     /// Search for assets.
     pub async fn assets(&self, query: &QueryAssets) -> Result<AssetResponse, ServiceError> {
         Ok(self.client.assets(query).await?)
     }
 
+    /// Search for assets, following the pages of results.
+    ///
+    /// Yields at most `max_items` assets, if set.
+    pub fn assets_stream(
+        &self,
+        query: &QueryAssets,
+        max_items: Option<usize>,
+    ) -> PageStream<'_, Asset> {
+        paginate(query.clone(), max_items, move |query| async move {
+            self.assets(&query).await
+        })
+    }
+
     /// Lookup asset information.
     pub async fn assets_info(
         &self,
@@ -101,6 +219,20 @@ impl Indexer {
         Ok(self.client.asset_balances(id, query).await?)
     }
 
+    /// Lookup the list of accounts who hold this asset, following the pages of results.
+    ///
+    /// Yields at most `max_items` balances, if set.
+    pub fn asset_balances_stream(
+        &self,
+        id: u64,
+        query: &QueryBalances,
+        max_items: Option<usize>,
+    ) -> PageStream<'_, MiniAssetHolding> {
+        paginate(query.clone(), max_items, move |query| async move {
+            self.asset_balances(id, &query).await
+        })
+    }
+
     /// Lookup transactions for an asset.
     pub async fn asset_transactions(
         &self,
@@ -110,6 +242,20 @@ impl Indexer {
         Ok(self.client.asset_transactions(id, query).await?)
     }
 
+    /// Lookup transactions for an asset, following the pages of results.
+    ///
+    /// Yields at most `max_items` transactions, if set.
+    pub fn asset_transactions_stream(
+        &self,
+        id: u64,
+        query: &QueryAssetTransaction,
+        max_items: Option<usize>,
+    ) -> PageStream<'_, Transaction> {
+        paginate(query.clone(), max_items, move |query| async move {
+            self.asset_transactions(id, &query).await
+        })
+    }
+
     /// Lookup block.
     pub async fn block(&self, round: Round) -> Result<Block, ServiceError> {
         Ok(self.client.block(round).await?)
@@ -123,6 +269,19 @@ impl Indexer {
         Ok(self.client.transactions(query).await?)
     }
 
+    /// Search for transactions, following the pages of results.
+    ///
+    /// Yields at most `max_items` transactions, if set.
+    pub fn transactions_stream(
+        &self,
+        query: &QueryTransaction,
+        max_items: Option<usize>,
+    ) -> PageStream<'_, Transaction> {
+        paginate(query.clone(), max_items, move |query| async move {
+            self.transactions(&query).await
+        })
+    }
+
     /// Search for transactions.
     pub async fn transaction_info(
         &self,
@@ -0,0 +1,338 @@
+use crate::error::ServiceError;
+use algonaut_model::indexer::v2::{
+    Account, AccountResponse, AccountTransactionResponse, Application, ApplicationBoxesResponse,
+    ApplicationResponse, Asset, AssetResponse, AssetTransactionResponse, BalancesResponse,
+    BoxDescriptor, MiniAssetHolding, QueryAccount, QueryAccountTransaction, QueryApplicationBoxes,
+    QueryApplications, QueryAssetTransaction, QueryAssets, QueryBalances, QueryTransaction,
+    Transaction, TransactionResponse,
+};
+use futures::stream::{self, StreamExt};
+use std::collections::VecDeque;
+use std::future::Future;
+
+/// The items of all the pages of a query, in order.
+///
+/// An error is yielded as item and polling again retries the page which failed.
+#[cfg(not(target_arch = "wasm32"))]
+pub type PageStream<'a, T> = stream::BoxStream<'a, Result<T, ServiceError>>;
+/// The items of all the pages of a query, in order.
+///
+/// An error is yielded as item and polling again retries the page which failed.
+#[cfg(target_arch = "wasm32")]
+pub type PageStream<'a, T> = stream::LocalBoxStream<'a, Result<T, ServiceError>>;
+
+/// A query which can continue where the previous page ended.
+pub(crate) trait PageQuery: Clone {
+    fn set_next(&mut self, next: String);
+}
+
+/// A page of results, with the token to get the next one.
+pub(crate) trait Page {
+    type Item;
+
+    fn into_items(self) -> (Vec<Self::Item>, Option<String>);
+}
+
+/// Follows the next tokens of the pages returned by `fetch`, starting with `query`, yielding at
+/// most `max_items` items.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn paginate<'a, Q, P, F, Fut>(
+    query: Q,
+    max_items: Option<usize>,
+    fetch: F,
+) -> PageStream<'a, P::Item>
+where
+    Q: PageQuery + Send + 'a,
+    P: Page + 'a,
+    P::Item: Send + 'a,
+    F: Fn(Q) -> Fut + Send + 'a,
+    Fut: Future<Output = Result<P, ServiceError>> + Send + 'a,
+{
+    pages(query, max_items, fetch).boxed()
+}
+
+/// Follows the next tokens of the pages returned by `fetch`, starting with `query`, yielding at
+/// most `max_items` items.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn paginate<'a, Q, P, F, Fut>(
+    query: Q,
+    max_items: Option<usize>,
+    fetch: F,
+) -> PageStream<'a, P::Item>
+where
+    Q: PageQuery + 'a,
+    P: Page + 'a,
+    P::Item: 'a,
+    F: Fn(Q) -> Fut + 'a,
+    Fut: Future<Output = Result<P, ServiceError>> + 'a,
+{
+    pages(query, max_items, fetch).boxed_local()
+}
+
+fn pages<Q, P, F, Fut>(
+    query: Q,
+    max_items: Option<usize>,
+    fetch: F,
+) -> impl stream::Stream<Item = Result<P::Item, ServiceError>>
+where
+    Q: PageQuery,
+    P: Page,
+    F: Fn(Q) -> Fut,
+    Fut: Future<Output = Result<P, ServiceError>>,
+{
+    let state = PageState {
+        query: Some(query),
+        items: VecDeque::new(),
+        remaining: max_items,
+        fetch,
+    };
+    stream::unfold(state, |mut state| async move {
+        let next = state.next().await?;
+        Some((next, state))
+    })
+}
+
+struct PageState<Q, T, F> {
+    /// The query of the next page, `None` after the last page.
+    query: Option<Q>,
+    /// The items fetched and not yielded yet.
+    items: VecDeque<T>,
+    remaining: Option<usize>,
+    fetch: F,
+}
+
+impl<Q, P, F, Fut> PageState<Q, P::Item, F>
+where
+    Q: PageQuery,
+    P: Page,
+    F: Fn(Q) -> Fut,
+    Fut: Future<Output = Result<P, ServiceError>>,
+{
+    async fn next(&mut self) -> Option<Result<P::Item, ServiceError>> {
+        if self.remaining == Some(0) {
+            return None;
+        }
+        loop {
+            if let Some(item) = self.items.pop_front() {
+                self.remaining = self.remaining.map(|remaining| remaining - 1);
+                return Some(Ok(item));
+            }
+            let mut query = self.query.take()?;
+            let page = match (self.fetch)(query.clone()).await {
+                Ok(page) => page,
+                Err(e) => {
+                    self.query = Some(query);
+                    return Some(Err(e));
+                }
+            };
+            let (items, next) = page.into_items();
+            // an empty page ends the results too, even if it has a next token
+            if let Some(next) = next.filter(|next| !next.is_empty() && !items.is_empty()) {
+                query.set_next(next);
+                self.query = Some(query);
+            }
+            self.items.extend(items);
+        }
+    }
+}
+
+impl PageQuery for QueryAccount {
+    fn set_next(&mut self, next: String) {
+        self.next = Some(next);
+    }
+}
+
+impl PageQuery for QueryAccountTransaction {
+    fn set_next(&mut self, next: String) {
+        self.next = Some(next);
+    }
+}
+
+impl PageQuery for QueryApplications {
+    fn set_next(&mut self, next: String) {
+        self.next = Some(next);
+    }
+}
+
+impl PageQuery for QueryApplicationBoxes {
+    fn set_next(&mut self, next: String) {
+        self.next = Some(next);
+    }
+}
+
+impl PageQuery for QueryAssets {
+    fn set_next(&mut self, next: String) {
+        self.next = Some(next);
+    }
+}
+
+impl PageQuery for QueryBalances {
+    fn set_next(&mut self, next: String) {
+        self.next = Some(next);
+    }
+}
+
+impl PageQuery for QueryAssetTransaction {
+    fn set_next(&mut self, next: String) {
+        self.next = Some(next);
+    }
+}
+
+impl PageQuery for QueryTransaction {
+    fn set_next(&mut self, next: String) {
+        self.next = Some(next);
+    }
+}
+
+impl Page for AccountResponse {
+    type Item = Account;
+
+    fn into_items(self) -> (Vec<Account>, Option<String>) {
+        (self.accounts, self.next_token)
+    }
+}
+
+impl Page for AccountTransactionResponse {
+    type Item = Transaction;
+
+    fn into_items(self) -> (Vec<Transaction>, Option<String>) {
+        (self.transactions, self.next_token)
+    }
+}
+
+impl Page for ApplicationResponse {
+    type Item = Application;
+
+    fn into_items(self) -> (Vec<Application>, Option<String>) {
+        (self.applications, self.next_token)
+    }
+}
+
+impl Page for ApplicationBoxesResponse {
+    type Item = BoxDescriptor;
+
+    fn into_items(self) -> (Vec<BoxDescriptor>, Option<String>) {
+        (self.boxes, self.next_token)
+    }
+}
+
+impl Page for AssetResponse {
+    type Item = Asset;
+
+    fn into_items(self) -> (Vec<Asset>, Option<String>) {
+        (self.assets, self.next_token)
+    }
+}
+
+impl Page for BalancesResponse {
+    type Item = MiniAssetHolding;
+
+    fn into_items(self) -> (Vec<MiniAssetHolding>, Option<String>) {
+        (self.balances, self.next_token)
+    }
+}
+
+impl Page for AssetTransactionResponse {
+    type Item = Transaction;
+
+    fn into_items(self) -> (Vec<Transaction>, Option<String>) {
+        (self.transactions, self.next_token)
+    }
+}
+
+impl Page for TransactionResponse {
+    type Item = Transaction;
+
+    fn into_items(self) -> (Vec<Transaction>, Option<String>) {
+        (self.transactions, self.next_token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::TryStreamExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn response(boxes: usize, next_token: Option<&str>) -> ApplicationBoxesResponse {
+        ApplicationBoxesResponse {
+            application_id: 1,
+            boxes: vec![BoxDescriptor { name: vec![1] }; boxes],
+            next_token: next_token.map(|t| t.to_owned()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_follow_next_tokens() {
+        let queries = std::sync::Mutex::new(vec![]);
+        let boxes: Vec<BoxDescriptor> = paginate(
+            QueryApplicationBoxes::default(),
+            None,
+            |q: QueryApplicationBoxes| {
+                queries.lock().unwrap().push(q.next.clone());
+                async move {
+                    Ok(match q.next.as_deref() {
+                        None => response(2, Some("a")),
+                        Some("a") => response(2, Some("b")),
+                        _ => response(1, None),
+                    })
+                }
+            },
+        )
+        .try_collect()
+        .await
+        .unwrap();
+        assert_eq!(5, boxes.len());
+        assert_eq!(
+            vec![None, Some("a".to_owned()), Some("b".to_owned())],
+            *queries.lock().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_max_items_and_empty_page() {
+        let fetches = AtomicUsize::new(0);
+        let fetch = |_: QueryApplicationBoxes| {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            async { Ok(response(2, Some("a"))) }
+        };
+        let boxes: Vec<BoxDescriptor> = paginate(QueryApplicationBoxes::default(), Some(3), fetch)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(3, boxes.len());
+        assert_eq!(2, fetches.load(Ordering::SeqCst));
+
+        let boxes: Vec<BoxDescriptor> = paginate(
+            QueryApplicationBoxes::default(),
+            None,
+            |_: QueryApplicationBoxes| async { Ok(response(0, Some("a"))) },
+        )
+        .try_collect()
+        .await
+        .unwrap();
+        assert!(boxes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_error() {
+        let fetches = AtomicUsize::new(0);
+        let mut stream = paginate(
+            QueryApplicationBoxes::default(),
+            None,
+            |_: QueryApplicationBoxes| {
+                let failed = fetches.fetch_add(1, Ordering::SeqCst) == 0;
+                async move {
+                    if failed {
+                        Err(ServiceError::Internal("unavailable".to_owned()))
+                    } else {
+                        Ok(response(1, None))
+                    }
+                }
+            },
+        );
+        assert!(stream.next().await.unwrap().is_err());
+        assert!(stream.next().await.unwrap().is_ok());
+        assert!(stream.next().await.is_none());
+    }
+}
@@ -1,4 +1,6 @@
-use algonaut_client::{kmd::v1::Client, token::ApiToken, Headers};
+use algonaut_client::{
+    kmd::v1::Client, retry::RetryPolicy, token::ApiToken, transport::HttpTransport, Headers,
+};
 use algonaut_core::{Address, MultisigSignature, ToMsgPack};
 use algonaut_crypto::{Ed25519PublicKey, MasterDerivationKey};
 use algonaut_model::kmd::v1::{
@@ -9,7 +11,11 @@ use algonaut_model::kmd::v1::{
     RenameWalletResponse, RenewWalletHandleResponse, SignMultisigTransactionResponse,
     SignTransactionResponse, VersionsResponse,
 };
-use algonaut_transaction::Transaction;
+use algonaut_transaction::{
+    error::TransactionError, signer::TransactionSigner, SignedTransaction, Transaction,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
 
 use crate::error::ServiceError;
 
@@ -39,6 +45,31 @@ impl Kmd {
         })
     }
 
+    /// Build a v1 client for the Algorand key management daemon.
+    /// Use this initializer to send the requests with a custom transport, e.g. to record and
+    /// replay them in tests or to route them through a proxy.
+    ///
+    /// Returns an error if the url or headers have an invalid format.
+    pub fn with_transport(
+        url: &str,
+        headers: Headers,
+        transport: Arc<dyn HttpTransport>,
+    ) -> Result<Kmd, ServiceError> {
+        Ok(Kmd {
+            client: Client::with_transport(url, headers, transport)?,
+        })
+    }
+
+    /// Retries the failed requests as allowed by the policy: by default they aren't retried.
+    ///
+    /// Only connection errors are retried for requests which aren't idempotent, e.g. broadcasting
+    /// a transaction.
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Kmd {
+        Kmd {
+            client: self.client.with_retry_policy(retry_policy),
+        }
+    }
+
     /// Retrieves the current version
     pub async fn versions(&self) -> Result<VersionsResponse, ServiceError> {
         Ok(self.client.versions().await?)
@@ -265,9 +296,65 @@ impl Kmd {
     }
 }
 
+/// Signs transactions with the keys of a KMD wallet, using an initialized wallet handle.
+#[derive(Debug)]
+pub struct KmdWalletSigner {
+    kmd: Kmd,
+    wallet_handle: String,
+    wallet_password: String,
+}
+
+impl KmdWalletSigner {
+    pub fn new(kmd: Kmd, wallet_handle: &str, wallet_password: &str) -> KmdWalletSigner {
+        KmdWalletSigner {
+            kmd,
+            wallet_handle: wallet_handle.to_owned(),
+            wallet_password: wallet_password.to_owned(),
+        }
+    }
+
+    /// The client used to sign, e.g. to renew or release the wallet handle.
+    pub fn kmd(&self) -> &Kmd {
+        &self.kmd
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl TransactionSigner for KmdWalletSigner {
+    async fn sign_transactions(
+        &self,
+        txns: &[Transaction],
+        indexes: &[usize],
+    ) -> Result<Vec<SignedTransaction>, TransactionError> {
+        let mut signed_txns = vec![];
+        for i in indexes {
+            let txn = txns
+                .get(*i)
+                .ok_or(TransactionError::TransactionIndexOutOfBounds { index: *i })?;
+            let response = self
+                .kmd
+                .sign_transaction(&self.wallet_handle, &self.wallet_password, txn)
+                .await
+                .map_err(|e| TransactionError::Signing(e.to_string()))?;
+            signed_txns.push(SignedTransaction::from_msg_pack(
+                &response.signed_transaction,
+            )?);
+        }
+        Ok(signed_txns)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use algonaut_client::error::ClientError;
+    use algonaut_client::transport::{HttpRequest, HttpResponse};
+    use algonaut_core::{MicroAlgos, Round, SuggestedTransactionParams};
+    use algonaut_crypto::HashDigest;
+    use algonaut_transaction::{account::Account, Pay, TxnBuilder};
+    use data_encoding::BASE64;
+    use std::sync::Mutex;
 
     #[test]
     fn test_valid_client_creation() {
@@ -293,4 +380,88 @@ mod tests {
         )
         .unwrap();
     }
+
+    /// A KMD wallet with the key of the account, signing the transactions it knows.
+    #[derive(Debug)]
+    struct Wallet {
+        account: Account,
+        txns: Vec<Transaction>,
+        requests: Mutex<Vec<HttpRequest>>,
+    }
+
+    #[async_trait]
+    impl HttpTransport for Wallet {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse, ClientError> {
+            let body = String::from_utf8(request.body.clone()).unwrap();
+            self.requests.lock().unwrap().push(request);
+            let txn = self
+                .txns
+                .iter()
+                .find(|t| body.contains(&BASE64.encode(&t.to_msg_pack().unwrap())))
+                .unwrap();
+            let signed = self.account.sign_transaction(txn.clone()).unwrap();
+            Ok(HttpResponse {
+                status: 200,
+                headers: vec![],
+                body: format!(
+                    r#"{{"signed_transaction":"{}"}}"#,
+                    BASE64.encode(&signed.to_msg_pack().unwrap())
+                )
+                .into_bytes(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wallet_signer() {
+        let account = Account::generate();
+        let params = SuggestedTransactionParams {
+            genesis_id: "testnet-v1.0".to_owned(),
+            genesis_hash: HashDigest([1; 32]),
+            consensus_version: "".to_owned(),
+            fee_per_byte: MicroAlgos(0),
+            min_fee: MicroAlgos(1000),
+            first_valid: Round(1),
+            last_valid: Round(1001),
+        };
+        let txns: Vec<Transaction> = (1..=2)
+            .map(|amount| {
+                TxnBuilder::with(
+                    &params,
+                    Pay::new(account.address(), account.address(), MicroAlgos(amount)).build(),
+                )
+                .build()
+                .unwrap()
+            })
+            .collect();
+        let wallet = Arc::new(Wallet {
+            account: Account::from_seed(account.seed()),
+            txns: txns.clone(),
+            requests: Mutex::new(vec![]),
+        });
+        let kmd = Kmd::with_transport("http://localhost:4002", vec![], wallet.clone()).unwrap();
+        let signer = KmdWalletSigner::new(kmd, "handle", "password");
+
+        let signed = signer.sign_transactions(&txns, &[1, 0]).await.unwrap();
+        assert_eq!(
+            vec![
+                account.sign_transaction(txns[1].clone()).unwrap(),
+                account.sign_transaction(txns[0].clone()).unwrap(),
+            ],
+            signed
+        );
+        {
+            let requests = wallet.requests.lock().unwrap();
+            assert_eq!(2, requests.len());
+            assert_eq!("http://localhost:4002/v1/transaction/sign", requests[0].url);
+            let body = String::from_utf8(requests[0].body.clone()).unwrap();
+            assert!(body.contains(r#""wallet_handle_token":"handle""#));
+            assert!(body.contains(r#""wallet_password":"password""#));
+        }
+
+        assert!(matches!(
+            signer.sign_transactions(&txns, &[2]).await,
+            Err(TransactionError::TransactionIndexOutOfBounds { index: 2 })
+        ));
+    }
 }
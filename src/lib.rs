@@ -6,12 +6,16 @@
 
 // Re-exports
 
+pub use algonaut_client as client;
 pub use algonaut_core as core;
 pub use algonaut_crypto as crypto;
 pub use algonaut_model as model;
 pub use algonaut_transaction as transaction;
 
 pub mod algod;
+pub mod atomic_transaction_composer;
 pub mod error;
 pub mod indexer;
 pub mod kmd;
+
+pub use error::Error;
@@ -1,5 +1,5 @@
 use cucumber::WorldInit;
-use step_defs::integration;
+use step_defs::{integration, unit};
 
 mod step_defs;
 
@@ -10,7 +10,15 @@ async fn main() {
 
     // algod feature: omitted (algod v1)
 
-    // TODO abi feature: ABI not supported yet
+    // abi feature: only the method scenarios, the json and composer steps aren't defined yet
+    unit::abi::World::cucumber()
+        .filter_run(unit_path("abi"), |_, _, scenario| {
+            scenario
+                .steps
+                .iter()
+                .all(|step| unit::abi::STEPS.iter().any(|s| step.value.starts_with(s)))
+        })
+        .await;
 
     integration::applications::World::cucumber()
         .max_concurrent_scenarios(1)
@@ -20,6 +28,10 @@ async fn main() {
     // assets feature: omitted (algod v1)
 }
 
+fn unit_path(feature_name: &str) -> String {
+    format!("tests/features/unit/{}.feature", feature_name)
+}
+
 fn integration_path(feature_name: &str) -> String {
     format!("tests/features/integration/{}.feature", feature_name)
 }
@@ -1,2 +1,3 @@
 pub mod integration;
+pub mod unit;
 mod util;
@@ -0,0 +1,51 @@
+use algonaut_transaction::abi::Method;
+use async_trait::async_trait;
+use cucumber::{given, then, when, WorldInit};
+use data_encoding::HEXLOWER;
+use std::convert::Infallible;
+use std::error::Error;
+
+/// Steps of the scenarios which only need the ABI types and methods.
+pub const STEPS: &[&str] = &[
+    "I create the Method object from method signature",
+    "the txn count should be",
+    "the method selector should be",
+];
+
+#[derive(Default, Debug, WorldInit)]
+pub struct World {
+    method: Option<Method>,
+}
+
+#[async_trait(?Send)]
+impl cucumber::World for World {
+    type Error = Infallible;
+
+    async fn new() -> Result<Self, Self::Error> {
+        Ok(Self::default())
+    }
+}
+
+#[given(expr = "I create the Method object from method signature {string}")]
+#[when(expr = "I create the Method object from method signature {string}")]
+async fn i_create_the_method_object_from_method_signature(
+    w: &mut World,
+    signature: String,
+) -> Result<(), Box<dyn Error>> {
+    w.method = Some(Method::from_signature(&signature)?);
+    Ok(())
+}
+
+#[then(expr = "the txn count should be {int}")]
+async fn the_txn_count_should_be(w: &mut World, count: usize) {
+    let method = w.method.as_ref().unwrap();
+
+    assert_eq!(count, method.transaction_arg_count());
+}
+
+#[then(expr = "the method selector should be {string}")]
+async fn the_method_selector_should_be(w: &mut World, selector: String) {
+    let method = w.method.as_ref().unwrap();
+
+    assert_eq!(selector, HEXLOWER.encode(&method.selector()));
+}
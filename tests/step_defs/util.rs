@@ -5,9 +5,12 @@ use std::{
     time::{Duration, Instant},
 };
 
-use algonaut::{algod::v2::Algod, error::ServiceError};
+use algonaut::{
+    algod::v2::{Algod, PendingTransaction},
+    error::ServiceError,
+};
 use algonaut_core::Address;
-use algonaut_model::{algod::v2::PendingTransaction, kmd::v1::ExportKeyResponse};
+use algonaut_model::kmd::v1::ExportKeyResponse;
 use algonaut_transaction::account::Account;
 
 /// Utility function to wait on a transaction to be confirmed